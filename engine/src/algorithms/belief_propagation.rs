@@ -0,0 +1,381 @@
+// src/algorithms/belief_propagation.rs
+use crate::board::Board;
+use crate::algorithms::{probability, Algorithm, SolverResult};
+use crate::algorithms::probability::{LinearConstraint, LogBinomial, ProbabilityMap};
+use std::collections::HashMap;
+
+/// sweeps before giving up on convergence
+const MAX_ITERATIONS: usize = 100;
+/// largest message change that still counts as converged
+const TOLERANCE: f64 = 1e-4;
+/// share of the old message kept each sweep (loopy graphs oscillate without it)
+const DAMPING: f64 = 0.5;
+/// floor for entries that are possible but underflowed, so 0.0 always means impossible
+const TINY: f64 = 1e-300;
+
+/// [p(safe), p(mine)], normalised
+type Message = [f64; 2];
+
+/// loopy belief propagation solver
+/// every revealed number is a factor "k of these cells are mines" over its hidden neighbours,
+/// plus one factor for the mine count over the whole frontier (weighted by the ways the interior
+/// can take the rest). messages are passed until they settle, which costs time linear in the
+/// frontier per sweep. clues contained in another clue are subtracted from it first: the pair
+/// would otherwise count the same evidence twice and bp locks onto one side of it.
+/// a mine belief of exactly 0 can only come from a clue that rules the mine out, so those
+/// cells are safe moves; otherwise the lowest approximate marginal is guessed
+#[allow(dead_code)]
+pub struct BeliefPropagationSolver {
+    width: usize,
+    height: usize,
+    mines: usize,
+}
+
+impl BeliefPropagationSolver {
+    pub fn new(width: usize, height: usize, mines: usize) -> Self {
+        Self { width, height, mines }
+    }
+}
+
+/// product that never underflows to 0 when both factors are possible
+fn mul(a: f64, b: f64) -> f64 {
+    let p = a * b;
+    if p == 0.0 && a > 0.0 && b > 0.0 { TINY } else { p }
+}
+
+fn combine(a: Message, b: Message) -> Message {
+    normalize([mul(a[0], b[0]), mul(a[1], b[1])])
+}
+
+fn normalize(m: Message) -> Message {
+    let s = m[0] + m[1];
+    if s <= 0.0 { return [0.5, 0.5]; } // contradicting clues
+    m.map(|x| if x > 0.0 { (x / s).max(TINY) } else { 0.0 })
+}
+
+/// adds one cell to a count distribution (entry j = weight of j mines), truncated to `cap` + 1 entries
+fn push_count(dist: &[f64], m: Message, cap: usize) -> Vec<f64> {
+    let len = (dist.len() + 1).min(cap + 1);
+    let mut out = vec![0.0; len];
+    for (j, o) in out.iter_mut().enumerate() {
+        let stay = dist.get(j).map_or(0.0, |&d| mul(d, m[0]));
+        let add = if j > 0 { dist.get(j - 1).map_or(0.0, |&d| mul(d, m[1])) } else { 0.0 };
+        *o = stay + add;
+    }
+    // rescale, only ratios matter
+    let max = out.iter().copied().fold(0.0, f64::max);
+    if max > 0.0 {
+        for o in &mut out { if *o > 0.0 { *o = (*o / max).max(TINY); } }
+    }
+    out
+}
+
+/// messages from the factor "exactly `mines` of these cells" to each of its cells
+fn factor_messages(incoming: &[Message], mines: usize) -> Vec<Message> {
+    let n = incoming.len();
+    // prefix[i] = count distribution of cells 0..i, suffix[i] = of cells i..n
+    let mut prefix = vec![vec![1.0]];
+    for &m in incoming {
+        let next = push_count(prefix.last().unwrap(), m, mines);
+        prefix.push(next);
+    }
+    let mut suffix = vec![vec![1.0]; n + 1];
+    for i in (0..n).rev() {
+        suffix[i] = push_count(&suffix[i + 1], incoming[i], mines);
+    }
+
+    (0..n).map(|i| {
+        // the other cells must hold mines - x
+        let others = |target: usize| -> f64 {
+            (0..=target).map(|j| {
+                let a = prefix[i].get(j).copied().unwrap_or(0.0);
+                let b = suffix[i + 1].get(target - j).copied().unwrap_or(0.0);
+                mul(a, b)
+            }).sum()
+        };
+        let safe = others(mines);
+        let mine = if mines > 0 { others(mines - 1) } else { 0.0 };
+        normalize([safe, mine])
+    }).collect()
+}
+
+/// messages from the mine count factor: weight[m] for m mines on the whole frontier.
+/// back[i][a] = weighted completions of cells i.. given a mines before them, so each message
+/// is one dot product with the count distribution of the cells before it
+fn count_messages(incoming: &[Message], weight: &[f64]) -> Vec<Message> {
+    let cap = weight.len() - 1;
+    let n = incoming.len();
+    let mut back = vec![weight.to_vec(); n + 1];
+    for i in (0..n).rev() {
+        let next = &back[i + 1];
+        let mut row: Vec<f64> = (0..=cap)
+            .map(|a| mul(next[a], incoming[i][0]) + if a < cap { mul(next[a + 1], incoming[i][1]) } else { 0.0 })
+            .collect();
+        let max = row.iter().copied().fold(0.0, f64::max);
+        if max > 0.0 {
+            for r in &mut row { if *r > 0.0 { *r = (*r / max).max(TINY); } }
+        }
+        back[i] = row;
+    }
+
+    let mut prefix = vec![1.0];
+    let mut out = Vec::with_capacity(n);
+    for (i, &m) in incoming.iter().enumerate() {
+        let take = |x: usize| -> f64 {
+            prefix.iter().enumerate()
+                .map(|(a, &p)| back[i + 1].get(a + x).map_or(0.0, |&b| mul(p, b)))
+                .sum()
+        };
+        out.push(normalize([take(0), take(1)]));
+        prefix = push_count(&prefix, m, cap);
+    }
+    out
+}
+
+/// subset rule to a fixpoint: a clue inside another is subtracted from it, duplicates are dropped.
+/// only clues sharing a cell are compared
+fn reduce(mut constraints: Vec<LinearConstraint>) -> Vec<LinearConstraint> {
+    for c in &mut constraints { c.cells.sort_unstable(); }
+    let is_subset = |a: &[usize], b: &[usize]| a.iter().all(|x| b.binary_search(x).is_ok());
+    loop {
+        let mut by_cell: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (i, c) in constraints.iter().enumerate() {
+            for &cell in &c.cells { by_cell.entry(cell).or_default().push(i); }
+        }
+        let mut changed = false;
+        for b in 0..constraints.len() {
+            if constraints[b].cells.is_empty() { continue; }
+            // a clue inside b shares a cell with it
+            let mut candidates: Vec<usize> = constraints[b].cells.iter()
+                .flat_map(|cell| by_cell.get(cell).into_iter().flatten().copied())
+                .collect();
+            candidates.sort_unstable();
+            candidates.dedup();
+            for a in candidates {
+                if a == b || constraints[a].cells.is_empty() || constraints[a].cells.len() > constraints[b].cells.len() { continue; }
+                if constraints[a].cells.len() == constraints[b].cells.len() && a > b { continue; } // keep the first duplicate
+                if !is_subset(&constraints[a].cells, &constraints[b].cells) { continue; }
+                let (inner, inner_mines) = (constraints[a].cells.clone(), constraints[a].mines);
+                let outer = &mut constraints[b];
+                outer.cells.retain(|x| inner.binary_search(x).is_err());
+                outer.mines = outer.mines.saturating_sub(inner_mines);
+                changed = true;
+                if outer.cells.is_empty() { break; }
+            }
+        }
+        constraints.retain(|c| !c.cells.is_empty());
+        if !changed { return constraints; }
+    }
+}
+
+/// approximate marginals of every cell. `exact` is false and there is no layout count
+/// (`log_weight` is nan); interior cells share the mines the frontier beliefs leave over
+pub fn marginals(board: &Board) -> ProbabilityMap {
+    let constraints = reduce(probability::collect_constraints(board));
+    let mut frontier: Vec<usize> = constraints.iter().flat_map(|c| c.cells.iter().copied()).collect();
+    frontier.sort_unstable();
+    frontier.dedup();
+    let position: HashMap<usize, usize> = frontier.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+
+    let flags = board.cells.iter().filter(|c| c.is_flagged).count();
+    let remaining = board.mines.saturating_sub(flags);
+    let interior: Vec<usize> = (0..board.cells.len())
+        .filter(|&i| !board.cells[i].is_revealed && !board.cells[i].is_flagged && !position.contains_key(&i))
+        .collect();
+
+    // count factor weights C(interior, remaining - m), scaled to a maximum of 1
+    let binom = LogBinomial::new(board.cells.len());
+    let ln_weight: Vec<f64> = (0..=frontier.len().min(remaining))
+        .map(|m| binom.ln_choose(interior.len(), remaining - m))
+        .collect();
+    let max = ln_weight.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let count_weight: Vec<f64> = ln_weight.iter()
+        .map(|&w| if w.is_finite() { (w - max).exp().max(TINY) } else { 0.0 })
+        .collect();
+
+    // one edge per (clue, cell) pair
+    let mut var_edges: Vec<Vec<usize>> = vec![Vec::new(); frontier.len()];
+    let mut factor_edges: Vec<Vec<usize>> = Vec::with_capacity(constraints.len());
+    let mut num_edges = 0;
+    for c in &constraints {
+        let mut edges = Vec::with_capacity(c.cells.len());
+        for cell in &c.cells {
+            let e = num_edges;
+            num_edges += 1;
+            var_edges[position[cell]].push(e);
+            edges.push(e);
+        }
+        factor_edges.push(edges);
+    }
+
+    // the count factor owns edge `num_edges + v` of every frontier variable v
+    for (v, edges) in var_edges.iter_mut().enumerate() { edges.push(num_edges + v); }
+    let count_edges: Vec<usize> = (num_edges..num_edges + frontier.len()).collect();
+    let total_edges = num_edges + frontier.len();
+
+    let mut to_factor: Vec<Message> = vec![[0.5, 0.5]; total_edges];
+    let mut to_var: Vec<Message> = vec![[0.5, 0.5]; total_edges];
+    for _ in 0..MAX_ITERATIONS {
+        let mut delta: f64 = 0.0;
+        let mut update = |edges: &[usize], new: Vec<Message>| {
+            for (&e, new) in edges.iter().zip(new) {
+                let old = to_var[e];
+                // a newly impossible value stays impossible, damping only smooths the rest
+                let damped = normalize([0, 1].map(|x| if new[x] == 0.0 { 0.0 } else { DAMPING * old[x] + (1.0 - DAMPING) * new[x] }));
+                delta = delta.max((damped[1] - old[1]).abs());
+                to_var[e] = damped;
+            }
+        };
+        for (c, edges) in constraints.iter().zip(&factor_edges) {
+            let incoming: Vec<Message> = edges.iter().map(|&e| to_factor[e]).collect();
+            update(edges, factor_messages(&incoming, c.mines));
+        }
+        let incoming: Vec<Message> = count_edges.iter().map(|&e| to_factor[e]).collect();
+        update(&count_edges, count_messages(&incoming, &count_weight));
+
+        for edges in &var_edges {
+            // leave-one-out products through prefix / suffix products
+            let mut suffix = vec![[0.5, 0.5]; edges.len() + 1];
+            for i in (0..edges.len()).rev() {
+                suffix[i] = combine(suffix[i + 1], to_var[edges[i]]);
+            }
+            let mut prefix: Message = [1.0, 1.0];
+            for (i, &e) in edges.iter().enumerate() {
+                to_factor[e] = combine(prefix, suffix[i + 1]);
+                prefix = combine(prefix, to_var[e]);
+            }
+        }
+        if delta < TOLERANCE { break; }
+    }
+
+    let mut cell_probs = vec![0.0; board.cells.len()];
+    for (i, cell) in board.cells.iter().enumerate() {
+        if cell.is_flagged { cell_probs[i] = 1.0; }
+    }
+    let mut frontier_mines = 0.0;
+    for (v, edges) in var_edges.iter().enumerate() {
+        let belief = edges.iter().fold([0.5, 0.5], |b, &e| combine(b, to_var[e]));
+        cell_probs[frontier[v]] = belief[1];
+        frontier_mines += belief[1];
+    }
+
+    let interior_prob = if interior.is_empty() {
+        0.0
+    } else {
+        ((remaining as f64 - frontier_mines) / interior.len() as f64).clamp(0.0, 1.0)
+    };
+    for (&i, p) in interior.iter().zip(board.prior.interior_probs(board, &interior, interior_prob)) {
+        cell_probs[i] = p;
+    }
+
+    ProbabilityMap { cell_probs, frontier, interior_prob, log_weight: f64::NAN, exact: false }
+}
+
+impl Algorithm for BeliefPropagationSolver {
+    fn find_candidates(&mut self, board: &Board) -> SolverResult {
+        // agent handles first move, solver provides next logical steps
+        let map = marginals(board);
+        let safe: Vec<usize> = map.frontier.iter().copied().filter(|&c| map.cell_probs[c] == 0.0).collect();
+        if !safe.is_empty() {
+            return SolverResult { candidates: safe, is_guess: false };
+        }
+        SolverResult { candidates: map.safest_cells(board), is_guess: true }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    /// the 0/1 assignments of `n` cells (bit i = cell i) that meet every constraint
+    fn solutions(constraints: &[LinearConstraint], n: usize) -> Vec<u32> {
+        (0..1u32 << n)
+            .filter(|&bits| constraints.iter().all(|c| c.cells.iter().filter(|&&x| (bits >> x) & 1 == 1).count() == c.mines))
+            .collect()
+    }
+
+    #[test]
+    fn reduce_keeps_the_solutions_and_leaves_no_subsets() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..300 {
+            let n = rng.gen_range(2..=8);
+            let layout: Vec<bool> = (0..n).map(|_| rng.gen_bool(0.4)).collect();
+            let constraints: Vec<LinearConstraint> = (0..rng.gen_range(1..=5))
+                .map(|_| {
+                    let cells: Vec<usize> = (0..n).filter(|_| rng.gen_bool(0.5)).collect();
+                    let mines = cells.iter().filter(|&&c| layout[c]).count();
+                    LinearConstraint { cells, mines }
+                })
+                .filter(|c| !c.cells.is_empty())
+                .collect();
+            let reduced = reduce(constraints.clone());
+            assert_eq!(solutions(&reduced, n), solutions(&constraints, n), "{constraints:?}");
+            for (i, a) in reduced.iter().enumerate() {
+                for (j, b) in reduced.iter().enumerate() {
+                    if i != j { assert!(!a.cells.iter().all(|x| b.cells.contains(x)), "{reduced:?}"); }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn factor_messages_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(9);
+        for _ in 0..200 {
+            let n = rng.gen_range(1..=7);
+            let mines = rng.gen_range(0..=n);
+            let incoming: Vec<Message> = (0..n).map(|_| normalize([rng.gen_range(0.05..1.0), rng.gen_range(0.05..1.0)])).collect();
+            let messages = factor_messages(&incoming, mines);
+            for i in 0..n {
+                // weight of the other cells holding mines - x, for x = 0 and 1
+                let mut weight = [0.0; 2];
+                for bits in 0..1u32 << n {
+                    if bits.count_ones() as usize != mines { continue; }
+                    let w: f64 = (0..n).filter(|&j| j != i).map(|j| incoming[j][((bits >> j) & 1) as usize]).product();
+                    weight[((bits >> i) & 1) as usize] += w;
+                }
+                let expected = normalize(weight);
+                assert!((messages[i][1] - expected[1]).abs() < 1e-9, "n {n} mines {mines} cell {i}");
+            }
+        }
+    }
+
+    /// on a tree the messages settle on the exact marginals
+    #[test]
+    fn tree_beliefs_are_exact() {
+        // clues {0,1} {1,2,3} {3,4} {2,5,6} share one cell per pair and close no loop
+        let constraints = vec![
+            LinearConstraint { cells: vec![0, 1], mines: 1 },
+            LinearConstraint { cells: vec![1, 2, 3], mines: 1 },
+            LinearConstraint { cells: vec![3, 4], mines: 1 },
+            LinearConstraint { cells: vec![2, 5, 6], mines: 1 },
+        ];
+        let n = 7;
+        let edge = |c: &LinearConstraint, v: usize| c.cells.iter().position(|&x| x == v).unwrap();
+        let mut to_var: Vec<Vec<Message>> = constraints.iter().map(|c| vec![[0.5, 0.5]; c.cells.len()]).collect();
+        for _ in 0..n {
+            let mut next = to_var.clone();
+            for (f, c) in constraints.iter().enumerate() {
+                // into a factor: what its cells hear from the other factors
+                let incoming: Vec<Message> = c.cells.iter()
+                    .map(|&v| constraints.iter().enumerate()
+                        .filter(|&(g, other)| g != f && other.cells.contains(&v))
+                        .fold([1.0, 1.0], |m, (g, other)| combine(m, to_var[g][edge(other, v)])))
+                    .collect();
+                next[f] = factor_messages(&incoming, c.mines);
+            }
+            to_var = next;
+        }
+
+        let all = solutions(&constraints, n);
+        for v in 0..n {
+            let belief = constraints.iter().enumerate()
+                .filter(|(_, c)| c.cells.contains(&v))
+                .fold([1.0, 1.0], |m, (f, c)| combine(m, to_var[f][edge(c, v)]));
+            let exact = all.iter().filter(|&&bits| (bits >> v) & 1 == 1).count() as f64 / all.len() as f64;
+            assert!((belief[1] - exact).abs() < 1e-9, "cell {v}: {} vs {exact}", belief[1]);
+        }
+    }
+}
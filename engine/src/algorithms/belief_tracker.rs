@@ -0,0 +1,160 @@
+/**
+ * Belief-tracking agent for the dynamic mines variant. Every other solver is stateless: it
+ * reads the current clues and forgets everything once mines move. This one carries its
+ * beliefs from move to move and pushes them through the known motion model (a hidden mine
+ * hops to an adjacent hidden cell with the board's move probability) whenever mines may
+ * have moved, so history keeps counting where the current clues say nothing.
+ */
+use crate::board::Board;
+use crate::algorithms::{probability, Algorithm, SolverResult};
+use std::collections::BTreeSet;
+
+/// keeps what was learnt about the layout across mine moves
+/// - safe cells: a cell proven safe stays safe through a move if none of its neighbours can
+///   hold a mine (all revealed or proven safe themselves), since mines only hop one step
+/// - marginals: the last move's mine probabilities, diffused through the motion kernel,
+///   reweight the interior cells the current clues don't reach
+///
+/// without moving mines it plays like the exact probability engine
+pub struct BeliefTracker {
+    /// mine probability per cell after the last move (empty before the first one)
+    belief: Vec<f64>,
+    safe: BTreeSet<usize>,
+    /// clicks seen at the last move, to count the mine moves in between
+    clicks: usize,
+    /// `Board::game` of the game the beliefs belong to
+    game: u64,
+}
+
+impl BeliefTracker {
+    pub fn new(_width: usize, _height: usize, _mines: usize) -> Self {
+        Self { belief: Vec::new(), safe: BTreeSet::new(), clicks: 0, game: 0 }
+    }
+
+    /// mine moves the board may have made since the last call (they follow a fixed click schedule)
+    fn moves_since_last(&self, board: &Board) -> usize {
+        match board.dynamic_mines {
+            Some(dynamic) if dynamic.interval > 0 => board.total_clicks / dynamic.interval - self.clicks / dynamic.interval,
+            _ => 0,
+        }
+    }
+
+    /// one step of the motion model on the tracked beliefs
+    fn predict(&mut self, board: &Board, move_probability: f64) {
+        let hidden = |i: usize| !board.cells[i].is_revealed && !board.cells[i].is_flagged;
+
+        let safe_before = self.safe.clone();
+        self.safe.retain(|&c| board.adjacency_map[c].iter().all(|&n| board.cells[n].is_revealed || safe_before.contains(&n)));
+
+        // a mine keeps its cell or moves to one of its hidden neighbours (occupancy is ignored)
+        let p = move_probability.clamp(0.0, 1.0);
+        let mut next = vec![0.0; self.belief.len()];
+        for (i, &b) in self.belief.iter().enumerate() {
+            if b == 0.0 || !hidden(i) { continue; }
+            let targets: Vec<usize> = board.adjacency_map[i].iter().copied().filter(|&n| hidden(n)).collect();
+            if targets.is_empty() {
+                next[i] += b;
+                continue;
+            }
+            next[i] += b * (1.0 - p);
+            for &t in &targets { next[t] += b * p / targets.len() as f64; }
+        }
+        for &c in &self.safe { next[c] = 0.0; }
+        self.belief = next;
+    }
+
+    fn decide(&mut self, board: &Board) -> SolverResult {
+        let mut map = probability::compute_probabilities(board);
+        if map.exact {
+            for (i, &p) in map.cell_probs.iter().enumerate() {
+                if p <= 1e-12 && !board.cells[i].is_revealed && !board.cells[i].is_flagged { self.safe.insert(i); }
+            }
+        }
+        self.safe.retain(|&c| !board.cells[c].is_revealed && !board.cells[c].is_flagged);
+
+        // interior cells share the leftover mines in proportion to the carried beliefs
+        let in_frontier: BTreeSet<usize> = map.frontier.iter().copied().collect();
+        let interior: Vec<usize> = (0..board.cells.len())
+            .filter(|&i| !board.cells[i].is_revealed && !board.cells[i].is_flagged && !in_frontier.contains(&i))
+            .collect();
+        let mass: f64 = interior.iter().map(|&i| self.belief.get(i).copied().unwrap_or(0.0)).sum();
+        if mass > 0.0 {
+            let expected = map.interior_prob * interior.len() as f64;
+            for &i in &interior {
+                map.cell_probs[i] = (expected * self.belief[i] / mass).clamp(0.0, 1.0);
+            }
+        }
+        for &c in &self.safe { map.cell_probs[c] = 0.0; }
+
+        let result = if self.safe.is_empty() {
+            SolverResult { candidates: map.safest_cells(board), is_guess: true }
+        } else {
+            SolverResult { candidates: self.safe.iter().copied().collect(), is_guess: false }
+        };
+        self.belief = map.cell_probs;
+        result
+    }
+}
+
+impl Algorithm for BeliefTracker {
+    fn find_candidates(&mut self, board: &Board) -> SolverResult {
+        // a reset (or another board) starts from scratch
+        if board.game != self.game {
+            self.belief = Vec::new();
+            self.safe.clear();
+            self.clicks = board.total_clicks;
+            self.game = board.game;
+        }
+
+        if let Some(dynamic) = board.dynamic_mines {
+            if !self.belief.is_empty() {
+                for _ in 0..self.moves_since_last(board) {
+                    self.predict(board, dynamic.move_probability);
+                }
+            }
+        }
+
+        let result = self.decide(board);
+        self.clicks = board.total_clicks;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+
+    /// one game played by the exact solver, with every tracker asked at each step
+    fn follow(sim: &mut Simulator, trackers: &mut [&mut BeliefTracker]) -> Vec<Vec<(Vec<usize>, bool)>> {
+        let mut answers = vec![Vec::new(); trackers.len()];
+        sim.run_step().unwrap();
+        for _ in 0..40 {
+            if sim.board.game_over { break; }
+            for (tracker, answers) in trackers.iter_mut().zip(&mut answers) {
+                let result = tracker.find_candidates(&sim.board);
+                answers.push((result.candidates, result.is_guess));
+            }
+            sim.run_step().unwrap();
+        }
+        answers
+    }
+
+    #[test]
+    fn a_reset_forgets_the_last_game() {
+        for seed in 0..5 {
+            let mut sim = Simulator::new(vec![9, 9], 10, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_dynamic_mines(3, 0.3);
+            sim.set_seed(seed).unwrap();
+            let mut reused = BeliefTracker::new(9, 9, 10);
+            follow(&mut sim, &mut [&mut reused]);
+
+            sim.reset().unwrap();
+            let mut fresh = BeliefTracker::new(9, 9, 10);
+            let answers = follow(&mut sim, &mut [&mut reused, &mut fresh]);
+            assert!(!answers[0].is_empty());
+            assert_eq!(answers[0], answers[1], "seed {seed}");
+        }
+    }
+}
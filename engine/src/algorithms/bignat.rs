@@ -0,0 +1,277 @@
+/**
+ * Arbitrary-size natural numbers for exact layout counts. Counts over the whole board are
+ * weighted by C(interior, remaining mines), which leaves u128 behind on expert boards, so the
+ * model counter finishes in these. Only what the counts need: +, *, small division,
+ * binomials, decimal output and a ratio as f64.
+ */
+use serde::{Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul};
+
+/// little-endian base 2^32 limbs, no leading zero limbs (zero is empty)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigNat {
+    limbs: Vec<u32>,
+}
+
+impl BigNat {
+    pub fn zero() -> Self {
+        Self { limbs: Vec::new() }
+    }
+
+    pub fn one() -> Self {
+        Self::from(1u128)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    fn trim(mut self) -> Self {
+        while self.limbs.last() == Some(&0) { self.limbs.pop(); }
+        self
+    }
+
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(&top) => self.limbs.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    pub fn mul_small(&self, factor: u32) -> BigNat {
+        let mut carry = 0u64;
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 1);
+        for &limb in &self.limbs {
+            let product = limb as u64 * factor as u64 + carry;
+            limbs.push(product as u32);
+            carry = product >> 32;
+        }
+        limbs.push(carry as u32);
+        BigNat { limbs }.trim()
+    }
+
+    /// quotient and remainder
+    pub fn div_small(&self, divisor: u32) -> (BigNat, u32) {
+        assert!(divisor != 0, "division by zero");
+        let mut rem = 0u64;
+        let mut limbs = vec![0; self.limbs.len()];
+        for (i, &limb) in self.limbs.iter().enumerate().rev() {
+            let current = (rem << 32) | limb as u64;
+            limbs[i] = (current / divisor as u64) as u32;
+            rem = current % divisor as u64;
+        }
+        (BigNat { limbs }.trim(), rem as u32)
+    }
+
+    /// n choose k, exact (0 for k > n)
+    pub fn binomial(n: usize, k: usize) -> BigNat {
+        if k > n { return BigNat::zero(); }
+        let k = k.min(n - k);
+        // every prefix product is itself a binomial, so each division is exact
+        let mut result = BigNat::one();
+        for i in 0..k {
+            result = result.mul_small((n - i) as u32).div_small((i + 1) as u32).0;
+        }
+        result
+    }
+
+    /// the top 64 bits as a float and the power of two they are scaled by
+    fn mantissa(&self) -> (f64, i64) {
+        let bits = self.bits() as i64;
+        let shift = (bits - 64).max(0);
+        let mut top = 0u128;
+        for i in (0..self.limbs.len()).rev() {
+            let position = i as i64 * 32;
+            if position + 32 <= shift { break; }
+            top = (top << 32) | self.limbs[i] as u128;
+        }
+        // drop the bits below `shift` that came along with the lowest limb read
+        let lowest = (shift / 32) * 32;
+        ((top >> (shift - lowest)) as f64, shift)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let (mantissa, shift) = self.mantissa();
+        mantissa * 2f64.powi(shift.min(i32::MAX as i64) as i32)
+    }
+
+    pub fn ln(&self) -> f64 {
+        let (mantissa, shift) = self.mantissa();
+        mantissa.ln() + shift as f64 * std::f64::consts::LN_2
+    }
+
+    /// self / other as a float, fine for numbers far outside the f64 range (NaN for 0 / 0)
+    pub fn ratio(&self, other: &BigNat) -> f64 {
+        if other.is_zero() { return if self.is_zero() { f64::NAN } else { f64::INFINITY }; }
+        if self.is_zero() { return 0.0; }
+        let (a, shift_a) = self.mantissa();
+        let (b, shift_b) = other.mantissa();
+        a / b * 2f64.powi((shift_a - shift_b).clamp(-2000, 2000) as i32)
+    }
+}
+
+impl From<u128> for BigNat {
+    fn from(value: u128) -> Self {
+        let limbs = (0..4).map(|i| (value >> (32 * i)) as u32).collect();
+        BigNat { limbs }.trim()
+    }
+}
+
+impl Add for &BigNat {
+    type Output = BigNat;
+
+    fn add(self, other: &BigNat) -> BigNat {
+        let mut result = self.clone();
+        result += other;
+        result
+    }
+}
+
+impl AddAssign<&BigNat> for BigNat {
+    fn add_assign(&mut self, other: &BigNat) {
+        if self.limbs.len() < other.limbs.len() { self.limbs.resize(other.limbs.len(), 0); }
+        let mut carry = 0u64;
+        for i in 0..self.limbs.len() {
+            let sum = self.limbs[i] as u64 + other.limbs.get(i).copied().unwrap_or(0) as u64 + carry;
+            self.limbs[i] = sum as u32;
+            carry = sum >> 32;
+            if carry == 0 && i >= other.limbs.len() { break; }
+        }
+        if carry > 0 { self.limbs.push(carry as u32); }
+    }
+}
+
+impl Mul for &BigNat {
+    type Output = BigNat;
+
+    fn mul(self, other: &BigNat) -> BigNat {
+        if self.is_zero() || other.is_zero() { return BigNat::zero(); }
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let current = limbs[i + j] as u64 + a as u64 * b as u64 + carry;
+                limbs[i + j] = current as u32;
+                carry = current >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        BigNat { limbs }.trim()
+    }
+}
+
+impl Ord for BigNat {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs.len().cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigNat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigNat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() { return write!(f, "0"); }
+        // base 10^9 chunks, least significant first
+        let mut chunks = Vec::new();
+        let mut rest = self.clone();
+        while !rest.is_zero() {
+            let (quotient, chunk) = rest.div_small(1_000_000_000);
+            chunks.push(chunk);
+            rest = quotient;
+        }
+        write!(f, "{}", chunks.pop().expect("nonzero has a chunk"))?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+/// decimal strings, so javascript gets every digit
+impl Serialize for BigNat {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn arithmetic_matches_u128() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..2000 {
+            // mixed sizes, so carries into new limbs and zeros both come up
+            let a: u128 = rng.gen::<u64>() as u128 >> rng.gen_range(0..64);
+            let b: u128 = rng.gen::<u64>() as u128 >> rng.gen_range(0..64);
+            let (x, y) = (BigNat::from(a), BigNat::from(b));
+            assert_eq!(&x + &y, BigNat::from(a + b));
+            assert_eq!(&x * &y, BigNat::from(a * b));
+            assert_eq!(x.cmp(&y), a.cmp(&b));
+            assert_eq!((&x * &y).to_string(), (a * b).to_string());
+            assert_eq!(x.bits(), 128 - a.leading_zeros() as u64);
+
+            let small: u32 = rng.gen_range(1..=u32::MAX);
+            assert_eq!(x.mul_small(small), BigNat::from(a * small as u128));
+            let (quotient, rem) = (&x * &y).div_small(small);
+            assert_eq!(quotient, BigNat::from(a * b / small as u128));
+            assert_eq!(rem as u128, a * b % small as u128);
+
+            let product = (a * b) as f64;
+            assert!(((&x * &y).to_f64() - product).abs() <= product * 1e-15);
+            if a > 0 && b > 0 {
+                assert!((x.ratio(&y) - a as f64 / b as f64).abs() <= 1e-12 * (a as f64 / b as f64));
+                assert!(((&x * &y).ln() - product.ln()).abs() < 1e-12 * product.ln().abs().max(1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn binomials_match_pascal_in_u128() {
+        let mut row = vec![1u128];
+        for n in 0..=120 {
+            for (k, &expected) in row.iter().enumerate() {
+                assert_eq!(BigNat::binomial(n, k), BigNat::from(expected), "C({n}, {k})");
+            }
+            assert!(BigNat::binomial(n, n + 1).is_zero());
+            let mut next = vec![1u128; n + 2];
+            for k in 1..=n { next[k] = row[k - 1] + row[k]; }
+            row = next;
+        }
+    }
+
+    #[test]
+    fn binomial_identities() {
+        for n in [150, 233, 480] {
+            let mut power = BigNat::one();
+            for _ in 0..n { power = power.mul_small(2); }
+            let mut sum = BigNat::zero();
+            for k in 0..=n {
+                let c = BigNat::binomial(n, k);
+                // symmetry and pascal's rule
+                assert_eq!(c, BigNat::binomial(n, n - k));
+                if k > 0 {
+                    assert_eq!(c, &BigNat::binomial(n - 1, k - 1) + &BigNat::binomial(n - 1, k));
+                }
+                sum += &c;
+            }
+            // the row sums to 2^n
+            assert_eq!(sum, power);
+        }
+        // vandermonde: C(m + n, r) = sum C(m, k) C(n, r - k)
+        let (m, n, r) = (170, 130, 140);
+        let mut sum = BigNat::zero();
+        for k in 0..=r { sum += &(&BigNat::binomial(m, k) * &BigNat::binomial(n, r - k)); }
+        assert_eq!(sum, BigNat::binomial(m + n, r));
+    }
+}
@@ -0,0 +1,477 @@
+/**
+ * Conflict-driven clause learning (CDCL) SAT solver used by the sat backends.
+ * Two watched literals for propagation, first-UIP learning, activity-based branching
+ * with phase saving and luby restarts. The search loop is iterative so deep boards
+ * don't blow the (small) wasm stack.
+ */
+use crate::algorithms::sat_utils::Clause;
+use crate::error::MinesweeperError;
+
+/// internal literal: 2 * var + (1 if negated)
+type Lit = usize;
+
+fn var(l: Lit) -> usize { l >> 1 }
+fn negate(l: Lit) -> Lit { l ^ 1 }
+
+/// converts the dimacs style literals of `Clause` (+-(var + 1)) into internal ones.
+/// 0 and variables past `num_vars` are rejected instead of indexing out of bounds
+fn from_dimacs(l: isize, num_vars: usize) -> Result<Lit, MinesweeperError> {
+    let v = l.unsigned_abs();
+    if v == 0 || v > num_vars {
+        return Err(MinesweeperError::InvalidLiteral { literal: l, num_vars });
+    }
+    Ok(2 * (v - 1) + usize::from(l < 0))
+}
+
+/// conflicts per luby unit before restarting
+const RESTART_BASE: usize = 100;
+
+#[derive(Clone)]
+pub struct CdclSolver {
+    clauses: Vec<Vec<Lit>>,
+    /// watches[lit] = clauses watching lit (lit sits at position 0 or 1)
+    watches: Vec<Vec<usize>>,
+    assigns: Vec<Option<bool>>,
+    level: Vec<usize>,
+    reason: Vec<Option<usize>>,
+    trail: Vec<Lit>,
+    trail_lim: Vec<usize>,
+    qhead: usize,
+    activity: Vec<f64>,
+    var_inc: f64,
+    /// last value of every var, reused when branching (phase saving)
+    polarity: Vec<bool>,
+    /// vars that occur in some clause; the others never need a decision
+    decision_vars: Vec<usize>,
+    in_use: Vec<bool>,
+    seen: Vec<bool>,
+    model: Vec<bool>,
+    /// false once a conflict at level 0 proved the formula unsat
+    ok: bool,
+    pub conflicts: usize,
+}
+
+impl CdclSolver {
+    pub fn new(num_vars: usize) -> Self {
+        Self {
+            clauses: Vec::new(),
+            watches: vec![Vec::new(); 2 * num_vars],
+            assigns: vec![None; num_vars],
+            level: vec![0; num_vars],
+            reason: vec![None; num_vars],
+            trail: Vec::new(),
+            trail_lim: Vec::new(),
+            qhead: 0,
+            activity: vec![0.0; num_vars],
+            var_inc: 1.0,
+            polarity: vec![false; num_vars],
+            decision_vars: Vec::new(),
+            in_use: vec![false; num_vars],
+            seen: vec![false; num_vars],
+            model: Vec::new(),
+            ok: true,
+            conflicts: 0,
+        }
+    }
+
+    pub fn from_clauses(num_vars: usize, clauses: &[Clause]) -> Result<Self, MinesweeperError> {
+        let mut solver = Self::new(num_vars);
+        for c in clauses {
+            solver.add_clause(&c.0)?;
+        }
+        Ok(solver)
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.assigns.len()
+    }
+
+    /// value of a var in the last satisfying assignment (true = mine)
+    pub fn model_value(&self, v: usize) -> bool {
+        self.model.get(v).copied().unwrap_or(false)
+    }
+
+    fn value(&self, l: Lit) -> Option<bool> {
+        self.assigns[var(l)].map(|a| a != (l & 1 == 1))
+    }
+
+    fn decision_level(&self) -> usize {
+        self.trail_lim.len()
+    }
+
+    /// adds a clause of dimacs literals at level 0, returns false if the formula became unsat
+    pub fn add_clause(&mut self, lits: &[isize]) -> Result<bool, MinesweeperError> {
+        let lits = self.convert(lits)?;
+        if !self.ok { return Ok(false); }
+        self.cancel_until(0);
+
+        let mut clause: Vec<Lit> = Vec::with_capacity(lits.len());
+        for lit in lits {
+            match self.value(lit) {
+                Some(true) => return Ok(true), // already satisfied
+                Some(false) => continue,   // falsified at level 0
+                None => {}
+            }
+            if clause.contains(&negate(lit)) { return Ok(true); } // tautology
+            if !clause.contains(&lit) { clause.push(lit); }
+        }
+        for &lit in &clause {
+            if !self.in_use[var(lit)] {
+                self.in_use[var(lit)] = true;
+                self.decision_vars.push(var(lit));
+            }
+        }
+
+        match clause.len() {
+            0 => self.ok = false,
+            1 => {
+                self.enqueue(clause[0], None);
+                if self.propagate().is_some() { self.ok = false; }
+            }
+            _ => { self.attach(clause); }
+        }
+        Ok(self.ok)
+    }
+
+    fn convert(&self, lits: &[isize]) -> Result<Vec<Lit>, MinesweeperError> {
+        lits.iter().map(|&l| from_dimacs(l, self.num_vars())).collect()
+    }
+
+    fn attach(&mut self, clause: Vec<Lit>) -> usize {
+        let cref = self.clauses.len();
+        self.watches[clause[0]].push(cref);
+        self.watches[clause[1]].push(cref);
+        self.clauses.push(clause);
+        cref
+    }
+
+    fn enqueue(&mut self, l: Lit, reason: Option<usize>) {
+        let v = var(l);
+        self.assigns[v] = Some(l & 1 == 0);
+        self.level[v] = self.decision_level();
+        self.reason[v] = reason;
+        self.trail.push(l);
+    }
+
+    /// unit propagation over the watch lists, returns the conflicting clause if any
+    fn propagate(&mut self) -> Option<usize> {
+        while self.qhead < self.trail.len() {
+            let p = self.trail[self.qhead];
+            self.qhead += 1;
+            let false_lit = negate(p);
+
+            let mut ws = std::mem::take(&mut self.watches[false_lit]);
+            let mut i = 0;
+            let mut conflict = None;
+            while i < ws.len() {
+                let cref = ws[i];
+                // keep the falsified watch at position 1
+                if self.clauses[cref][0] == false_lit {
+                    self.clauses[cref].swap(0, 1);
+                }
+                let first = self.clauses[cref][0];
+                if self.value(first) == Some(true) {
+                    i += 1;
+                    continue;
+                }
+
+                // look for a replacement watch
+                let len = self.clauses[cref].len();
+                if let Some(k) = (2..len).find(|&k| self.value(self.clauses[cref][k]) != Some(false)) {
+                    self.clauses[cref].swap(1, k);
+                    let new_watch = self.clauses[cref][1];
+                    self.watches[new_watch].push(cref);
+                    ws.swap_remove(i);
+                    continue;
+                }
+
+                // clause is unit or conflicting
+                i += 1;
+                if self.value(first) == Some(false) {
+                    conflict = Some(cref);
+                    break;
+                }
+                self.enqueue(first, Some(cref));
+            }
+            self.watches[false_lit].append(&mut ws);
+
+            if conflict.is_some() {
+                self.qhead = self.trail.len();
+                return conflict;
+            }
+        }
+        None
+    }
+
+    /// first-uip conflict analysis, returns the learnt clause (asserting literal first)
+    /// and the level to backjump to
+    fn analyze(&mut self, mut confl: usize) -> (Vec<Lit>, usize) {
+        let mut learnt: Vec<Lit> = vec![0];
+        let mut counter = 0;
+        let mut p: Option<Lit> = None;
+        let mut index = self.trail.len();
+
+        loop {
+            // reason clauses hold the implied literal at position 0
+            let start = usize::from(p.is_some());
+            for j in start..self.clauses[confl].len() {
+                let q = self.clauses[confl][j];
+                let v = var(q);
+                if self.seen[v] || self.level[v] == 0 { continue; }
+                self.seen[v] = true;
+                self.bump(v);
+                if self.level[v] >= self.decision_level() {
+                    counter += 1;
+                } else {
+                    learnt.push(q);
+                }
+            }
+
+            // next literal of the current level on the trail
+            loop {
+                index -= 1;
+                if self.seen[var(self.trail[index])] { break; }
+            }
+            let lit = self.trail[index];
+            self.seen[var(lit)] = false;
+            counter -= 1;
+            p = Some(lit);
+            if counter == 0 { break; }
+            confl = self.reason[var(lit)].expect("implied literal without reason");
+        }
+        learnt[0] = negate(p.unwrap());
+
+        for &l in &learnt[1..] { self.seen[var(l)] = false; }
+
+        // second watch = literal with the highest level, which is also the backjump level
+        let mut backjump = 0;
+        if learnt.len() > 1 {
+            let mut max_i = 1;
+            for i in 2..learnt.len() {
+                if self.level[var(learnt[i])] > self.level[var(learnt[max_i])] { max_i = i; }
+            }
+            learnt.swap(1, max_i);
+            backjump = self.level[var(learnt[1])];
+        }
+        (learnt, backjump)
+    }
+
+    fn bump(&mut self, v: usize) {
+        self.activity[v] += self.var_inc;
+        if self.activity[v] > 1e100 {
+            for a in &mut self.activity { *a *= 1e-100; }
+            self.var_inc *= 1e-100;
+        }
+    }
+
+    fn cancel_until(&mut self, level: usize) {
+        if self.decision_level() <= level { return; }
+        let start = self.trail_lim[level];
+        for &l in &self.trail[start..] {
+            let v = var(l);
+            self.polarity[v] = l & 1 == 0;
+            self.assigns[v] = None;
+            self.reason[v] = None;
+        }
+        self.trail.truncate(start);
+        self.trail_lim.truncate(level);
+        self.qhead = start;
+    }
+
+    /// unassigned var with the highest activity, using its saved phase
+    fn pick_branch(&self) -> Option<Lit> {
+        let mut best: Option<usize> = None;
+        for &v in &self.decision_vars {
+            if self.assigns[v].is_some() { continue; }
+            if best.is_none_or(|b| self.activity[v] > self.activity[b]) { best = Some(v); }
+        }
+        best.map(|v| 2 * v + usize::from(!self.polarity[v]))
+    }
+
+    /// runs until sat/unsat (Some) or until the conflict budget is used up (None)
+    fn search(&mut self, budget: usize, assumptions: &[Lit]) -> Option<bool> {
+        let mut conflicts = 0;
+        loop {
+            if let Some(confl) = self.propagate() {
+                conflicts += 1;
+                self.conflicts += 1;
+                if self.decision_level() == 0 {
+                    self.ok = false;
+                    return Some(false);
+                }
+                let (learnt, backjump) = self.analyze(confl);
+                self.cancel_until(backjump);
+                if learnt.len() == 1 {
+                    self.enqueue(learnt[0], None);
+                } else {
+                    let asserting = learnt[0];
+                    let cref = self.attach(learnt);
+                    self.enqueue(asserting, Some(cref));
+                }
+                self.var_inc /= 0.95;
+            } else {
+                if conflicts >= budget {
+                    self.cancel_until(0);
+                    return None;
+                }
+
+                // assumptions are the first decisions, one level each
+                if let Some(&a) = assumptions.get(self.decision_level()) {
+                    match self.value(a) {
+                        Some(true) => self.trail_lim.push(self.trail.len()),
+                        Some(false) => return Some(false), // unsat under the assumptions only
+                        None => {
+                            self.trail_lim.push(self.trail.len());
+                            self.enqueue(a, None);
+                        }
+                    }
+                    continue;
+                }
+
+                match self.pick_branch() {
+                    None => {
+                        self.model = self.assigns.iter().map(|a| a.unwrap_or(false)).collect();
+                        return Some(true);
+                    }
+                    Some(lit) => {
+                        self.trail_lim.push(self.trail.len());
+                        self.enqueue(lit, None);
+                    }
+                }
+            }
+        }
+    }
+
+    /// decides satisfiability; learnt clauses are kept for later calls
+    pub fn solve(&mut self) -> bool {
+        self.solve_lits(&[])
+    }
+
+    /// decides satisfiability with the given dimacs literals temporarily forced true.
+    /// clauses learnt under assumptions only depend on the formula, so they are kept
+    pub fn solve_with_assumptions(&mut self, assumptions: &[isize]) -> Result<bool, MinesweeperError> {
+        let assumptions = self.convert(assumptions)?;
+        Ok(self.solve_lits(&assumptions))
+    }
+
+    fn solve_lits(&mut self, assumptions: &[Lit]) -> bool {
+        if !self.ok { return false; }
+        let mut restarts = 0;
+        let result = loop {
+            if let Some(result) = self.search(luby(restarts) * RESTART_BASE, assumptions) {
+                break result;
+            }
+            restarts += 1;
+        };
+        self.cancel_until(0);
+        result
+    }
+}
+
+/// luby sequence 1 1 2 1 1 2 4 1 1 2 ...
+fn luby(mut i: usize) -> usize {
+    let mut size = 1;
+    let mut seq = 0;
+    while size < i + 1 {
+        seq += 1;
+        size = 2 * size + 1;
+    }
+    while size - 1 != i {
+        size = (size - 1) >> 1;
+        seq -= 1;
+        i %= size;
+    }
+    1 << seq
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    fn satisfies(solver: &CdclSolver, clauses: &[Vec<isize>]) -> bool {
+        clauses.iter().all(|c| c.iter().any(|&l| solver.model_value(l.unsigned_abs() - 1) == (l > 0)))
+    }
+
+    fn brute_force_sat(num_vars: usize, clauses: &[Vec<isize>]) -> bool {
+        (0..1u32 << num_vars).any(|bits| {
+            clauses.iter().all(|c| c.iter().any(|&l| ((bits >> (l.unsigned_abs() - 1)) & 1 == 1) == (l > 0)))
+        })
+    }
+
+    /// `pigeons` pigeons in `holes` holes, every pigeon somewhere, no hole shared
+    fn pigeonhole(pigeons: usize, holes: usize) -> (usize, Vec<Vec<isize>>) {
+        let v = |p: usize, h: usize| (p * holes + h + 1) as isize;
+        let mut clauses: Vec<Vec<isize>> = (0..pigeons).map(|p| (0..holes).map(|h| v(p, h)).collect()).collect();
+        for h in 0..holes {
+            for p in 0..pigeons {
+                for q in p + 1..pigeons {
+                    clauses.push(vec![-v(p, h), -v(q, h)]);
+                }
+            }
+        }
+        (pigeons * holes, clauses)
+    }
+
+    #[test]
+    fn pigeonhole_is_unsat_with_one_hole_short() {
+        for holes in 1..5 {
+            let (n, clauses) = pigeonhole(holes + 1, holes);
+            let mut solver = CdclSolver::new(n);
+            for c in &clauses { solver.add_clause(c).unwrap(); }
+            assert!(!solver.solve(), "php({}, {})", holes + 1, holes);
+
+            let (n, clauses) = pigeonhole(holes, holes);
+            let mut solver = CdclSolver::new(n);
+            for c in &clauses { solver.add_clause(c).unwrap(); }
+            assert!(solver.solve());
+            assert!(satisfies(&solver, &clauses));
+        }
+    }
+
+    #[test]
+    fn random_3sat_agrees_with_brute_force() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let n = 10;
+        for round in 0..300 {
+            // around the 4.26 clauses/var threshold, so both answers show up
+            let m = 30 + round % 25;
+            let clauses: Vec<Vec<isize>> = (0..m)
+                .map(|_| (0..3).map(|_| {
+                    let v = rng.gen_range(1..=n as isize);
+                    if rng.gen_bool(0.5) { v } else { -v }
+                }).collect())
+                .collect();
+            let mut solver = CdclSolver::new(n);
+            for c in &clauses { solver.add_clause(c).unwrap(); }
+            let sat = solver.solve();
+            assert_eq!(sat, brute_force_sat(n, &clauses), "round {round}");
+            if sat { assert!(satisfies(&solver, &clauses)); }
+        }
+    }
+
+    #[test]
+    fn assumptions_are_temporary() {
+        let mut solver = CdclSolver::new(2);
+        solver.add_clause(&[1, 2]).unwrap();
+        assert!(solver.solve_with_assumptions(&[-1]).unwrap());
+        assert!(solver.model_value(1));
+        assert!(!solver.solve_with_assumptions(&[-1, -2]).unwrap());
+        assert!(solver.solve());
+        solver.add_clause(&[-1]).unwrap();
+        assert!(!solver.add_clause(&[-2]).unwrap());
+        assert!(!solver.solve());
+    }
+
+    #[test]
+    fn out_of_range_literals_are_errors() {
+        let mut solver = CdclSolver::new(3);
+        assert_eq!(solver.add_clause(&[1, 4]), Err(MinesweeperError::InvalidLiteral { literal: 4, num_vars: 3 }));
+        assert_eq!(solver.add_clause(&[0]), Err(MinesweeperError::InvalidLiteral { literal: 0, num_vars: 3 }));
+        assert_eq!(solver.solve_with_assumptions(&[-7]), Err(MinesweeperError::InvalidLiteral { literal: -7, num_vars: 3 }));
+        // a rejected clause leaves the formula untouched
+        assert!(solver.solve());
+        assert!(CdclSolver::from_clauses(2, &[Clause(vec![1, -3])]).is_err());
+    }
+}
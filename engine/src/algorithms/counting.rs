@@ -0,0 +1,279 @@
+/**
+ * The counting core shared by the constraint programming counter (`cp.rs`, f64 counts per
+ * mine total) and the model counter (`model_count.rs`, exact u128 counts). Both keep counts
+ * per mine total of a cell set (`Tally`), add the branches of a decision, multiply the counts
+ * of independent parts and split the open cells into components at every node. Only the
+ * propagation differs: bounds consistency on clue equalities there, unit propagation on
+ * clauses here, so `Assignment` keeps the trail and the per-constraint counters both read.
+ */
+use std::collections::HashMap;
+use std::ops::{AddAssign, Mul};
+
+/// a count type: f64 (scaled, may round) or u128 (exact)
+pub trait Count: Copy + PartialEq + AddAssign + Mul<Output = Self> {
+    const ZERO: Self;
+    const ONE: Self;
+}
+
+impl Count for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+}
+
+impl Count for u128 {
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+}
+
+/// counts of a set of cells: `counts[k]` solutions with `low + k` mines among them,
+/// `cell_counts[i * counts.len() + k]` of those with cells[i] a mine. only the mine totals
+/// that occur are stored, so a leaf costs one entry per cell
+#[derive(Clone, Debug)]
+pub struct Tally<T> {
+    pub cells: Vec<usize>,
+    pub low: usize,
+    pub counts: Vec<T>,
+    pub cell_counts: Vec<T>,
+}
+
+impl<T: Count> Tally<T> {
+    /// the one solution of cells that are all assigned
+    pub fn fixed(cells: Vec<usize>, value: &[Option<bool>]) -> Self {
+        let cell_counts: Vec<T> = cells.iter().map(|&v| if value[v] == Some(true) { T::ONE } else { T::ZERO }).collect();
+        let low = cell_counts.iter().filter(|&&x| x != T::ZERO).count();
+        Self { cells, low, counts: vec![T::ONE], cell_counts }
+    }
+
+    /// no solutions
+    pub fn empty(cells: Vec<usize>) -> Self {
+        Self { cells, low: 0, counts: Vec::new(), cell_counts: Vec::new() }
+    }
+
+    /// per cell, its counts by mine total
+    pub fn rows(&self) -> std::slice::Chunks<'_, T> {
+        self.cell_counts.chunks(self.counts.len().max(1))
+    }
+
+    /// adds the solutions of another branch over the same cells
+    pub fn add(&mut self, other: Tally<T>) {
+        if other.counts.is_empty() { return; }
+        if self.counts.is_empty() {
+            *self = other;
+            return;
+        }
+        let low = self.low.min(other.low);
+        let width = (self.low + self.counts.len()).max(other.low + other.counts.len()) - low;
+        if low != self.low || width != self.counts.len() {
+            // widen to the union of both mine ranges
+            let offset = self.low - low;
+            let mut counts = vec![T::ZERO; width];
+            counts[offset..offset + self.counts.len()].copy_from_slice(&self.counts);
+            let mut cell_counts = vec![T::ZERO; self.cells.len() * width];
+            for (row, wide) in self.rows().zip(cell_counts.chunks_mut(width)) {
+                wide[offset..offset + row.len()].copy_from_slice(row);
+            }
+            self.low = low;
+            self.counts = counts;
+            self.cell_counts = cell_counts;
+        }
+        let offset = other.low - low;
+        for (k, &b) in other.counts.iter().enumerate() { self.counts[offset + k] += b; }
+        for (row, other_row) in self.cell_counts.chunks_mut(width).zip(other.rows()) {
+            for (k, &b) in other_row.iter().enumerate() { row[offset + k] += b; }
+        }
+    }
+
+    /// solutions of two disjoint cell sets together
+    pub fn product(self, other: &Tally<T>) -> Tally<T> {
+        let mut cells = self.cells.clone();
+        cells.extend(other.cells.iter().copied());
+        if self.counts.is_empty() || other.counts.is_empty() {
+            return Tally::empty(cells);
+        }
+        let counts = convolve(&self.counts, &other.counts);
+        let mut cell_counts = Vec::with_capacity(cells.len() * counts.len());
+        for row in self.rows() { cell_counts.extend(convolve(row, &other.counts)); }
+        for row in other.rows() { cell_counts.extend(convolve(row, &self.counts)); }
+        Tally { cells, low: self.low + other.low, counts, cell_counts }
+    }
+
+    /// the same counts with the cells in the given order
+    pub fn reorder(self, cells: &[usize]) -> Tally<T> {
+        let width = self.counts.len();
+        if width == 0 { return Tally::empty(cells.to_vec()); }
+        let position: HashMap<usize, usize> = self.cells.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+        let mut cell_counts = Vec::with_capacity(self.cell_counts.len());
+        for v in cells {
+            let i = position[v];
+            cell_counts.extend_from_slice(&self.cell_counts[i * width..(i + 1) * width]);
+        }
+        Tally { cells: cells.to_vec(), low: self.low, counts: self.counts, cell_counts }
+    }
+}
+
+/// product of two count polynomials (index = mine count)
+pub fn convolve<T: Count>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut out = vec![T::ZERO; a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        if x == T::ZERO { continue; }
+        for (j, &y) in b.iter().enumerate() {
+            out[i + j] += x * y;
+        }
+    }
+    out
+}
+
+/// a partial assignment of 0/1 variables over constraints (clues or clauses), undone along a
+/// trail. a variable occurs in a constraint positively or negatively; per constraint it counts
+/// the occurrences the assignment makes true (`hits`) and false (`misses`)
+pub struct Assignment {
+    /// variables per constraint
+    pub members: Vec<Vec<usize>>,
+    /// (constraint, occurs positively) per variable
+    pub occurrences: Vec<Vec<(usize, bool)>>,
+    pub value: Vec<Option<bool>>,
+    pub hits: Vec<usize>,
+    pub misses: Vec<usize>,
+    trail: Vec<usize>,
+    /// generation stamps for `components`
+    var_stamp: Vec<usize>,
+    constraint_stamp: Vec<usize>,
+    stamp: usize,
+}
+
+impl Assignment {
+    /// `constraints` lists the (variable, occurs positively) pairs of each constraint
+    pub fn new(constraints: &[Vec<(usize, bool)>], vars: usize) -> Self {
+        let mut occurrences = vec![Vec::new(); vars];
+        for (c, constraint) in constraints.iter().enumerate() {
+            for &(v, positive) in constraint { occurrences[v].push((c, positive)); }
+        }
+        let n = constraints.len();
+        Self {
+            members: constraints.iter().map(|c| c.iter().map(|&(v, _)| v).collect()).collect(),
+            occurrences,
+            value: vec![None; vars],
+            hits: vec![0; n],
+            misses: vec![0; n],
+            trail: Vec::new(),
+            var_stamp: vec![0; vars],
+            constraint_stamp: vec![0; n],
+            stamp: 0,
+        }
+    }
+
+    /// unassigned variables of constraint `c`
+    pub fn open_in(&self, c: usize) -> usize {
+        self.members[c].len() - self.hits[c] - self.misses[c]
+    }
+
+    /// trail position to `undo` back to
+    pub fn mark(&self) -> usize {
+        self.trail.len()
+    }
+
+    pub fn assign(&mut self, v: usize, value: bool) {
+        self.value[v] = Some(value);
+        for &(c, positive) in &self.occurrences[v] {
+            if positive == value { self.hits[c] += 1; } else { self.misses[c] += 1; }
+        }
+        self.trail.push(v);
+    }
+
+    pub fn undo(&mut self, mark: usize) {
+        while self.trail.len() > mark {
+            let v = self.trail.pop().expect("trail is longer than mark");
+            let value = self.value[v].take() == Some(true);
+            for &(c, positive) in &self.occurrences[v] {
+                if positive == value { self.hits[c] -= 1; } else { self.misses[c] -= 1; }
+            }
+        }
+    }
+
+    /// open variables split by the constraints `active` still counts as a link, each group sorted
+    pub fn components(&mut self, open: &[usize], active: impl Fn(&Self, usize) -> bool) -> Vec<Vec<usize>> {
+        // stamp: member of `open` and not yet grouped; stamp + 1: grouped
+        self.stamp += 2;
+        let (member, grouped) = (self.stamp, self.stamp + 1);
+        for &v in open { self.var_stamp[v] = member; }
+        let mut groups = Vec::new();
+        for &start in open {
+            if self.var_stamp[start] != member { continue; }
+            self.var_stamp[start] = grouped;
+            let mut group = vec![start];
+            let mut next = 0;
+            while next < group.len() {
+                let v = group[next];
+                next += 1;
+                for i in 0..self.occurrences[v].len() {
+                    let c = self.occurrences[v][i].0;
+                    if self.constraint_stamp[c] == grouped || !active(self, c) { continue; }
+                    self.constraint_stamp[c] = grouped;
+                    for j in 0..self.members[c].len() {
+                        let u = self.members[c][j];
+                        if self.var_stamp[u] == member {
+                            self.var_stamp[u] = grouped;
+                            group.push(u);
+                        }
+                    }
+                }
+            }
+            group.sort_unstable();
+            groups.push(group);
+        }
+        self.stamp += 1;
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// every 0/1 layout of `cells`, added up leaf by leaf
+    fn all_layouts<T: Count>(cells: &[usize], vars: usize) -> Tally<T> {
+        let mut tally = Tally::empty(cells.to_vec());
+        for bits in 0..1u32 << cells.len() {
+            let mut value = vec![None; vars];
+            for (i, &v) in cells.iter().enumerate() { value[v] = Some((bits >> i) & 1 == 1); }
+            tally.add(Tally::fixed(cells.to_vec(), &value));
+        }
+        tally
+    }
+
+    fn binomial(n: usize, k: usize) -> u128 {
+        (0..k).fold(1, |acc, i| acc * (n - i) as u128 / (i + 1) as u128)
+    }
+
+    #[test]
+    fn tallies_count_layouts_by_mine_total() {
+        let (a, b): (Tally<u128>, Tally<u128>) = (all_layouts(&[4, 0, 2], 7), all_layouts(&[1, 6, 3, 5], 7));
+        let joint = a.product(&b).reorder(&[0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(joint.low, 0);
+        assert_eq!(joint.counts, (0..=7).map(|k| binomial(7, k)).collect::<Vec<_>>());
+        // a cell is a mine in C(6, k - 1) of the layouts with k mines
+        for row in joint.rows() {
+            assert_eq!(row, (0..=7).map(|k| if k == 0 { 0 } else { binomial(6, k - 1) }).collect::<Vec<_>>().as_slice());
+        }
+
+        let scaled: Tally<f64> = all_layouts::<f64>(&[4, 0, 2], 7).product(&all_layouts(&[1, 6, 3, 5], 7)).reorder(&[0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(scaled.counts, joint.counts.iter().map(|&c| c as f64).collect::<Vec<_>>());
+        assert_eq!(scaled.cell_counts, joint.cell_counts.iter().map(|&c| c as f64).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn assignment_undo_restores_the_counters() {
+        // x0 + x1 + x2, and the clause (x1 or not x3)
+        let mut a = Assignment::new(&[vec![(0, true), (1, true), (2, true)], vec![(1, true), (3, false)]], 4);
+        let mark = a.mark();
+        a.assign(1, false);
+        a.assign(3, true);
+        assert_eq!((a.hits.clone(), a.misses.clone()), (vec![0, 0], vec![1, 2]));
+        assert_eq!(a.open_in(0), 2);
+        // the clause is lost, so only the first constraint links 0 and 2
+        assert_eq!(a.components(&[0, 2], |a, c| a.open_in(c) > 0), vec![vec![0, 2]]);
+        a.undo(mark);
+        assert_eq!((a.hits, a.misses), (vec![0, 0], vec![0, 0]));
+        assert!(a.value.iter().all(Option::is_none));
+    }
+}
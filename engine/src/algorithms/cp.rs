@@ -0,0 +1,336 @@
+/**
+ * Constraint programming over the frontier. Every hidden frontier cell is a 0/1 domain and
+ * every clue a linear equality. Bounds consistency on such an equality empties the free cells
+ * once the clue is met and fills them once it can only just be met. After every decision the
+ * propagation runs to a fixpoint over the whole component. The search branches on the most
+ * constrained cell and re-splits the open cells into components at every node, so parts that a
+ * decision cut apart are counted on their own and multiplied, not enumerated together.
+ * Counts are kept per mine total, which is what `probability::combine` needs for exact
+ * marginals under the global mine count.
+ */
+use crate::board::Board;
+use crate::algorithms::{probability, Algorithm, SolverResult};
+use crate::algorithms::probability::{ComponentCounts, ProbabilityMap};
+use crate::algorithms::counting::{Assignment, Tally};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// search nodes per top-level component before the shared probability engine takes over
+const MAX_NODES: usize = 400_000;
+
+/// solutions of a cell's component (clues only) with the cell safe and with it a mine
+#[derive(Clone, Debug, Serialize)]
+pub struct ValueCount {
+    pub cell: usize,
+    pub safe: f64,
+    pub mine: f64,
+}
+
+impl Tally<f64> {
+    /// full-width and scaled like the enumerator's output
+    fn into_component(self) -> ComponentCounts {
+        let max = self.counts.iter().cloned().fold(0.0, f64::max);
+        let scale = if max > 0.0 { max } else { 1.0 };
+        let n = self.cells.len();
+        let mut counts = vec![0.0; n + 1];
+        let mut cell_counts = vec![vec![0.0; n]; n + 1];
+        for (k, &c) in self.counts.iter().enumerate() {
+            counts[self.low + k] = c / scale;
+        }
+        for (i, row) in self.rows().enumerate() {
+            for (k, &c) in row.iter().enumerate() {
+                cell_counts[self.low + k][i] = c / scale;
+            }
+        }
+        ComponentCounts { cells: self.cells, counts, cell_counts, log_scale: scale.ln(), exact: true }
+    }
+}
+
+struct Search {
+    /// mines per clue; cells are the assignment's members, mines so far its hits
+    mines: Vec<usize>,
+    assignment: Assignment,
+    queued: Vec<bool>,
+    nodes: usize,
+}
+
+impl Search {
+    fn new(constraints: Vec<(Vec<usize>, usize)>, vars: usize) -> Self {
+        let cells: Vec<Vec<(usize, bool)>> = constraints.iter().map(|(cells, _)| cells.iter().map(|&v| (v, true)).collect()).collect();
+        Self {
+            mines: constraints.iter().map(|&(_, mines)| mines).collect(),
+            assignment: Assignment::new(&cells, vars),
+            queued: vec![false; constraints.len()],
+            nodes: 0,
+        }
+    }
+
+    /// bounds consistency from the given constraints to a fixpoint, false on a wipe-out
+    fn propagate(&mut self, start: &[usize]) -> bool {
+        let mut queue: Vec<usize> = start.to_vec();
+        for &c in start { self.queued[c] = true; }
+        let mut ok = true;
+        while let Some(c) = queue.pop() {
+            self.queued[c] = false;
+            if !ok { continue; }
+            let (ones, free, mines) = (self.assignment.hits[c], self.assignment.open_in(c), self.mines[c]);
+            if ones > mines || ones + free < mines {
+                ok = false;
+                continue;
+            }
+            if free == 0 { continue; }
+            // met: the rest is empty; only just reachable: the rest are mines
+            let fill = if ones == mines { false } else if ones + free == mines { true } else { continue };
+            for i in 0..self.assignment.members[c].len() {
+                let v = self.assignment.members[c][i];
+                if self.assignment.value[v].is_some() { continue; }
+                self.assignment.assign(v, fill);
+                for &(d, _) in &self.assignment.occurrences[v] {
+                    if !self.queued[d] {
+                        self.queued[d] = true;
+                        queue.push(d);
+                    }
+                }
+            }
+        }
+        ok
+    }
+
+    /// open cells split by the clues that still have open cells
+    fn components(&mut self, open: &[usize]) -> Vec<Vec<usize>> {
+        // a clue with no open cells left no longer ties anything together
+        self.assignment.components(open, |a, c| a.open_in(c) > 0)
+    }
+
+    /// open cell in the tightest clue (fewest ways left to place its mines), then the one in most clues
+    fn most_constrained(&self, open: &[usize]) -> usize {
+        let a = &self.assignment;
+        *open.iter()
+            .min_by_key(|&&v| {
+                let active = a.occurrences[v].iter().map(|&(c, _)| c).filter(|&c| a.open_in(c) > 0);
+                let (tightest, degree) = active.fold((usize::MAX, 0), |(tightest, degree), c| {
+                    let need = self.mines[c] - a.hits[c];
+                    (tightest.min(need.min(a.open_in(c) - need)), degree + 1)
+                });
+                (tightest, usize::MAX - degree, v)
+            })
+            .expect("open is not empty")
+    }
+
+    /// counts the solutions of `cells` under the current assignment, none past the node budget
+    fn count(&mut self, cells: &[usize]) -> Option<Tally<f64>> {
+        self.nodes += 1;
+        if self.nodes > MAX_NODES { return None; }
+
+        let (open, assigned): (Vec<usize>, Vec<usize>) = cells.iter().partition(|&&v| self.assignment.value[v].is_none());
+        if open.is_empty() {
+            return Some(Tally::fixed(cells.to_vec(), &self.assignment.value));
+        }
+        let groups = self.components(&open);
+        if groups.len() > 1 {
+            let mut result = Tally::fixed(assigned, &self.assignment.value);
+            for group in groups {
+                result = result.product(&self.count(&group)?);
+            }
+            return Some(result.reorder(cells));
+        }
+
+        let var = self.most_constrained(&open);
+        // every branch comes back in the order of `cells`, so adding them lines up
+        let mut result = Tally::empty(cells.to_vec());
+        for value in [true, false] {
+            let mark = self.assignment.mark();
+            self.assignment.assign(var, value);
+            let start: Vec<usize> = self.assignment.occurrences[var].iter().map(|&(c, _)| c).collect();
+            if self.propagate(&start) {
+                let Some(branch) = self.count(cells) else {
+                    self.assignment.undo(mark);
+                    return None;
+                };
+                result.add(branch);
+            }
+            self.assignment.undo(mark);
+        }
+        Some(result)
+    }
+}
+
+/// solution counts of every frontier component (clues only), none if the clues contradict
+/// each other or a component needs more than `MAX_NODES` nodes
+pub fn count_components(board: &Board) -> Option<Vec<ComponentCounts>> {
+    let constraints = probability::collect_constraints(board);
+    let mut frontier: Vec<usize> = constraints.iter().flat_map(|c| c.cells.iter().copied()).collect();
+    frontier.sort_unstable();
+    frontier.dedup();
+    let column: HashMap<usize, usize> = frontier.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+    let rows = constraints.iter()
+        .map(|c| (c.cells.iter().map(|cell| column[cell]).collect(), c.mines))
+        .collect();
+
+    let mut search = Search::new(rows, frontier.len());
+    let all: Vec<usize> = (0..search.mines.len()).collect();
+    if !search.propagate(&all) { return None; }
+
+    // cells settled at the root are one constant component, the rest split as usual
+    let (open, assigned): (Vec<usize>, Vec<usize>) = (0..frontier.len()).partition(|&v| search.assignment.value[v].is_none());
+    let mut parts = vec![Tally::fixed(assigned, &search.assignment.value)];
+    for group in search.components(&open) {
+        search.nodes = 0;
+        parts.push(search.count(&group)?);
+    }
+
+    let mut components = Vec::with_capacity(parts.len());
+    for mut part in parts {
+        if part.counts.iter().all(|&c| c == 0.0) { return None; }
+        part.cells = part.cells.iter().map(|&v| frontier[v]).collect();
+        components.push(part.into_component());
+    }
+    Some(components)
+}
+
+/// exact mine probabilities from the cp counts, none where `count_components` gives none
+pub fn probabilities(board: &Board) -> Option<ProbabilityMap> {
+    count_components(board).map(|components| probability::combine(board, &components))
+}
+
+/// per frontier cell: solutions of its component with the cell safe and with it a mine
+pub fn value_counts(board: &Board) -> Option<Vec<ValueCount>> {
+    let components = count_components(board)?;
+    let scale = |c: &ComponentCounts, x: f64| x * c.log_scale.exp();
+    Some(components.iter()
+        .flat_map(|c| {
+            let total: f64 = c.counts.iter().sum();
+            c.cells.iter().enumerate().map(move |(i, &cell)| {
+                let mine: f64 = c.cell_counts.iter().map(|row| row[i]).sum();
+                ValueCount { cell, safe: scale(c, total - mine), mine: scale(c, mine) }
+            })
+        })
+        .collect())
+}
+
+/// constraint programming solver
+/// cells with probability 0 under the exact counts are played as safe, otherwise the safest cell
+#[allow(dead_code)]
+pub struct CpSolver {
+    width: usize,
+    height: usize,
+    mines: usize,
+}
+
+impl CpSolver {
+    pub fn new(width: usize, height: usize, mines: usize) -> Self {
+        Self { width, height, mines }
+    }
+}
+
+impl Algorithm for CpSolver {
+    fn find_candidates(&mut self, board: &Board) -> SolverResult {
+        let Some(map) = probabilities(board) else {
+            return probability::safest_move(board);
+        };
+        let candidates = map.safest_cells(board);
+        let proven = map.proven_safe(&candidates);
+        if proven.is_empty() {
+            SolverResult { candidates, is_guess: true }
+        } else {
+            SolverResult { candidates: proven, is_guess: false }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+
+    /// over every frontier assignment that meets the clues: solutions per mine total, and per
+    /// cell the solutions with it a mine
+    fn brute_force(board: &Board) -> (Vec<usize>, Vec<f64>, HashMap<usize, f64>) {
+        let constraints = probability::collect_constraints(board);
+        let mut frontier: Vec<usize> = constraints.iter().flat_map(|c| c.cells.iter().copied()).collect();
+        frontier.sort_unstable();
+        frontier.dedup();
+        let mut totals = vec![0.0; frontier.len() + 1];
+        let mut mines: HashMap<usize, f64> = frontier.iter().map(|&c| (c, 0.0)).collect();
+        for bits in 0..1u64 << frontier.len() {
+            let is_mine = |cell: &usize| (bits >> frontier.binary_search(cell).unwrap()) & 1 == 1;
+            if !constraints.iter().all(|c| c.cells.iter().filter(|cell| is_mine(cell)).count() == c.mines) { continue; }
+            totals[bits.count_ones() as usize] += 1.0;
+            for cell in frontier.iter().filter(|cell| is_mine(cell)) { *mines.get_mut(cell).unwrap() += 1.0; }
+        }
+        (frontier, totals, mines)
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn counts_match_brute_force() {
+        let mut checked = 0;
+        for seed in 0..30 {
+            let mut sim = Simulator::new(vec![5, 5], 6, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            for _ in 0..5 {
+                if sim.board.game_over { break; }
+                let (frontier, totals, mines) = brute_force(&sim.board);
+                if frontier.is_empty() || frontier.len() > 18 {
+                    sim.run_step().unwrap();
+                    continue;
+                }
+                let components = count_components(&sim.board).expect("consistent clues");
+
+                // the components multiply out to the frontier counts per mine total
+                let mut product = vec![1.0];
+                for c in &components {
+                    let counts: Vec<f64> = c.counts.iter().map(|x| x * c.log_scale.exp()).collect();
+                    let mut next = vec![0.0; product.len() + counts.len() - 1];
+                    for (i, a) in product.iter().enumerate() {
+                        for (j, b) in counts.iter().enumerate() { next[i + j] += a * b; }
+                    }
+                    product = next;
+                }
+                product.resize(totals.len().max(product.len()), 0.0);
+                for (m, &expected) in totals.iter().enumerate() {
+                    assert!(close(product[m], expected), "seed {seed}, {m} mines: {} vs {expected}", product[m]);
+                }
+
+                // a cell's counts times the solutions of the other components give the frontier counts
+                let all: f64 = totals.iter().sum();
+                let component_total: HashMap<usize, f64> = components.iter()
+                    .flat_map(|c| {
+                        let total = c.counts.iter().sum::<f64>() * c.log_scale.exp();
+                        c.cells.iter().map(move |&cell| (cell, total))
+                    })
+                    .collect();
+                for value in value_counts(&sim.board).unwrap() {
+                    let others = all / component_total[&value.cell];
+                    assert!(close(value.mine * others, mines[&value.cell]), "seed {seed} cell {}", value.cell);
+                    assert!(close(value.safe * others, all - mines[&value.cell]), "seed {seed} cell {}", value.cell);
+                }
+                checked += 1;
+                sim.run_step().unwrap();
+            }
+        }
+        assert!(checked > 20);
+    }
+
+    #[test]
+    fn probabilities_match_enumeration() {
+        for seed in 0..20 {
+            let mut sim = Simulator::new(vec![5, 5], 6, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            for _ in 0..5 {
+                if sim.board.game_over { break; }
+                let map = probabilities(&sim.board).expect("consistent clues");
+                let expected = probability::enumerate_probabilities(&sim.board);
+                for (i, cell) in sim.board.cells.iter().enumerate() {
+                    if cell.is_revealed { continue; }
+                    assert!((map.cell_probs[i] - expected.cell_probs[i]).abs() < 1e-9, "seed {seed} cell {i}");
+                }
+                sim.run_step().unwrap();
+            }
+        }
+    }
+}
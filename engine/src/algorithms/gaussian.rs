@@ -0,0 +1,202 @@
+// src/algorithms/gaussian.rs
+use crate::board::Board;
+use crate::algorithms::{probability, Algorithm, SolverResult};
+use crate::algorithms::probability::LinearConstraint;
+use std::collections::HashMap;
+
+/// linear algebra solver
+/// writes the clues as a 0/1 matrix (one row per revealed number, one column per frontier cell),
+/// row-reduces it over the integers and runs bounds reasoning on every reduced row.
+/// sits between the pairwise subset rule of the exact solver and full sat
+#[allow(dead_code)]
+pub struct GaussianSolver {
+    width: usize,
+    height: usize,
+    mines: usize,
+}
+
+impl GaussianSolver {
+    pub fn new(width: usize, height: usize, mines: usize) -> Self {
+        Self { width, height, mines }
+    }
+
+    fn solve(&self, board: &Board) -> SolverResult {
+        let safe: Vec<usize> = Self::decide(board).into_iter()
+            .filter(|&(_, mine)| !mine)
+            .map(|(cell, _)| cell)
+            .collect();
+
+        if safe.is_empty() {
+            return probability::safest_move(board);
+        }
+        SolverResult { candidates: safe, is_guess: false }
+    }
+
+    /// repeats elimination + bounds until no new cell is decided.
+    /// returns (cell, is a mine) for every decided frontier cell
+    fn decide(board: &Board) -> Vec<(usize, bool)> {
+        let constraints = probability::collect_constraints(board);
+        let mut frontier: Vec<usize> = constraints.iter().flat_map(|c| c.cells.iter().copied()).collect();
+        frontier.sort_unstable();
+        frontier.dedup();
+        let column: HashMap<usize, usize> = frontier.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+
+        // known[col] = Some(true) for a forced mine, Some(false) for a forced safe cell
+        let mut known: Vec<Option<bool>> = vec![None; frontier.len()];
+        loop {
+            let mut rows = Self::build_rows(&constraints, &column, &known);
+            Self::row_reduce(&mut rows, frontier.len());
+
+            let mut changed = false;
+            for row in &rows {
+                for (col, value) in Self::bounds(row) {
+                    if known[col].is_none() {
+                        known[col] = Some(value);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed { break; }
+        }
+
+        known.iter().enumerate()
+            .filter_map(|(col, k)| k.map(|mine| (frontier[col], mine)))
+            .collect()
+    }
+
+    /// one row per clue: coefficients over frontier columns and the right-hand side,
+    /// with already decided cells moved to the right
+    fn build_rows(constraints: &[LinearConstraint], column: &HashMap<usize, usize>, known: &[Option<bool>]) -> Vec<Row> {
+        constraints.iter()
+            .map(|c| {
+                let mut coefs = vec![0i64; column.len()];
+                let mut rhs = c.mines as i64;
+                for cell in &c.cells {
+                    let col = column[cell];
+                    match known[col] {
+                        Some(true) => rhs -= 1,
+                        Some(false) => {}
+                        None => coefs[col] = 1,
+                    }
+                }
+                Row { coefs, rhs }
+            })
+            .filter(|r| r.coefs.iter().any(|&a| a != 0))
+            .collect()
+    }
+
+    /// fraction-free gauss-jordan elimination, rows are kept primitive (divided by their gcd)
+    fn row_reduce(rows: &mut [Row], columns: usize) {
+        let mut pivot_row = 0;
+        for col in 0..columns {
+            if pivot_row == rows.len() { break; }
+            let Some(found) = (pivot_row..rows.len()).find(|&r| rows[r].coefs[col] != 0) else {
+                continue;
+            };
+            rows.swap(pivot_row, found);
+
+            let pivot = rows[pivot_row].clone();
+            for (r, row) in rows.iter_mut().enumerate() {
+                let factor = row.coefs[col];
+                if r == pivot_row || factor == 0 { continue; }
+                // row = row * pivot - pivot_row * factor  (keeps everything integral)
+                let p = pivot.coefs[col];
+                let combine = |a: i64, b: i64| a.checked_mul(p)?.checked_sub(b.checked_mul(factor)?);
+                let coefs: Option<Vec<i64>> = row.coefs.iter().zip(&pivot.coefs).map(|(&a, &b)| combine(a, b)).collect();
+                match (coefs, combine(row.rhs, pivot.rhs)) {
+                    (Some(coefs), Some(rhs)) => {
+                        *row = Row { coefs, rhs };
+                        row.normalize();
+                    }
+                    // coefficients outgrew i64: dropping the row loses information but stays sound
+                    _ => *row = Row { coefs: vec![0; columns], rhs: 0 },
+                }
+            }
+            pivot_row += 1;
+        }
+    }
+
+    /// bounds reasoning on sum(a_j * x_j) = b with x_j in {0, 1}:
+    /// a value of x_j that pushes the reachable range past b is impossible
+    fn bounds(row: &Row) -> Vec<(usize, bool)> {
+        let min: i64 = row.coefs.iter().filter(|&&a| a < 0).sum();
+        let max: i64 = row.coefs.iter().filter(|&&a| a > 0).sum();
+        let b = row.rhs;
+
+        let mut forced = Vec::new();
+        for (col, &a) in row.coefs.iter().enumerate() {
+            if a == 0 { continue; }
+            // range of the sum with x_j fixed to 1 / to 0
+            let (min_one, max_one) = if a > 0 { (min + a, max) } else { (min, max + a) };
+            let (min_zero, max_zero) = if a > 0 { (min, max - a) } else { (min - a, max) };
+            let one_possible = min_one <= b && b <= max_one;
+            let zero_possible = min_zero <= b && b <= max_zero;
+            match (zero_possible, one_possible) {
+                (true, false) => forced.push((col, false)),
+                (false, true) => forced.push((col, true)),
+                _ => {}
+            }
+        }
+        forced
+    }
+}
+
+#[derive(Clone)]
+struct Row {
+    coefs: Vec<i64>,
+    rhs: i64,
+}
+
+impl Row {
+    fn normalize(&mut self) {
+        let g = self.coefs.iter().fold(self.rhs.abs(), |g, &a| gcd(g, a.abs()));
+        if g > 1 {
+            for a in &mut self.coefs { *a /= g; }
+            self.rhs /= g;
+        }
+    }
+}
+
+fn gcd(mut a: i64, mut b: i64) -> i64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Algorithm for GaussianSolver {
+    fn find_candidates(&mut self, board: &Board) -> SolverResult {
+        // agent handles first move, solver provides next logical steps
+        self.solve(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+
+    /// every decided cell is a 0 or 1 marginal: no layout the clues allow disagrees
+    #[test]
+    fn decisions_are_sound() {
+        let mut decided = 0;
+        for (dims, mines) in [(vec![4, 5], 5), (vec![6, 6], 9), (vec![3, 3, 3], 5)] {
+            for seed in 0..20 {
+                let mut sim = Simulator::new(dims.clone(), mines, WasmAlgorithmType::ExactSolver).unwrap();
+                sim.set_seed(seed).unwrap();
+                sim.run_step().unwrap();
+                while !sim.board.game_over {
+                    let map = probability::enumerate_probabilities(&sim.board);
+                    for (cell, mine) in GaussianSolver::decide(&sim.board) {
+                        let expected = if mine { 1.0 } else { 0.0 };
+                        assert!((map.cell_probs[cell] - expected).abs() < 1e-12, "{dims:?} seed {seed} cell {cell}");
+                        decided += 1;
+                    }
+                    sim.run_step().unwrap();
+                }
+            }
+        }
+        assert!(decided > 0);
+    }
+}
@@ -0,0 +1,464 @@
+/**
+ * Pure-Rust 0-1 integer programming, so the MILP approach also runs in WASM.
+ * The model is the one `scip_solver.rs` builds: every clue is an equality over binary cell
+ * variables, and with the global count an interior integer joins the mine total. A node of
+ * the branch-and-bound first propagates bounds over the equalities, then a bounded-variable
+ * phase-1 simplex decides whether the LP relaxation is feasible; a fractional LP point is
+ * branched on, depth first.
+ */
+use crate::board::Board;
+use crate::algorithms::{probability, Algorithm, SolverConfig, SolverResult};
+use crate::algorithms::sat_utils::global_counts;
+use std::collections::HashMap;
+
+/// branch-and-bound nodes per solve before it reports `IlpOutcome::Unknown`
+const NODE_LIMIT: usize = 20_000;
+/// pivot tolerance of the simplex
+const EPS: f64 = 1e-9;
+/// the LP is feasible when the artificial variables sum to at most this
+const FEASIBILITY_TOL: f64 = 1e-7;
+
+/// result of `IntegerProgram::solve`
+#[derive(Clone, Debug, PartialEq)]
+pub enum IlpOutcome {
+    Infeasible,
+    /// one integer point, indexed like the variables
+    Feasible(Vec<i64>),
+    /// the node limit was hit first
+    Unknown,
+}
+
+/// sum(vars) == rhs constraints over bounded integer variables (every coefficient is 1)
+#[derive(Clone, Debug, Default)]
+pub struct IntegerProgram {
+    rows: Vec<(Vec<usize>, i64)>,
+    lower: Vec<i64>,
+    upper: Vec<i64>,
+}
+
+impl IntegerProgram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds an integer variable in [lower, upper] and returns its index
+    pub fn add_var(&mut self, lower: i64, upper: i64) -> usize {
+        self.lower.push(lower);
+        self.upper.push(upper);
+        self.lower.len() - 1
+    }
+
+    pub fn add_equality(&mut self, vars: Vec<usize>, rhs: i64) {
+        self.rows.push((vars, rhs));
+    }
+
+    pub fn set_bounds(&mut self, var: usize, lower: i64, upper: i64) {
+        self.lower[var] = lower;
+        self.upper[var] = upper;
+    }
+
+    pub fn bounds(&self, var: usize) -> (i64, i64) {
+        (self.lower[var], self.upper[var])
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.lower.len()
+    }
+
+    /// depth-first branch-and-bound for any integer point
+    pub fn solve(&self) -> IlpOutcome {
+        let mut stack = vec![(self.lower.clone(), self.upper.clone())];
+        let mut nodes = 0;
+        while let Some((mut lower, mut upper)) = stack.pop() {
+            nodes += 1;
+            if nodes > NODE_LIMIT { return IlpOutcome::Unknown; }
+            if !propagate(&self.rows, &mut lower, &mut upper) { continue; }
+            // propagation checked every row, so a fully fixed node is a solution
+            if lower == upper { return IlpOutcome::Feasible(lower); }
+
+            let (var, split) = match lp_point(&self.rows, &lower, &upper) {
+                Relaxation::Empty => continue,
+                Relaxation::Point(point) => {
+                    let distance = |v: f64| (v - v.floor() - 0.5).abs();
+                    let fractional = point.iter()
+                        .enumerate()
+                        .filter(|&(_, &v)| (v - v.round()).abs() > FEASIBILITY_TOL)
+                        .min_by(|a, b| distance(*a.1).total_cmp(&distance(*b.1)));
+                    let Some((var, &value)) = fractional else {
+                        return IlpOutcome::Feasible(point.iter().map(|v| v.round() as i64).collect());
+                    };
+                    // most fractional variable
+                    (var, value.floor() as i64)
+                }
+                // no verdict from the LP: split the first open domain in half
+                Relaxation::Stalled => {
+                    let var = (0..lower.len()).find(|&v| lower[v] < upper[v]).expect("node has an open variable");
+                    (var, lower[var] + (upper[var] - lower[var]) / 2)
+                }
+            };
+
+            // the "up" branch goes on top of the stack
+            let mut down = upper.clone();
+            down[var] = split;
+            stack.push((lower.clone(), down));
+            lower[var] = split + 1;
+            stack.push((lower, upper));
+        }
+        IlpOutcome::Infeasible
+    }
+}
+
+/// bounds consistency over the equalities until nothing changes, false on a contradiction
+fn propagate(rows: &[(Vec<usize>, i64)], lower: &mut [i64], upper: &mut [i64]) -> bool {
+    loop {
+        let mut changed = false;
+        for (vars, rhs) in rows {
+            let min: i64 = vars.iter().map(|&v| lower[v]).sum();
+            let max: i64 = vars.iter().map(|&v| upper[v]).sum();
+            if *rhs < min || *rhs > max { return false; }
+            for &v in vars {
+                // the others take at least min - lower[v] and at most max - upper[v]
+                let hi = upper[v].min(rhs - (min - lower[v]));
+                let lo = lower[v].max(rhs - (max - upper[v]));
+                if lo > hi { return false; }
+                if lo != lower[v] || hi != upper[v] {
+                    lower[v] = lo;
+                    upper[v] = hi;
+                    changed = true;
+                }
+            }
+        }
+        if !changed { return true; }
+    }
+}
+
+/// what the LP relaxation of a node says
+enum Relaxation {
+    Empty,
+    Point(Vec<f64>),
+    /// the simplex hit its iteration limit
+    Stalled,
+}
+
+/// a point of the LP relaxation under the given bounds
+fn lp_point(rows: &[(Vec<usize>, i64)], lower: &[i64], upper: &[i64]) -> Relaxation {
+    // fixed variables move to the right-hand side, free ones are shifted to [0, upper - lower]
+    let free: Vec<usize> = (0..lower.len()).filter(|&v| lower[v] < upper[v]).collect();
+    let column: HashMap<usize, usize> = free.iter().enumerate().map(|(c, &v)| (v, c)).collect();
+    let mut matrix = Vec::new();
+    let mut rhs = Vec::new();
+    for (vars, b) in rows {
+        let mut row = vec![0.0; free.len()];
+        let mut residual = *b as f64;
+        for &v in vars {
+            residual -= lower[v] as f64;
+            if let Some(&c) = column.get(&v) { row[c] += 1.0; }
+        }
+        if row.iter().all(|&a| a == 0.0) { continue; } // settled by propagation
+        matrix.push(row);
+        rhs.push(residual);
+    }
+    let bounds: Vec<f64> = free.iter().map(|&v| (upper[v] - lower[v]) as f64).collect();
+
+    let Some(result) = Simplex::phase_one(matrix, rhs, &bounds) else { return Relaxation::Stalled; };
+    let Some(values) = result else { return Relaxation::Empty; };
+    let mut point: Vec<f64> = lower.iter().map(|&l| l as f64).collect();
+    for (c, &v) in free.iter().enumerate() {
+        point[v] += values[c];
+    }
+    Relaxation::Point(point)
+}
+
+/// dense tableau of a bounded-variable simplex: nonbasic columns sit at one of their bounds,
+/// the basic ones take what the rows leave. entering and leaving columns follow Bland's rule,
+/// so degenerate pivots can't cycle
+struct Simplex {
+    tableau: Vec<Vec<f64>>,
+    /// reduced costs of the phase-1 objective (sum of the artificial columns)
+    cost: Vec<f64>,
+    basis: Vec<usize>,
+    value: Vec<f64>,
+    upper: Vec<f64>,
+}
+
+impl Simplex {
+    /// values of the structural columns for some 0 <= x <= upper with matrix * x == rhs
+    /// (inner none if there is none), outer none if the iteration limit was hit
+    fn phase_one(matrix: Vec<Vec<f64>>, rhs: Vec<f64>, upper: &[f64]) -> Option<Option<Vec<f64>>> {
+        let n = upper.len();
+        let m = rhs.len();
+        let cols = n + m;
+
+        // structural columns start at 0, one artificial column per row takes |rhs|
+        let mut tableau = Vec::with_capacity(m);
+        let mut value = vec![0.0; cols];
+        for (i, mut row) in matrix.into_iter().enumerate() {
+            let sign = if rhs[i] < 0.0 { -1.0 } else { 1.0 };
+            for a in row.iter_mut() { *a *= sign; }
+            row.resize(cols, 0.0);
+            row[n + i] = 1.0;
+            tableau.push(row);
+            value[n + i] = rhs[i].abs();
+        }
+        let mut cost = vec![0.0; cols];
+        for j in 0..n {
+            cost[j] = -tableau.iter().map(|row| row[j]).sum::<f64>();
+        }
+        let mut bounds = upper.to_vec();
+        bounds.resize(cols, f64::INFINITY);
+
+        let mut simplex = Simplex { tableau, cost, basis: (n..cols).collect(), value, upper: bounds };
+        if !simplex.run(n) { return None; }
+
+        let infeasibility: f64 = simplex.value[n..].iter().sum();
+        Some((infeasibility <= FEASIBILITY_TOL).then(|| simplex.value[..n].to_vec()))
+    }
+
+    /// pivots until no column improves, false if that takes more than the iteration limit
+    fn run(&mut self, structural: usize) -> bool {
+        let mut is_basic = vec![false; self.value.len()];
+        for &b in &self.basis { is_basic[b] = true; }
+        let limit = 50 * self.value.len().max(1);
+
+        for _ in 0..limit {
+            // entering column: lowest index that improves the objective
+            let entering = (0..self.value.len()).find_map(|j| {
+                if is_basic[j] || self.upper[j] <= EPS { return None; }
+                let at_lower = self.value[j] <= EPS;
+                if at_lower && self.cost[j] < -EPS { return Some((j, 1.0)); }
+                if !at_lower && self.cost[j] > EPS { return Some((j, -1.0)); }
+                None
+            });
+            let Some((j, direction)) = entering else { return true; };
+
+            // ratio test against the bound flip of j itself
+            let mut step = self.upper[j];
+            let mut leaving: Option<(usize, bool)> = None; // (row, leaves at its upper bound)
+            for (i, row) in self.tableau.iter().enumerate() {
+                let rate = row[j] * direction;
+                let b = self.basis[i];
+                let (room, to_upper) = if rate > EPS {
+                    (self.value[b] / rate, false)
+                } else if rate < -EPS && self.upper[b].is_finite() {
+                    ((self.upper[b] - self.value[b]) / -rate, true)
+                } else {
+                    continue;
+                };
+                let room = room.max(0.0);
+                let better = match leaving {
+                    None => room <= step,
+                    Some((r, _)) => room < step - EPS || (room <= step + EPS && b < self.basis[r]),
+                };
+                if better {
+                    step = room;
+                    leaving = Some((i, to_upper));
+                }
+            }
+
+            for (i, row) in self.tableau.iter().enumerate() {
+                self.value[self.basis[i]] -= row[j] * direction * step;
+            }
+            self.value[j] += direction * step;
+
+            let Some((r, to_upper)) = leaving else { continue; }; // bound flip, no pivot
+            let out = self.basis[r];
+            self.value[out] = if to_upper { self.upper[out] } else { 0.0 };
+            // an artificial column that left the basis is never needed again
+            if out >= structural { self.upper[out] = 0.0; }
+            self.pivot(r, j);
+            is_basic[out] = false;
+            is_basic[j] = true;
+        }
+        false
+    }
+
+    fn pivot(&mut self, r: usize, j: usize) {
+        let scale = self.tableau[r][j];
+        for a in self.tableau[r].iter_mut() { *a /= scale; }
+        let pivot_row = self.tableau[r].clone();
+        for (i, row) in self.tableau.iter_mut().enumerate() {
+            let factor = row[j];
+            if i == r || factor == 0.0 { continue; }
+            for (a, p) in row.iter_mut().zip(&pivot_row) { *a -= factor * p; }
+        }
+        let factor = self.cost[j];
+        for (c, p) in self.cost.iter_mut().zip(&pivot_row) { *c -= factor * p; }
+        self.basis[r] = j;
+    }
+}
+
+/// branch-and-bound solver
+/// builds one integer program per move and asks it, cell by cell, whether the cell can be a mine.
+/// like SCIP, a cell is only probed if no point found so far has a mine there
+#[allow(dead_code)]
+pub struct IlpSolver {
+    width: usize,
+    height: usize,
+    mines: usize,
+    global_mine_count: bool,
+}
+
+impl IlpSolver {
+    pub fn new(width: usize, height: usize, mines: usize) -> Self {
+        Self { width, height, mines, global_mine_count: false }
+    }
+
+    fn solve(&self, board: &Board) -> SolverResult {
+        let constraints = probability::collect_constraints(board);
+        if constraints.is_empty() {
+            return probability::safest_move(board);
+        }
+        let mut frontier: Vec<usize> = constraints.iter().flat_map(|c| c.cells.iter().copied()).collect();
+        frontier.sort_unstable();
+        frontier.dedup();
+        let column: HashMap<usize, usize> = frontier.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+
+        // x_i in {0,1} per frontier cell, sum x == mines per clue
+        let mut program = IntegerProgram::new();
+        for _ in &frontier { program.add_var(0, 1); }
+        for c in &constraints {
+            program.add_equality(c.cells.iter().map(|cell| column[cell]).collect(), c.mines as i64);
+        }
+        // with the global count: interior y in [0, interior] and sum x + y == remaining
+        let (remaining, interior) = global_counts(board, &frontier);
+        let interior_var = self.global_mine_count.then(|| {
+            let y = program.add_var(0, interior.len() as i64);
+            program.add_equality((0..=frontier.len()).collect(), remaining as i64);
+            y
+        });
+
+        let first = program.solve();
+        let IlpOutcome::Feasible(point) = first else {
+            // contradictory clues (or too hard): leave it to the probability engine
+            return probability::safest_move(board);
+        };
+        let mut possible_mine: Vec<bool> = point[..frontier.len()].iter().map(|&v| v == 1).collect();
+        let mut interior_mine = interior_var.is_some_and(|y| point[y] > 0);
+
+        for i in 0..frontier.len() {
+            if possible_mine[i] { continue; }
+            program.set_bounds(i, 1, 1);
+            match program.solve() {
+                IlpOutcome::Infeasible => {}
+                IlpOutcome::Unknown => possible_mine[i] = true,
+                IlpOutcome::Feasible(point) => {
+                    for (mine, &v) in possible_mine.iter_mut().zip(&point) { *mine |= v == 1; }
+                    interior_mine |= interior_var.is_some_and(|y| point[y] > 0);
+                }
+            }
+            program.set_bounds(i, 0, 1);
+        }
+
+        let mut safe: Vec<usize> = frontier.iter().zip(&possible_mine)
+            .filter(|&(_, &mine)| !mine)
+            .map(|(&cell, _)| cell)
+            .collect();
+
+        // endgame: the interior can't hold a single mine -> all of it is safe
+        if let Some(y) = interior_var.filter(|_| !interior.is_empty() && !interior_mine) {
+            program.set_bounds(y, 1, interior.len() as i64);
+            if program.solve() == IlpOutcome::Infeasible {
+                safe.extend(interior);
+            }
+        }
+
+        if safe.is_empty() {
+            return probability::safest_move(board);
+        }
+        SolverResult { candidates: safe, is_guess: false }
+    }
+}
+
+impl Algorithm for IlpSolver {
+    fn find_candidates(&mut self, board: &Board) -> SolverResult {
+        self.solve(board)
+    }
+
+    fn configure(&mut self, config: &SolverConfig) {
+        self.global_mine_count = config.global_mine_count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    fn brute_force_feasible(program: &IntegerProgram) -> bool {
+        let n = program.num_vars();
+        (0..1u32 << n).any(|bits| {
+            let value = |v: usize| ((bits >> v) & 1) as i64;
+            (0..n).all(|v| (program.lower[v]..=program.upper[v]).contains(&value(v)))
+                && program.rows.iter().all(|(vars, rhs)| vars.iter().map(|&v| value(v)).sum::<i64>() == *rhs)
+        })
+    }
+
+    #[test]
+    fn feasibility_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..300 {
+            let mut program = IntegerProgram::new();
+            let n = rng.gen_range(1..=8);
+            for _ in 0..n { program.add_var(0, 1); }
+            for _ in 0..rng.gen_range(1..=5) {
+                let vars: Vec<usize> = (0..n).filter(|_| rng.gen_bool(0.5)).collect();
+                let rhs = rng.gen_range(0..=vars.len()) as i64;
+                program.add_equality(vars, rhs);
+            }
+            if rng.gen_bool(0.3) {
+                let fixed = rng.gen_range(0..=1);
+                program.set_bounds(rng.gen_range(0..n), fixed, fixed);
+            }
+            let outcome = program.solve();
+            assert_eq!(matches!(outcome, IlpOutcome::Feasible(_)), brute_force_feasible(&program), "{:?}", program);
+            if let IlpOutcome::Feasible(point) = outcome {
+                for (vars, rhs) in &program.rows {
+                    assert_eq!(vars.iter().map(|&v| point[v]).sum::<i64>(), *rhs);
+                }
+                for (v, &x) in point.iter().enumerate() {
+                    let (lower, upper) = program.bounds(v);
+                    assert!((lower..=upper).contains(&x));
+                }
+            }
+        }
+    }
+
+    /// with the global count, whole games down to the endgame: the deductions are exactly
+    /// the cells no layout puts a mine on
+    #[test]
+    fn deductions_match_exact_marginals() {
+        let mut endgames = 0;
+        for (dims, mines) in [(vec![4, 5], 5), (vec![6, 6], 12), (vec![3, 3, 3], 4)] {
+            for seed in 0..20 {
+                let mut sim = Simulator::new(dims.clone(), mines, WasmAlgorithmType::ExactSolver).unwrap();
+                sim.set_seed(seed).unwrap();
+                sim.run_step().unwrap();
+                while !sim.board.game_over {
+                    let mut solver = IlpSolver::new(0, 0, mines);
+                    solver.configure(&SolverConfig { global_mine_count: true, ..SolverConfig::default() });
+                    let result = solver.find_candidates(&sim.board);
+                    let map = probability::enumerate_probabilities(&sim.board);
+                    let mut safe: Vec<usize> = (0..sim.board.cells.len())
+                        .filter(|&i| !sim.board.cells[i].is_revealed && map.cell_probs[i] < 1e-12)
+                        .collect();
+                    if !safe.is_empty() {
+                        let mut candidates = result.candidates.clone();
+                        candidates.sort_unstable();
+                        safe.sort_unstable();
+                        assert!(!result.is_guess, "{dims:?} seed {seed}: guessed with safe cells left");
+                        assert_eq!(candidates, safe, "{dims:?} seed {seed}");
+                        // only the mine total can clear a cell no clue touches
+                        if safe.iter().any(|c| !map.frontier.contains(c)) { endgames += 1; }
+                    } else {
+                        assert!(result.is_guess, "{dims:?} seed {seed}: deduced a cell that can hold a mine");
+                    }
+                    sim.run_step().unwrap();
+                }
+            }
+        }
+        assert!(endgames > 0);
+    }
+}
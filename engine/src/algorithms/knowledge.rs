@@ -0,0 +1,268 @@
+/**
+ * What the solvers know about the board, kept up to date between moves. The board logs every
+ * revealed cell, and `sync` replays only the new entries: the revealed cell leaves the hidden
+ * set, the frontier and the clues around it, and if it shows a number it becomes a new clue.
+ * A move costs the cells it revealed times their neighbours, not a scan of the whole board.
+ * Anything else that changes the board (reset, flags, a new or moving mine layout) changes
+ * `Board::epoch`, and the next sync rebuilds from scratch.
+ * The greedy, exact and SAT solvers read their clues, frontier and components from here for
+ * their deductions; guesses still go through `probability::safest_move`, which reads the
+ * board. Cells they prove to be mines or safe can be recorded, so the next move starts from
+ * them.
+ */
+use crate::board::Board;
+use crate::algorithms::probability::LinearConstraint;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// frontier cells that share clues, with those clues
+#[derive(Clone, Debug)]
+pub struct Component {
+    pub cells: Vec<usize>,
+    /// clue cells (keys of `KnowledgeState::clues`)
+    pub clues: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct KnowledgeState {
+    /// board epoch this state matches, none before the first sync
+    epoch: Option<u64>,
+    /// reveal log entries already applied
+    seen: usize,
+    /// per revealed numbered cell with hidden neighbours: those neighbours (not flagged) and
+    /// the mines among them (number minus flags)
+    clues: BTreeMap<usize, LinearConstraint>,
+    /// hidden cells next to a clue (cells only leave it by being revealed)
+    frontier: BTreeSet<usize>,
+    /// hidden, unflagged cells
+    hidden: BTreeSet<usize>,
+    flags: usize,
+    known_mines: BTreeSet<usize>,
+    known_safe: BTreeSet<usize>,
+    /// until the next change
+    components: Option<Vec<Component>>,
+    /// full rebuilds so far (the rest were incremental syncs)
+    rebuilds: usize,
+}
+
+impl KnowledgeState {
+    /// a state synced to `board`
+    pub fn from_board(board: &Board) -> Self {
+        let mut state = Self::default();
+        state.sync(board);
+        state
+    }
+
+    /// catches up with the board: replays new reveals, or rebuilds after any other change
+    pub fn sync(&mut self, board: &Board) {
+        if self.epoch != Some(board.epoch) || board.reveal_log.len() < self.seen {
+            self.rebuild(board);
+            return;
+        }
+        if self.seen == board.reveal_log.len() { return; }
+        for i in self.seen..board.reveal_log.len() {
+            self.apply_reveal(board, board.reveal_log[i]);
+        }
+        self.seen = board.reveal_log.len();
+        self.components = None;
+    }
+
+    fn rebuild(&mut self, board: &Board) {
+        let rebuilds = self.rebuilds + 1;
+        *self = Self {
+            epoch: Some(board.epoch),
+            seen: board.reveal_log.len(),
+            rebuilds,
+            ..Self::default()
+        };
+        for (idx, cell) in board.cells.iter().enumerate() {
+            if cell.is_flagged { self.flags += 1; }
+            else if !cell.is_revealed { self.hidden.insert(idx); }
+            else if cell.adjacent_mines > 0 { self.add_clue(board, idx); }
+        }
+    }
+
+    fn add_clue(&mut self, board: &Board, idx: usize) {
+        let mut hidden = Vec::new();
+        let mut flags = 0;
+        for &n in &board.adjacency_map[idx] {
+            let neighbor = &board.cells[n];
+            if neighbor.is_flagged { flags += 1; } else if !neighbor.is_revealed { hidden.push(n); }
+        }
+        if hidden.is_empty() { return; }
+        self.frontier.extend(hidden.iter().copied());
+        let mines = (board.cells[idx].adjacent_mines as usize).saturating_sub(flags);
+        self.clues.insert(idx, LinearConstraint { cells: hidden, mines });
+    }
+
+    fn apply_reveal(&mut self, board: &Board, idx: usize) {
+        self.hidden.remove(&idx);
+        self.frontier.remove(&idx);
+        self.known_safe.remove(&idx);
+        self.known_mines.remove(&idx);
+        for &n in &board.adjacency_map[idx] {
+            let Some(clue) = self.clues.get_mut(&n) else { continue; };
+            clue.cells.retain(|&c| c != idx);
+            if clue.cells.is_empty() { self.clues.remove(&n); }
+        }
+        // cells revealed later in the same log are already revealed on the board, so the new
+        // clue never holds them and their own replay finds nothing to remove
+        if board.cells[idx].adjacent_mines > 0 { self.add_clue(board, idx); }
+    }
+
+    pub fn epoch(&self) -> Option<u64> {
+        self.epoch
+    }
+
+    pub fn rebuilds(&self) -> usize {
+        self.rebuilds
+    }
+
+    /// clue constraints keyed by their revealed cell, in board order
+    pub fn clues(&self) -> &BTreeMap<usize, LinearConstraint> {
+        &self.clues
+    }
+
+    /// hidden cells next to a clue, in board order
+    pub fn frontier(&self) -> &BTreeSet<usize> {
+        &self.frontier
+    }
+
+    /// hidden, unflagged cells, in board order
+    pub fn hidden(&self) -> &BTreeSet<usize> {
+        &self.hidden
+    }
+
+    pub fn flags(&self) -> usize {
+        self.flags
+    }
+
+    /// hidden cells without a clue, in board order (a walk over the hidden set)
+    pub fn interior(&self) -> Vec<usize> {
+        self.hidden.difference(&self.frontier).copied().collect()
+    }
+
+    /// the board's mine total minus flags
+    pub fn remaining_mines(&self, board: &Board) -> usize {
+        board.mines.saturating_sub(self.flags)
+    }
+
+    /// hidden cells proven to be mines on this or an earlier move
+    pub fn known_mines(&self) -> &BTreeSet<usize> {
+        &self.known_mines
+    }
+
+    /// hidden cells proven to be safe and not yet revealed
+    pub fn known_safe(&self) -> &BTreeSet<usize> {
+        &self.known_safe
+    }
+
+    /// records a proven mine (kept until the board changes other than by reveals)
+    pub fn record_mine(&mut self, cell: usize) {
+        if self.hidden.contains(&cell) { self.known_mines.insert(cell); }
+    }
+
+    /// records a proven safe cell (dropped once it is revealed)
+    pub fn record_safe(&mut self, cell: usize) {
+        if self.hidden.contains(&cell) { self.known_safe.insert(cell); }
+    }
+
+    /// frontier cells grouped by shared clues, computed once per change
+    pub fn components(&mut self) -> &[Component] {
+        if self.components.is_none() {
+            self.components = Some(self.split_components());
+        }
+        self.components.as_deref().expect("set above")
+    }
+
+    fn split_components(&self) -> Vec<Component> {
+        let position: BTreeMap<usize, usize> = self.frontier.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+        let mut parent: Vec<usize> = (0..position.len()).collect();
+        fn find(parent: &mut [usize], mut x: usize) -> usize {
+            while parent[x] != x {
+                parent[x] = parent[parent[x]];
+                x = parent[x];
+            }
+            x
+        }
+        for clue in self.clues.values() {
+            let first = find(&mut parent, position[&clue.cells[0]]);
+            for cell in &clue.cells[1..] {
+                let root = find(&mut parent, position[cell]);
+                parent[root] = first;
+            }
+        }
+
+        let mut by_root: BTreeMap<usize, Component> = BTreeMap::new();
+        for (&cell, &i) in &position {
+            let root = find(&mut parent, i);
+            by_root.entry(root).or_insert_with(|| Component { cells: Vec::new(), clues: Vec::new() }).cells.push(cell);
+        }
+        for (&idx, clue) in &self.clues {
+            let root = find(&mut parent, position[&clue.cells[0]]);
+            by_root.get_mut(&root).expect("every clue cell is in the frontier").clues.push(idx);
+        }
+        // ordered by first cell, like a scan of the board would find them
+        let mut components: Vec<Component> = by_root.into_values().collect();
+        components.sort_by_key(|c| c.cells[0]);
+        components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+
+    #[test]
+    fn a_clone_is_a_new_epoch() {
+        let mut sim = Simulator::new(vec![6, 6], 6, WasmAlgorithmType::ExactSolver).unwrap();
+        sim.set_seed(3).unwrap();
+        sim.run_step().unwrap();
+        let mut state = KnowledgeState::from_board(&sim.board);
+        let copy = sim.board.clone();
+        assert_ne!(copy.epoch, sim.board.epoch);
+        state.sync(&copy);
+        assert_eq!(state.rebuilds(), 2);
+        assert_eq!(state.epoch(), Some(copy.epoch));
+    }
+
+    /// clues, frontier, hidden cells, flags and components
+    type View = (Vec<(usize, Vec<usize>, usize)>, BTreeSet<usize>, BTreeSet<usize>, usize, Vec<(Vec<usize>, Vec<usize>)>);
+
+    /// everything a solver reads, in comparable form
+    fn view(state: &mut KnowledgeState) -> View {
+        let clues = state.clues().iter().map(|(&i, c)| (i, c.cells.clone(), c.mines)).collect();
+        let components = state.components().iter().map(|c| (c.cells.clone(), c.clues.clone())).collect();
+        (clues, state.frontier().clone(), state.hidden().clone(), state.flags(), components)
+    }
+
+    #[test]
+    fn syncing_matches_a_rebuild_with_flags_and_moving_mines() {
+        let mut steps = 0;
+        let mut synced_rebuilds = 0;
+        let mut mine_moves = 0;
+        for seed in 0..10 {
+            let mut sim = Simulator::new(vec![9, 9], 12, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            sim.set_dynamic_mines(3, 0.5);
+            let mut state = KnowledgeState::default();
+            for step in 0..60 {
+                if !sim.run_step().unwrap() { break; }
+                // toggle the flag of a hidden cell now and then (a flagged one loses it)
+                if step % 4 == 1 {
+                    let hidden: Vec<usize> = (0..sim.board.cells.len()).filter(|&i| !sim.board.cells[i].is_revealed).collect();
+                    if let Some(&cell) = hidden.get(step % hidden.len().max(1)) { sim.board.toggle_flag(cell); }
+                }
+                state.sync(&sim.board);
+                assert_eq!(view(&mut state), view(&mut KnowledgeState::from_board(&sim.board)), "seed {seed} step {step}");
+                steps += 1;
+            }
+            synced_rebuilds += state.rebuilds();
+            mine_moves += sim.board.total_mine_moves;
+        }
+        // the incremental path ran too, not only rebuilds
+        assert!(synced_rebuilds < steps);
+        assert!(mine_moves > 0);
+    }
+}
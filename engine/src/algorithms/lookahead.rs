@@ -0,0 +1,154 @@
+/**
+ * Lookahead guess policy. The safest cell is not always the best guess: a cell that is
+ * slightly riskier but whose number is likely to unlock safe cells keeps the game going.
+ * Every candidate's possible numbers are played out on a scratch board and the exact
+ * probability engine is run again; the ratio of layout counts (`log_weight`) gives
+ * P(safe and number = v), and the new zero-probability cells are what that number unlocks.
+ */
+use crate::board::Board;
+use crate::algorithms::SolverResult;
+use crate::algorithms::probability::{self, ProbabilityMap};
+
+/// candidates may be at most this much more likely to be a mine than the safest cell
+const SAFETY_MARGIN: f64 = 0.05;
+/// cells scored per guess
+const MAX_CANDIDATES: usize = 10;
+/// probability runs per guess (one per candidate and possible number), caps 4d boards
+const MAX_EVALUATIONS: usize = 120;
+
+/// what one guess is expected to achieve
+#[derive(Clone, Debug)]
+pub struct GuessScore {
+    pub cell: usize,
+    pub safe_prob: f64,
+    /// P(the cell is safe and its number forces at least one new safe cell)
+    pub progress_prob: f64,
+    /// expected number of new forced-safe cells, given the cell is safe
+    pub expected_safe: f64,
+}
+
+/// scores the safest hidden cells of `map` (which must be exact), best guesses first
+pub fn score_candidates(board: &Board, map: &ProbabilityMap) -> Vec<GuessScore> {
+    let mut open: Vec<usize> = (0..board.cells.len())
+        .filter(|&i| !board.cells[i].is_revealed && !board.cells[i].is_flagged)
+        .collect();
+    open.sort_by(|&a, &b| map.cell_probs[a].total_cmp(&map.cell_probs[b]).then(a.cmp(&b)));
+    let Some(&safest) = open.first() else { return Vec::new(); };
+    let limit = map.cell_probs[safest] + SAFETY_MARGIN;
+
+    let mut scratch = board.clone();
+    let mut budget = MAX_EVALUATIONS;
+    let mut scores = Vec::new();
+    for &cell in open.iter().take_while(|&&c| map.cell_probs[c] <= limit).take(MAX_CANDIDATES) {
+        let hidden = board.get_hidden_neighbor_count(cell);
+        if hidden + 1 > budget { continue; }
+        budget -= hidden + 1;
+        if let Some(score) = score_cell(&mut scratch, map, cell) {
+            scores.push(score);
+        }
+    }
+
+    scores.sort_by(|a, b| b.progress_prob.total_cmp(&a.progress_prob)
+        .then(b.expected_safe.total_cmp(&a.expected_safe))
+        .then(b.safe_prob.total_cmp(&a.safe_prob))
+        .then(a.cell.cmp(&b.cell)));
+    scores
+}
+
+/// plays every possible number of `cell` on the scratch board (restored afterwards)
+fn score_cell(scratch: &mut Board, map: &ProbabilityMap, cell: usize) -> Option<GuessScore> {
+    let outcomes = number_outcomes(scratch, map, cell)?;
+    let total: f64 = outcomes.iter().map(|&(weight, _)| weight).sum(); // P(safe), summed over the numbers
+    if total <= 0.0 { return None; }
+    let progress: f64 = outcomes.iter().filter(|&&(_, unlocked)| unlocked > 0).map(|&(weight, _)| weight).sum();
+    let expected: f64 = outcomes.iter().map(|&(weight, unlocked)| weight * unlocked as f64).sum();
+
+    // the map's own survival chance includes the distribution prior, the numbers only split it
+    let safe_prob = 1.0 - map.cell_probs[cell];
+    Some(GuessScore {
+        cell,
+        safe_prob,
+        progress_prob: safe_prob * progress / total,
+        expected_safe: expected / total,
+    })
+}
+
+/// per possible number of `cell`: P(safe and that number) and the cells it newly proves safe.
+/// none if a board after some number is too large to enumerate exactly
+fn number_outcomes(scratch: &mut Board, map: &ProbabilityMap, cell: usize) -> Option<Vec<(f64, usize)>> {
+    // a forced mine never shows a number; revealing it would only empty the clues that force it
+    if map.cell_probs[cell] >= 1.0 - 1e-12 { return Some(Vec::new()); }
+    let flags = scratch.adjacency_map[cell].iter().filter(|&&n| scratch.cells[n].is_flagged).count();
+    let hidden = scratch.get_hidden_neighbor_count(cell);
+    let saved = scratch.cells[cell].adjacent_mines;
+
+    let mut outcomes = Vec::with_capacity(hidden + 1);
+    let mut exact = true;
+    scratch.cells[cell].is_revealed = true;
+    for value in 0..=hidden {
+        scratch.cells[cell].adjacent_mines = (flags + value) as u8;
+        let next = probability::enumerate_probabilities(scratch);
+        if !next.exact { exact = false; break; }
+        if !next.log_weight.is_finite() { continue; } // this number is impossible
+
+        let weight = (next.log_weight - map.log_weight).exp();
+        let unlocked = (0..scratch.cells.len())
+            .filter(|&i| !scratch.cells[i].is_revealed && !scratch.cells[i].is_flagged)
+            .filter(|&i| next.cell_probs[i] <= 1e-12 && map.cell_probs[i] > 1e-12)
+            .count();
+        outcomes.push((weight, unlocked));
+    }
+    scratch.cells[cell].is_revealed = false;
+    scratch.cells[cell].adjacent_mines = saved;
+    exact.then_some(outcomes)
+}
+
+/// replaces the solver's guess with the best lookahead scores.
+/// the solver's own candidates stay when the frontier is too large to enumerate exactly
+pub fn lookahead_move(board: &Board, fallback: SolverResult) -> SolverResult {
+    let map = probability::enumerate_probabilities(board);
+    if !map.exact || !map.log_weight.is_finite() { return fallback; }
+
+    let scores = score_candidates(board, &map);
+    let Some(best) = scores.first() else { return fallback; };
+    let candidates = scores.iter()
+        .take_while(|s| (s.progress_prob - best.progress_prob).abs() <= 1e-9
+            && (s.expected_safe - best.expected_safe).abs() <= 1e-9
+            && (s.safe_prob - best.safe_prob).abs() <= 1e-9)
+        .map(|s| s.cell)
+        .collect();
+    SolverResult { candidates, is_guess: true }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+
+    /// the numbers a safe cell can show split its survival chance, so their weights add up to it
+    #[test]
+    fn number_weights_sum_to_the_safe_probability() {
+        let mut scored = 0;
+        for seed in 0..15 {
+            let mut sim = Simulator::new(vec![6, 6], 8, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            sim.run_step().unwrap();
+            for _ in 0..5 {
+                if sim.board.game_over { break; }
+                let map = probability::enumerate_probabilities(&sim.board);
+                let mut scratch = sim.board.clone();
+                for cell in (0..sim.board.cells.len()).filter(|&i| !sim.board.cells[i].is_revealed).take(8) {
+                    let outcomes = number_outcomes(&mut scratch, &map, cell).unwrap();
+                    let total: f64 = outcomes.iter().map(|&(weight, _)| weight).sum();
+                    assert!((total - (1.0 - map.cell_probs[cell])).abs() < 1e-9, "seed {seed} cell {cell}: {total} vs {} {:?}", 1.0 - map.cell_probs[cell], outcomes);
+                    scored += 1;
+                }
+                // and the scratch board is left as it was
+                assert!(scratch.cells.iter().zip(&sim.board.cells).all(|(a, b)| a.is_revealed == b.is_revealed && a.adjacent_mines == b.adjacent_mines));
+                sim.run_step().unwrap();
+            }
+        }
+        assert!(scored > 0);
+    }
+}
@@ -26,6 +26,8 @@ macro_rules! register_algorithms {
                     solver,
                     objective,
                     first_move: true,
+                    config: SolverConfig::default(),
+                    knowledge: KnowledgeState::default(),
                 }
             }
         }
@@ -0,0 +1,181 @@
+// src/algorithms/mcts.rs
+use crate::board::Board;
+use crate::algorithms::{probability, sampling, Algorithm, SolverConfig, SolverResult};
+use crate::algorithms::exact_solver::ExactSolver;
+use crate::algorithms::greedy::GreedyAlgorithm;
+
+/// rng stream for the sampled layouts
+const MCTS_STREAM: u64 = 0xA54F_F53A_5F1D_36F1;
+/// playouts per guess; wasm runs on the ui thread, so it gets a much smaller budget
+const DEFAULT_PLAYOUTS: usize = if cfg!(target_arch = "wasm32") { 48 } else { 400 };
+/// moves searched per guess
+const MAX_ARMS: usize = 8;
+/// interior cells are near copies of each other, only the ones with fewest neighbours get an arm
+const INTERIOR_ARMS: usize = 2;
+/// arms may be at most this much more likely to be a mine than the safest cell
+const SAFETY_MARGIN: f64 = 0.05;
+/// sampler sweeps before the first layout is kept
+const BURN_IN: usize = 10;
+
+/// monte carlo search over the guess step
+/// deductions come from the exact solver. when it has to guess, the safest cells become arms
+/// and are compared on sampled layouts that agree with every clue and the mine count: each
+/// playout reveals the arm on one layout and lets a greedy rollout finish the game, so a
+/// guess is scored by how often the game is won, not by its one-step safety. the chance of
+/// surviving the click itself is known exactly, so an arm scores p(safe) times the share of
+/// rollouts won on the layouts where it is safe (less noise than counting deaths). arms are cut
+/// by sequential halving (every round plays all survivors on the same fresh layouts, then
+/// drops the worse half), which spends most of the budget telling the best moves apart
+pub struct MctsSolver {
+    exact: ExactSolver,
+    playouts: usize,
+}
+
+impl MctsSolver {
+    pub fn new(width: usize, height: usize, mines: usize) -> Self {
+        Self { exact: ExactSolver::new(width, height, mines), playouts: DEFAULT_PLAYOUTS }
+    }
+
+    /// the safest cells of the board with their mine probability, at most `INTERIOR_ARMS` interior ones
+    fn arms(&self, board: &Board) -> Vec<(usize, f64)> {
+        let map = probability::compute_probabilities(board);
+        let mut open: Vec<usize> = (0..board.cells.len())
+            .filter(|&i| !board.cells[i].is_revealed && !board.cells[i].is_flagged)
+            .collect();
+        let in_frontier = |i: usize| map.frontier.contains(&i);
+        open.sort_by(|&a, &b| map.cell_probs[a].total_cmp(&map.cell_probs[b])
+            .then(board.adjacency_map[a].len().cmp(&board.adjacency_map[b].len()))
+            .then(a.cmp(&b)));
+        let Some(&safest) = open.first() else { return Vec::new(); };
+        let limit = map.cell_probs[safest] + SAFETY_MARGIN;
+
+        let mut arms = Vec::new();
+        let mut interior = 0;
+        for &cell in open.iter().take_while(|&&c| map.cell_probs[c] <= limit) {
+            if !in_frontier(cell) {
+                if interior == INTERIOR_ARMS { continue; }
+                interior += 1;
+            }
+            arms.push((cell, map.cell_probs[cell]));
+            if arms.len() == MAX_ARMS { break; }
+        }
+        arms
+    }
+
+    /// sequential halving over `arms`, none if the board admits no layout to sample
+    fn search(&self, board: &Board, mut arms: Vec<(usize, f64)>) -> Option<Vec<usize>> {
+        let rounds = arms.len().next_power_of_two().trailing_zeros().max(1) as usize;
+        // layouts per round so that every round plays about the same number of games
+        let mut per_round = Vec::with_capacity(rounds);
+        let mut alive = arms.len();
+        for _ in 0..rounds {
+            per_round.push((self.playouts / rounds / alive).max(1));
+            alive = alive.div_ceil(2);
+        }
+        let layouts = sampling::sample_layouts(board, per_round.iter().sum(), BURN_IN, MCTS_STREAM)?;
+
+        // (won, survived the click) per arm
+        let mut games = vec![(0usize, 0usize); board.cells.len()];
+        let score = |(cell, p): (usize, f64), games: &[(usize, usize)]| {
+            let (won, survived) = games[cell];
+            (1.0 - p) * (won as f64 + 1.0) / (survived as f64 + 2.0)
+        };
+        let mut next = layouts.iter();
+        let mut scratch = board.clone();
+        scratch.dynamic_mines = None; // rollouts play the sampled layout as it is
+        for count in per_round {
+            let round: Vec<&Vec<usize>> = next.by_ref().take(count).collect();
+            for &(arm, _) in &arms {
+                for layout in round.iter().filter(|layout| !layout.contains(&arm)) {
+                    games[arm].1 += 1;
+                    if playout(&mut scratch, board, layout, arm) { games[arm].0 += 1; }
+                }
+            }
+            if arms.len() == 1 { break; }
+            // stable sort keeps the safer arm first on ties
+            arms.sort_by(|&a, &b| score(b, &games).total_cmp(&score(a, &games)));
+            arms.truncate(arms.len().div_ceil(2));
+        }
+
+        let best = score(arms[0], &games);
+        Some(arms.into_iter().filter(|&a| score(a, &games) >= best - 1e-12).map(|(cell, _)| cell).collect())
+    }
+}
+
+/// plays `arm` and then greedy moves on `layout` from the current position, true on a win
+fn playout(scratch: &mut Board, board: &Board, layout: &[usize], arm: usize) -> bool {
+    scratch.cells.clone_from(&board.cells);
+    scratch.total_revealed = board.total_revealed;
+    scratch.total_clicks = board.total_clicks;
+    scratch.game_over = false;
+    scratch.game_won = false;
+    if scratch.place_mines_at(layout).is_err() { return false; }
+
+    let mut rollout = GreedyAlgorithm::new(0, 0, 0);
+    let mut next = vec![arm];
+    loop {
+        for &cell in &next {
+            if scratch.reveal_cell(cell).is_err() { return false; }
+            if scratch.game_over { return scratch.game_won; }
+        }
+        let result = rollout.find_candidates(scratch);
+        if result.candidates.is_empty() { return false; }
+        // safe cells all at once, guesses one at a time
+        next = if result.is_guess { vec![result.candidates[0]] } else { result.candidates };
+    }
+}
+
+impl Algorithm for MctsSolver {
+    fn find_candidates(&mut self, board: &Board) -> SolverResult {
+        let result = self.exact.find_candidates(board);
+        if !result.is_guess { return result; }
+
+        let arms = self.arms(board);
+        if arms.len() < 2 { return result; }
+        match self.search(board, arms) {
+            Some(candidates) => SolverResult { candidates, is_guess: true },
+            None => result,
+        }
+    }
+
+    fn configure(&mut self, config: &SolverConfig) {
+        self.playouts = if config.playouts > 0 { config.playouts } else { DEFAULT_PLAYOUTS };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+
+    /// the layouts come from the board's seed, so a guess repeats; a forced mine never wins an arm
+    #[test]
+    fn search_is_seeded_and_avoids_known_mines() {
+        let mut solver = MctsSolver::new(0, 0, 0);
+        solver.playouts = 40;
+        let mut searched = 0;
+        for seed in 0..8 {
+            let mut sim = Simulator::new(vec![8, 8], 12, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            while sim.run_step().unwrap() {
+                if !solver.exact.find_candidates(&sim.board).is_guess { continue; }
+                let mut arms = solver.arms(&sim.board);
+                // once only mines are left open there is nothing to search
+                if arms.len() < 2 || arms[0].1 >= 1.0 - 1e-12 { continue; }
+                let map = probability::compute_probabilities(&sim.board);
+                // offer the search a cell the clues force to be a mine as well
+                let forced = (0..sim.board.cells.len())
+                    .find(|&i| !sim.board.cells[i].is_revealed && !sim.board.cells[i].is_flagged && map.cell_probs[i] >= 1.0 - 1e-12);
+                if let Some(mine) = forced { arms.push((mine, 1.0)); }
+
+                let first = solver.search(&sim.board, arms.clone()).unwrap();
+                assert_eq!(solver.search(&sim.board.clone(), arms).unwrap(), first, "seed {seed}");
+                assert!(!first.is_empty());
+                assert!(first.iter().all(|&c| map.cell_probs[c] < 1.0 - 1e-12 && Some(c) != forced), "seed {seed}: {first:?}");
+                searched += 1;
+            }
+        }
+        assert!(searched > 0);
+    }
+}
@@ -1,4 +1,4 @@
-/**
+/*
 * module for different algorithms to solve the minesweeper game.
 * each algorithm should implement the algorithm trait.
 * i have added 0 ~ 5 comments to guide you when adding a new algorithm! (~ ˘∇˘ )~
@@ -9,17 +9,36 @@ pub mod macros;
 
 pub mod greedy;
 pub mod exact_solver;
+pub mod probability;
+pub mod sampling;
 pub mod sat_utils;
+pub mod cdcl;
 pub mod sat_global;
 pub mod sat_partitioned;
 // pub mod metaheuristic; 
 pub mod sat_solver_4d;
+pub mod gaussian;
+pub mod belief_propagation;
+pub mod lookahead;
+pub mod mcts;
+pub mod patterns;
+pub mod ilp;
+pub mod cp;
+pub mod bignat;
+pub mod model_count;
+pub mod walksat;
+pub mod knowledge;
+pub mod cell_set;
+pub mod counting;
+pub mod belief_tracker;
 
 #[cfg(feature = "native")]
 pub mod scip_solver;
-/// [0] when adding a new algorithm, create a new module here
+// [0] when adding a new algorithm, create a new module here
 
 use crate::board::Board;
+use crate::algorithms::patterns::{PatternDb, PatternMatch};
+use crate::algorithms::knowledge::KnowledgeState;
 use wasm_bindgen::prelude::*;
 
 /// result structure to track if a move is a logical deduction or a guess
@@ -31,6 +50,58 @@ pub struct SolverResult {
 /// returns a solver result containing safe indices or probabilistic guesses
 pub trait Algorithm {
     fn find_candidates(&mut self, board: &Board) -> SolverResult;
+
+    /// same as `find_candidates`, reading from the agent's knowledge state (synced to `board`).
+    /// the default ignores the state and scans the board
+    fn find_candidates_with(&mut self, board: &Board, _knowledge: &mut KnowledgeState) -> SolverResult {
+        self.find_candidates(board)
+    }
+
+    /// solvers pick the options they understand, the rest ignore the config
+    fn configure(&mut self, _config: &SolverConfig) {}
+
+    /// the local patterns behind the last candidates (empty for solvers without any)
+    fn last_explanation(&self) -> Vec<PatternMatch> { Vec::new() }
+
+    /// replaces the pattern library of solvers that use one
+    fn load_patterns(&mut self, _db: &PatternDb) {}
+}
+
+/// how "k of these n cells are mines" becomes cnf
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum CardinalityEncoding {
+    /// one clause per subset, no auxiliary variables (fine for 8 neighbours)
+    #[default]
+    Combinatorial,
+    SequentialCounter,
+    Totalizer,
+    CardinalityNetwork,
+}
+
+/// how the agent picks among guesses once the solver has no safe move
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum GuessPolicy {
+    /// whatever the solver proposed (the lowest mine probability for most of them)
+    #[default]
+    SafestCell,
+    /// survival chance weighed against the safe cells the revealed number would unlock
+    Lookahead,
+}
+
+/// solver options that survive algorithm switches
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SolverConfig {
+    pub encoding: CardinalityEncoding,
+    /// add "remaining mines = frontier mines + interior mines" (endgame deductions)
+    pub global_mine_count: bool,
+    pub guess_policy: GuessPolicy,
+    /// playouts per guess for the mcts solver (0 = its default)
+    pub playouts: usize,
+    /// walksat flips per witness before a sat solver falls back to complete search (0 = off)
+    pub local_search_flips: usize,
+    pub local_search_seed: u64,
 }
 
 #[wasm_bindgen]
@@ -45,14 +116,23 @@ pub struct MinesweeperAgent {
     pub solver: Box<dyn Algorithm>,
     pub objective: TspObjective,
     pub first_move: bool,
+    pub config: SolverConfig,
+    /// frontier, clues and deductions carried from move to move
+    pub knowledge: KnowledgeState,
 }
 
 impl MinesweeperAgent {
+    pub fn configure(&mut self, config: SolverConfig) {
+        self.config = config;
+        self.solver.configure(&config);
+    }
+
     pub fn next_move(&mut self, board: &Board) -> Option<SolverResult> {
         let total_cells = board.cells.len();
         if total_cells == 0 { return None; }
 
-        if self.first_move {
+        // pre-revealed boards already have an opening, so the agent goes straight to its solver
+        if self.first_move && board.total_revealed == 0 {
             self.first_move = false;
             
             let w = board.get_width();
@@ -64,18 +144,27 @@ impl MinesweeperAgent {
             let center_idx = (h / 2) * w + (w / 2); 
             
             return Some(SolverResult {
-                candidates: vec![center_idx],
+                candidates: vec![board.first_click.unwrap_or(center_idx)],
                 is_guess: true,
             });
         }
 
-        let result = self.solver.find_candidates(board);
+        self.first_move = false;
+        self.knowledge.sync(board);
+        let mut result = self.solver.find_candidates_with(board, &mut self.knowledge);
         if result.candidates.is_empty() { return None; }
+
+        // the policy sits above the solvers so every guess step can use it
+        if result.is_guess && self.config.guess_policy == GuessPolicy::Lookahead {
+            result = lookahead::lookahead_move(board, result);
+        }
         
         Some(result)
     }
 
     pub fn pick_best_from_candidates(&self, board: &Board, mut result: SolverResult) -> usize {
+        // a single candidate (most deductions) needs no ranking, and no walk over the board
+        if let [only] = result.candidates[..] { return only; }
         let last_idx = board.last_click_idx;
         let distance_map = board.get_distance_map(last_idx);
         let candidates = &mut result.candidates;
@@ -124,6 +213,14 @@ register_algorithms!(
     GlobalSat => "global_sat", crate::algorithms::sat_global::GlobalSatSolver,
     PartitionedSat => "partitioned_sat", crate::algorithms::sat_partitioned::PartitionedSatSolver,
     SATSolver4D => "sat_solver_4d", crate::algorithms::sat_solver_4d::SatSolver4D,
+    GaussianSolver => "gaussian", crate::algorithms::gaussian::GaussianSolver,
+    BeliefPropagation => "belief_propagation", crate::algorithms::belief_propagation::BeliefPropagationSolver,
+    Mcts => "mcts", crate::algorithms::mcts::MctsSolver,
+    Patterns => "patterns", crate::algorithms::patterns::PatternSolver,
+    Ilp => "ilp", crate::algorithms::ilp::IlpSolver,
+    Cp => "cp", crate::algorithms::cp::CpSolver,
+    SharpSat => "sharp_sat", crate::algorithms::model_count::SharpSatSolver,
+    BeliefTracker => "belief_tracker", crate::algorithms::belief_tracker::BeliefTracker,
 );
 
 // 2. Runner Purpose
@@ -134,4 +231,12 @@ register_algorithms!(
     GlobalSat => "global_sat", crate::algorithms::sat_global::GlobalSatSolver,
     PartitionedSat => "partitioned_sat", crate::algorithms::sat_partitioned::PartitionedSatSolver,
     SCIPSolver => "scip_solver", crate::algorithms::scip_solver::SCIPSolver,
+    GaussianSolver => "gaussian", crate::algorithms::gaussian::GaussianSolver,
+    BeliefPropagation => "belief_propagation", crate::algorithms::belief_propagation::BeliefPropagationSolver,
+    Mcts => "mcts", crate::algorithms::mcts::MctsSolver,
+    Patterns => "patterns", crate::algorithms::patterns::PatternSolver,
+    Ilp => "ilp", crate::algorithms::ilp::IlpSolver,
+    Cp => "cp", crate::algorithms::cp::CpSolver,
+    SharpSat => "sharp_sat", crate::algorithms::model_count::SharpSatSolver,
+    BeliefTracker => "belief_tracker", crate::algorithms::belief_tracker::BeliefTracker,
 );
\ No newline at end of file
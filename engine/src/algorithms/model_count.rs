@@ -0,0 +1,342 @@
+/**
+ * Exact model counting (#SAT) over the clue CNF. Every clue becomes its combinatorial
+ * "exactly k" clauses over the frontier cells, so models and frontier layouts are the same
+ * thing (no auxiliary variables). The counter branches on the cell in most open clauses and
+ * runs unit propagation after each decision. At every node the open cells are split into the
+ * components of the clauses that are still unsatisfied, like
+ * `PartitionedSatSolver::get_connected_components` does once on the board. Each component is
+ * counted on its own and cached under its cells and open clauses, so the same sub-problem
+ * found again on another branch is looked up. Counts are kept per frontier mine total in
+ * u128 (at most 127 frontier cells, so they cannot overflow). At the end every total is
+ * weighted by C(interior, remaining - total) in `BigNat`, giving exact layout counts.
+ */
+use crate::board::Board;
+use crate::distribution::MineDistribution;
+use crate::algorithms::{probability, Algorithm, SolverResult};
+use crate::algorithms::bignat::BigNat;
+use crate::algorithms::counting::{Assignment, Tally};
+use crate::algorithms::probability::{LogBinomial, ProbabilityMap};
+use crate::algorithms::sat_utils::{add_exactly_k_clauses, global_counts, Clause};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// frontier cells that still fit their layout counts into a u128
+const MAX_FRONTIER: usize = 127;
+/// combinatorial clauses the cnf may take before the counter declines
+const MAX_CLAUSES: f64 = 200_000.0;
+/// search nodes before the counter gives up
+const MAX_NODES: usize = 1_000_000;
+/// cached components, later ones are counted without being stored
+const MAX_CACHE: usize = 200_000;
+
+/// exact layout counts of the whole board under the clues and the mine count
+#[derive(Clone, Debug, Serialize)]
+pub struct ModelCount {
+    pub frontier: Vec<usize>,
+    /// per frontier cell, layouts with it a mine
+    pub frontier_mines: Vec<BigNat>,
+    /// hidden cells without a clue
+    pub interior: Vec<usize>,
+    /// per interior cell (all the same), layouts with it a mine
+    pub interior_mines: BigNat,
+    /// all consistent layouts
+    pub total: BigNat,
+    /// frontier layouts per frontier mine count, before the interior weighting
+    pub frontier_layouts: Vec<u128>,
+}
+
+impl ModelCount {
+    /// exact mine probability of a hidden cell (0 for cells the counts don't cover)
+    pub fn probability(&self, cell: usize) -> f64 {
+        if let Some(i) = self.frontier.iter().position(|&c| c == cell) {
+            return self.frontier_mines[i].ratio(&self.total);
+        }
+        if self.interior.contains(&cell) { self.interior_mines.ratio(&self.total) } else { 0.0 }
+    }
+
+    /// the counts as the shared probability map (interior cells get the board's prior, so the
+    /// map is only exact under the uniform prior the counts assume)
+    pub fn to_probability_map(&self, board: &Board) -> ProbabilityMap {
+        let mut cell_probs: Vec<f64> = board.cells.iter().map(|c| if c.is_flagged { 1.0 } else { 0.0 }).collect();
+        for (i, &cell) in self.frontier.iter().enumerate() {
+            cell_probs[cell] = self.frontier_mines[i].ratio(&self.total);
+        }
+        let interior_prob = self.interior_mines.ratio(&self.total);
+        for (&cell, p) in self.interior.iter().zip(board.prior.interior_probs(board, &self.interior, interior_prob)) {
+            cell_probs[cell] = p;
+        }
+        ProbabilityMap {
+            cell_probs,
+            frontier: self.frontier.clone(),
+            interior_prob,
+            log_weight: self.total.ln(),
+            exact: matches!(board.prior, MineDistribution::Uniform),
+        }
+    }
+}
+
+struct Counter {
+    /// literals as `lit(var)` / `-lit(var)` over frontier columns
+    clauses: Vec<Vec<isize>>,
+    /// true literals per clause are the assignment's hits, false ones its misses
+    assignment: Assignment,
+    cache: HashMap<Vec<usize>, Tally<u128>>,
+    nodes: usize,
+}
+
+impl Counter {
+    fn new(clauses: Vec<Vec<isize>>, vars: usize) -> Self {
+        let literals: Vec<Vec<(usize, bool)>> = clauses.iter()
+            .map(|clause| clause.iter().map(|&l| (l.unsigned_abs() - 1, l > 0)).collect())
+            .collect();
+        Self {
+            assignment: Assignment::new(&literals, vars),
+            clauses,
+            cache: HashMap::new(),
+            nodes: 0,
+        }
+    }
+
+    /// unit propagation from the given clauses, false on a conflict
+    fn propagate(&mut self, start: Vec<usize>) -> bool {
+        let mut queue = start;
+        while let Some(c) = queue.pop() {
+            if self.assignment.hits[c] > 0 { continue; }
+            let len = self.clauses[c].len();
+            if self.assignment.misses[c] == len { return false; }
+            if self.assignment.misses[c] + 1 < len { continue; }
+            let unit = *self.clauses[c].iter()
+                .find(|&&l| self.assignment.value[l.unsigned_abs() - 1].is_none())
+                .expect("a unit clause has an open literal");
+            let v = unit.unsigned_abs() - 1;
+            self.assignment.assign(v, unit > 0);
+            queue.extend(self.assignment.occurrences[v].iter().map(|&(d, _)| d));
+        }
+        true
+    }
+
+    fn is_open(&self, c: usize) -> bool {
+        self.assignment.hits[c] == 0
+    }
+
+    /// counts the models of `cells` under the current assignment, none past the node budget
+    fn count(&mut self, cells: &[usize]) -> Option<Tally<u128>> {
+        let (open, assigned): (Vec<usize>, Vec<usize>) = cells.iter().partition(|&&v| self.assignment.value[v].is_none());
+        let mut result = Tally::fixed(assigned, &self.assignment.value);
+        // open cells split by the unsatisfied clauses
+        for group in self.assignment.components(&open, |a, c| a.hits[c] == 0) {
+            result = result.product(&self.count_component(&group)?);
+        }
+        Some(result.reorder(cells))
+    }
+
+    /// one connected component of open cells, from the cache if it was counted before
+    fn count_component(&mut self, group: &[usize]) -> Option<Tally<u128>> {
+        self.nodes += 1;
+        if self.nodes > MAX_NODES { return None; }
+
+        // cells and open clauses fix the residual formula: every other literal is assigned
+        let mut clauses: Vec<usize> = group.iter()
+            .flat_map(|&v| self.assignment.occurrences[v].iter().map(|&(c, _)| c))
+            .filter(|&c| self.is_open(c))
+            .collect();
+        clauses.sort_unstable();
+        clauses.dedup();
+        let mut key = group.to_vec();
+        key.push(usize::MAX);
+        key.extend(clauses);
+        if let Some(hit) = self.cache.get(&key) {
+            return Some(hit.clone());
+        }
+
+        let var = *group.iter()
+            .max_by_key(|&&v| (self.assignment.occurrences[v].iter().filter(|&&(c, _)| self.is_open(c)).count(), usize::MAX - v))
+            .expect("a component is not empty");
+        let mut result = Tally::empty(group.to_vec());
+        for value in [true, false] {
+            let mark = self.assignment.mark();
+            self.assignment.assign(var, value);
+            let start = self.assignment.occurrences[var].iter().map(|&(c, _)| c).collect();
+            if self.propagate(start) {
+                let Some(branch) = self.count(group) else {
+                    self.assignment.undo(mark);
+                    return None;
+                };
+                result.add(branch);
+            }
+            self.assignment.undo(mark);
+        }
+        if self.cache.len() < MAX_CACHE {
+            self.cache.insert(key, result.clone());
+        }
+        Some(result)
+    }
+}
+
+/// exact layout counts of the current board, none if the clues contradict each other or
+/// the frontier is too large (over 127 cells, a clue cnf over 200k clauses, 1M search nodes)
+pub fn count_models(board: &Board) -> Option<ModelCount> {
+    let constraints = probability::collect_constraints(board);
+    let mut frontier: Vec<usize> = constraints.iter().flat_map(|c| c.cells.iter().copied()).collect();
+    frontier.sort_unstable();
+    frontier.dedup();
+    if frontier.len() > MAX_FRONTIER { return None; }
+
+    // combinatorial exactly-k: C(n, n - k + 1) "at least" and C(n, k + 1) "at most" clauses
+    let binom = LogBinomial::new(board.cells.len());
+    let mut size = 0.0;
+    for c in &constraints {
+        let n = c.cells.len();
+        if c.mines > n { return None; }
+        if c.mines > 0 { size += binom.ln_choose(n, n - c.mines + 1).exp(); }
+        if c.mines < n { size += binom.ln_choose(n, c.mines + 1).exp(); }
+    }
+    if size > MAX_CLAUSES { return None; }
+
+    let column: HashMap<usize, usize> = frontier.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+    let mut cnf: Vec<Clause> = Vec::new();
+    for c in &constraints {
+        let vars: Vec<usize> = c.cells.iter().map(|cell| column[cell]).collect();
+        add_exactly_k_clauses(&mut cnf, &vars, c.mines);
+    }
+    let clauses: Vec<Vec<isize>> = cnf.into_iter().map(|c| c.0).collect();
+    let all = (0..clauses.len()).collect();
+    let mut counter = Counter::new(clauses, frontier.len());
+    if !counter.propagate(all) { return None; }
+
+    let vars: Vec<usize> = (0..frontier.len()).collect();
+    let tally = counter.count(&vars)?;
+
+    // weight every frontier total by the ways to place the rest in the interior
+    let (remaining, interior) = global_counts(board, &frontier);
+    let weight = |m: usize, interior_cells: usize, extra: usize| {
+        remaining.checked_sub(m + extra).map_or(BigNat::zero(), |rest| BigNat::binomial(interior_cells, rest))
+    };
+    let mut total = BigNat::zero();
+    let mut frontier_mines = vec![BigNat::zero(); frontier.len()];
+    let mut interior_mines = BigNat::zero();
+    let mut frontier_layouts = vec![0u128; tally.low + tally.counts.len()];
+    let width = tally.counts.len();
+    for (k, &layouts) in tally.counts.iter().enumerate() {
+        let m = tally.low + k;
+        frontier_layouts[m] = layouts;
+        let w = weight(m, interior.len(), 0);
+        total += &(&BigNat::from(layouts) * &w);
+        for (i, mines) in frontier_mines.iter_mut().enumerate() {
+            *mines += &(&BigNat::from(tally.cell_counts[i * width + k]) * &w);
+        }
+        if !interior.is_empty() {
+            interior_mines += &(&BigNat::from(layouts) * &weight(m, interior.len() - 1, 1));
+        }
+    }
+    if total.is_zero() { return None; }
+
+    Some(ModelCount {
+        frontier,
+        frontier_mines,
+        interior,
+        interior_mines,
+        total,
+        frontier_layouts,
+    })
+}
+
+/// exact mine probabilities from the model counts, none where `count_models` gives none
+pub fn probabilities(board: &Board) -> Option<ProbabilityMap> {
+    count_models(board).map(|counts| counts.to_probability_map(board))
+}
+
+/// model counting solver
+/// cells with probability 0 under the exact counts are played as safe, otherwise the safest cell
+#[allow(dead_code)]
+pub struct SharpSatSolver {
+    width: usize,
+    height: usize,
+    mines: usize,
+}
+
+impl SharpSatSolver {
+    pub fn new(width: usize, height: usize, mines: usize) -> Self {
+        Self { width, height, mines }
+    }
+}
+
+impl Algorithm for SharpSatSolver {
+    fn find_candidates(&mut self, board: &Board) -> SolverResult {
+        let Some(counts) = count_models(board) else {
+            return probability::safest_move(board);
+        };
+        let map = counts.to_probability_map(board);
+        let candidates = map.safest_cells(board);
+        // a zero count is exact here, no tolerance needed (a prior can only add zero marginals)
+        let proven: Vec<usize> = candidates.iter().copied()
+            .filter(|&c| match counts.frontier.iter().position(|&f| f == c) {
+                Some(i) => counts.frontier_mines[i].is_zero(),
+                None => counts.interior_mines.is_zero(),
+            })
+            .collect();
+        if proven.is_empty() {
+            SolverResult { candidates, is_guess: true }
+        } else {
+            SolverResult { candidates: proven, is_guess: false }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+
+    /// every layout of the mines over the hidden cells that matches the revealed numbers:
+    /// how many there are and, per cell, how many put a mine on it
+    fn brute_force(board: &Board) -> (u128, Vec<u128>) {
+        let hidden: Vec<usize> = (0..board.cells.len()).filter(|&i| !board.cells[i].is_revealed).collect();
+        let mut total = 0;
+        let mut mines = vec![0; board.cells.len()];
+        for bits in 0..1u32 << hidden.len() {
+            if bits.count_ones() as usize != board.mines { continue; }
+            let is_mine = |cell: usize| hidden.binary_search(&cell).is_ok_and(|k| (bits >> k) & 1 == 1);
+            let consistent = board.cells.iter().enumerate().filter(|(_, c)| c.is_revealed).all(|(i, c)| {
+                board.adjacency_map[i].iter().filter(|&&n| is_mine(n)).count() == c.adjacent_mines as usize
+            });
+            if !consistent { continue; }
+            total += 1;
+            for &cell in hidden.iter().filter(|&&cell| is_mine(cell)) { mines[cell] += 1; }
+        }
+        (total, mines)
+    }
+
+    #[test]
+    fn counts_match_brute_force() {
+        for seed in 0..20 {
+            let mut sim = Simulator::new(vec![4, 5], 5, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            for _ in 0..4 {
+                if sim.board.game_over { break; }
+                let counts = count_models(&sim.board).expect("consistent clues");
+                let (total, mines) = brute_force(&sim.board);
+                assert_eq!(counts.total, BigNat::from(total), "seed {seed}");
+                for (i, &cell) in counts.frontier.iter().enumerate() {
+                    assert_eq!(counts.frontier_mines[i], BigNat::from(mines[cell]), "seed {seed} cell {cell}");
+                }
+                for &cell in &counts.interior {
+                    assert_eq!(counts.interior_mines, BigNat::from(mines[cell]), "seed {seed} cell {cell}");
+                }
+                sim.run_step().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn map_is_exact_only_under_the_uniform_prior() {
+        let mut sim = Simulator::new(vec![4, 5], 5, WasmAlgorithmType::ExactSolver).unwrap();
+        sim.set_seed(1).unwrap();
+        sim.run_step().unwrap();
+        let counts = count_models(&sim.board).expect("consistent clues");
+        assert!(counts.to_probability_map(&sim.board).exact);
+        sim.board.prior = MineDistribution::Gradient { axis: 0, strength: 0.5 };
+        assert!(!counts.to_probability_map(&sim.board).exact);
+    }
+}
@@ -0,0 +1,470 @@
+/**
+ * Pattern database solver: local "human" patterns instead of a global search.
+ * A pattern is stored in a topology-free form. For a group of up to three clues, every hidden
+ * cell falls into one Venn region (the set of clues that see it), and the pattern is the
+ * number of cells per region plus the mines every clue still needs. Cells in one region are
+ * interchangeable, so whatever the group forces is forced region by region. The form does not
+ * depend on how the cells are laid out, so one entry matches a 1-2-1 on a 2D wall in any
+ * rotation or mirror image, across a cube edge, or in 4D. Clue orderings are canonicalised,
+ * which covers the remaining symmetry.
+ */
+use crate::board::Board;
+use crate::error::MinesweeperError;
+use crate::algorithms::{probability, Algorithm, SolverResult};
+use itertools::Itertools;
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+
+/// largest clue group the solver looks at (groups of three are solved on first sight)
+const MAX_CLUES: usize = 3;
+
+/// venn form of a clue group: `regions[mask - 1]` hidden cells are seen by exactly the clues in
+/// `mask`, clue `i` still needs `mines[i]` mines
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Signature {
+    pub regions: Vec<u8>,
+    pub mines: Vec<u8>,
+}
+
+impl Signature {
+    fn clues(&self) -> usize {
+        self.mines.len()
+    }
+
+    /// hidden cells seen by clue `i`
+    fn cells_of(&self, i: usize) -> usize {
+        self.regions.iter().enumerate()
+            .filter(|&(r, _)| (r + 1) >> i & 1 == 1)
+            .map(|(_, &s)| s as usize)
+            .sum()
+    }
+
+    /// the same group with its clues reordered: new clue `i` is old clue `perm[i]`
+    fn permuted(&self, perm: &[usize]) -> Signature {
+        let k = self.clues();
+        let mut regions = vec![0; self.regions.len()];
+        for (r, slot) in regions.iter_mut().enumerate() {
+            let new_mask = r + 1;
+            let old_mask: usize = (0..k).filter(|&i| new_mask >> i & 1 == 1).map(|i| 1 << perm[i]).sum();
+            *slot = self.regions[old_mask - 1];
+        }
+        Signature { regions, mines: perm.iter().map(|&p| self.mines[p]).collect() }
+    }
+
+    /// smallest form over all clue orders, with the order that produced it
+    fn canonical(&self) -> (Signature, Vec<usize>) {
+        let k = self.clues();
+        if k == 1 { return (self.clone(), vec![0]); }
+        (0..k).permutations(k)
+            .map(|perm| (self.permuted(&perm), perm))
+            .min_by(|a, b| (&a.0.regions, &a.0.mines).cmp(&(&b.0.regions, &b.0.mines)))
+            .unwrap_or_else(|| (self.clone(), Vec::new()))
+    }
+
+    /// every clue shares a cell with the rest of the group (else it is two smaller patterns)
+    fn connected(&self) -> bool {
+        let k = self.clues();
+        let mut reached = 1usize;
+        for _ in 0..k {
+            for (r, &s) in self.regions.iter().enumerate() {
+                if s > 0 && (r + 1) & reached != 0 { reached |= r + 1; }
+            }
+        }
+        reached == (1 << k) - 1
+    }
+
+    /// the group restricted to the clues in `keep` (cells seen only by dropped clues vanish)
+    fn project(&self, keep: &[usize]) -> Signature {
+        let mut regions = vec![0u8; (1 << keep.len()) - 1];
+        for (r, &s) in self.regions.iter().enumerate() {
+            let mask: usize = keep.iter().enumerate().filter(|&(_, &c)| (r + 1) >> c & 1 == 1).map(|(i, _)| 1 << i).sum();
+            if mask > 0 { regions[mask - 1] += s; }
+        }
+        Signature { regions, mines: keep.iter().map(|&c| self.mines[c]).collect() }
+    }
+
+    /// regions that hold no mine / only mines in every assignment that satisfies the clues
+    fn solve(&self) -> (Vec<u8>, Vec<u8>) {
+        let mut low = vec![u8::MAX; self.regions.len()];
+        let mut high = vec![0u8; self.regions.len()];
+        let mut need: Vec<isize> = self.mines.iter().map(|&m| m as isize).collect();
+        let mut counts = vec![0u8; self.regions.len()];
+        self.enumerate(0, &mut need, &mut counts, &mut low, &mut high);
+
+        let (mut safe, mut mines) = (Vec::new(), Vec::new());
+        for (r, &s) in self.regions.iter().enumerate() {
+            if s == 0 || low[r] == u8::MAX { continue; } // empty region or no assignment at all
+            if high[r] == 0 { safe.push(r as u8 + 1); }
+            if low[r] == s { mines.push(r as u8 + 1); }
+        }
+        (safe, mines)
+    }
+
+    fn enumerate(&self, r: usize, need: &mut [isize], counts: &mut [u8], low: &mut [u8], high: &mut [u8]) {
+        if r == self.regions.len() {
+            if need.iter().all(|&n| n == 0) {
+                for (i, &c) in counts.iter().enumerate() {
+                    low[i] = low[i].min(c);
+                    high[i] = high[i].max(c);
+                }
+            }
+            return;
+        }
+        let mask = r + 1;
+        for c in 0..=self.regions[r] {
+            let clues = (0..need.len()).filter(|&i| mask >> i & 1 == 1);
+            if clues.clone().any(|i| need[i] < c as isize) { break; }
+            for i in clues.clone() { need[i] -= c as isize; }
+            counts[r] = c;
+            self.enumerate(r + 1, need, counts, low, high);
+            for i in clues { need[i] += c as isize; }
+        }
+    }
+
+    /// "2-1 subset"-style label from the clue values and the overlap structure
+    fn describe(&self) -> String {
+        let values = self.mines.iter().map(|m| m.to_string()).join("-");
+        match self.clues() {
+            1 if self.mines[0] == 0 => "satisfied clue".to_string(),
+            1 => "all mines".to_string(),
+            2 if self.regions[0] == 0 || self.regions[1] == 0 => format!("{} subset", values),
+            2 => format!("{} overlap", values),
+            _ => format!("{} triple", values),
+        }
+    }
+}
+
+/// a signature that forces something, with the forced regions as masks of the canonical clue order
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Pattern {
+    pub name: String,
+    pub signature: Signature,
+    pub safe: Vec<u8>,
+    pub mines: Vec<u8>,
+}
+
+/// one pattern that fired: the revealed cells it used and the cells it decided
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PatternMatch {
+    pub pattern: String,
+    pub clues: Vec<usize>,
+    pub safe: Vec<usize>,
+    pub mines: Vec<usize>,
+}
+
+/// forcing patterns keyed by canonical signature, saved and loaded as json
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PatternDb {
+    pub patterns: Vec<Pattern>,
+    #[serde(skip)]
+    index: HashMap<Signature, usize>,
+    /// signatures already solved without a deduction
+    #[serde(skip)]
+    misses: HashSet<Signature>,
+}
+
+impl PatternDb {
+    /// every minimal forcing pattern of up to `max_clues` clues with at most `max_cells` hidden
+    /// cells per clue, found by solving each window exhaustively. patterns in which a smaller
+    /// group already forces something are left out: the solver tries smaller groups first
+    pub fn generate(max_clues: usize, max_cells: usize) -> Self {
+        let mut db = PatternDb::default();
+        for k in 1..=max_clues.min(MAX_CLUES) {
+            let mut regions = Vec::with_capacity((1 << k) - 1);
+            db.generate_regions(k, max_cells, &mut regions);
+        }
+        db
+    }
+
+    fn generate_regions(&mut self, k: usize, max_cells: usize, regions: &mut Vec<u8>) {
+        if regions.len() < (1 << k) - 1 {
+            let mask = regions.len() + 1;
+            for s in 0..=max_cells as u8 {
+                regions.push(s);
+                let fits = (0..k).all(|i| mask >> i & 1 == 0 || {
+                    let sig = Signature { regions: regions.clone(), mines: vec![0; k] };
+                    sig.cells_of(i) <= max_cells
+                });
+                if fits { self.generate_regions(k, max_cells, regions); }
+                regions.pop();
+                if !fits { break; }
+            }
+            return;
+        }
+
+        let shape = Signature { regions: regions.clone(), mines: vec![0; k] };
+        if (0..k).any(|i| shape.cells_of(i) == 0) || !shape.connected() { return; }
+        let cells: Vec<usize> = (0..k).map(|i| shape.cells_of(i)).collect();
+        for mines in cells.iter().map(|&n| 0..=n as u8).multi_cartesian_product() {
+            let sig = Signature { regions: regions.clone(), mines };
+            let (canonical, _) = sig.canonical();
+            if canonical != sig || !Self::minimal(&sig) { continue; }
+            self.insert(sig);
+        }
+    }
+
+    /// no clue of a bigger group is trivial on its own and no smaller connected group forces anything
+    fn minimal(sig: &Signature) -> bool {
+        let k = sig.clues();
+        if k == 1 { return true; }
+        (1..k).all(|size| (0..k).combinations(size).all(|keep| {
+            let sub = sig.project(&keep);
+            !sub.connected() || sub.solve() == (Vec::new(), Vec::new())
+        }))
+    }
+
+    /// the pattern of a canonical signature, none if it forces nothing
+    fn solve_signature(sig: &Signature) -> Option<Pattern> {
+        let (safe, mines) = sig.solve();
+        if safe.is_empty() && mines.is_empty() { return None; }
+        Some(Pattern { name: sig.describe(), signature: sig.clone(), safe, mines })
+    }
+
+    /// solves `sig` (canonical) and keeps it if it forces anything
+    fn insert(&mut self, sig: Signature) {
+        match Self::solve_signature(&sig) {
+            Some(pattern) => {
+                self.patterns.push(pattern);
+                self.index.insert(sig, self.patterns.len() - 1);
+            }
+            None => { self.misses.insert(sig); }
+        }
+    }
+
+    /// the pattern of a canonical signature the database has seen (inner none: it forces nothing),
+    /// none for signatures it never solved
+    fn get(&self, sig: &Signature) -> Option<Option<&Pattern>> {
+        match self.index.get(sig) {
+            Some(&i) => Some(Some(&self.patterns[i])),
+            None if self.misses.contains(sig) => Some(None),
+            None => None,
+        }
+    }
+
+    /// all patterns of one and two clues with up to 8 cells each (every 2D case), built once
+    pub fn standard() -> Arc<PatternDb> {
+        static STANDARD: OnceLock<Arc<PatternDb>> = OnceLock::new();
+        STANDARD.get_or_init(|| Arc::new(PatternDb::generate(2, 8))).clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("pattern database serializes")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, MinesweeperError> {
+        let mut db: PatternDb = serde_json::from_str(json)
+            .map_err(|e| MinesweeperError::InvalidPatternDatabase(e.to_string()))?;
+        for (i, pattern) in db.patterns.iter().enumerate() {
+            let sig = &pattern.signature;
+            let k = sig.clues();
+            // forced regions are masks of the canonical clue order, so only canonical signatures fit
+            if k == 0 || k > MAX_CLUES || sig.regions.len() != (1 << k) - 1 || sig.canonical().0 != *sig {
+                return Err(MinesweeperError::InvalidPatternDatabase(format!("pattern {} has a malformed signature", i)));
+            }
+            // the solver trusts what a pattern forces, so every entry is re-derived from its signature
+            let derived = Self::solve_signature(sig);
+            if derived.is_none_or(|d| d.safe != pattern.safe || d.mines != pattern.mines) {
+                return Err(MinesweeperError::InvalidPatternDatabase(format!("pattern {} does not match its signature", i)));
+            }
+            db.index.insert(sig.clone(), i);
+        }
+        Ok(db)
+    }
+}
+
+/// pattern database solver
+/// looks for forced cells in single clues, then pairs, then triples of clues that share cells,
+/// matching each group against the database. forced mines become virtual flags and the search
+/// repeats; guesses fall back to the shared probability engine. `last_explanation` lists the
+/// patterns behind the last move
+#[allow(dead_code)]
+pub struct PatternSolver {
+    width: usize,
+    height: usize,
+    mines: usize,
+    db: Arc<PatternDb>,
+    /// signatures outside the database, solved on first sight
+    learned: HashMap<Signature, Option<Pattern>>,
+    last: Vec<PatternMatch>,
+}
+
+impl PatternSolver {
+    pub fn new(width: usize, height: usize, mines: usize) -> Self {
+        Self { width, height, mines, db: PatternDb::standard(), learned: HashMap::new(), last: Vec::new() }
+    }
+
+    pub fn with_database(db: PatternDb) -> Self {
+        Self { width: 0, height: 0, mines: 0, db: Arc::new(db), learned: HashMap::new(), last: Vec::new() }
+    }
+
+    /// matches one clue group; its cells are grouped into regions by which clues see them
+    fn match_group(&mut self, group: &[usize], clues: &[Clue]) -> Option<PatternMatch> {
+        if let [c] = *group {
+            // single clues only decide when they are trivial, skip the lookup for the rest
+            let clue = &clues[c];
+            if clue.mines != 0 && clue.mines != clue.cells.len() { return None; }
+        }
+        let mut membership: HashMap<usize, usize> = HashMap::new();
+        for (i, &c) in group.iter().enumerate() {
+            for &cell in &clues[c].cells { *membership.entry(cell).or_default() |= 1 << i; }
+        }
+        let mut regions = vec![0u8; (1 << group.len()) - 1];
+        for &mask in membership.values() { regions[mask - 1] = regions[mask - 1].saturating_add(1); }
+        let sig = Signature { regions, mines: group.iter().map(|&c| clues[c].mines.min(u8::MAX as usize) as u8).collect() };
+
+        let (canonical, perm) = sig.canonical();
+        let pattern = match self.db.get(&canonical) {
+            Some(known) => known?,
+            None => self.learned.entry(canonical.clone())
+                .or_insert_with(|| PatternDb::solve_signature(&canonical))
+                .as_ref()?,
+        };
+        // canonical clue i is group clue perm[i]
+        let to_group_mask = |m: u8| -> usize {
+            (0..group.len()).filter(|&i| m >> i & 1 == 1).map(|i| 1 << perm[i]).sum()
+        };
+        let cells_in = |masks: &[u8]| -> Vec<usize> {
+            let wanted: Vec<usize> = masks.iter().map(|&m| to_group_mask(m)).collect();
+            let mut cells: Vec<usize> = membership.iter().filter(|(_, m)| wanted.contains(m)).map(|(&c, _)| c).collect();
+            cells.sort_unstable();
+            cells
+        };
+        Some(PatternMatch {
+            pattern: pattern.name.clone(),
+            clues: group.iter().map(|&c| clues[c].source).collect(),
+            safe: cells_in(&pattern.safe),
+            mines: cells_in(&pattern.mines),
+        })
+    }
+}
+
+/// a revealed number with the virtual flags already taken out
+struct Clue {
+    source: usize,
+    cells: Vec<usize>,
+    mines: usize,
+}
+
+fn collect_clues(board: &Board, known_mines: &HashSet<usize>) -> Vec<Clue> {
+    let mut clues = Vec::new();
+    for (idx, cell) in board.cells.iter().enumerate() {
+        if !cell.is_revealed { continue; }
+        let mut hidden = Vec::new();
+        let mut flags = 0;
+        for &n in &board.adjacency_map[idx] {
+            let nc = &board.cells[n];
+            if nc.is_flagged || known_mines.contains(&n) { flags += 1; } else if !nc.is_revealed { hidden.push(n); }
+        }
+        if !hidden.is_empty() {
+            clues.push(Clue { source: idx, cells: hidden, mines: (cell.adjacent_mines as usize).saturating_sub(flags) });
+        }
+    }
+    clues
+}
+
+/// connected groups of `size` clues (every clue shares a cell with another one of the group)
+fn clue_groups(clues: &[Clue], size: usize) -> Vec<Vec<usize>> {
+    if size == 1 { return (0..clues.len()).map(|c| vec![c]).collect(); }
+    let mut by_cell: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (c, clue) in clues.iter().enumerate() {
+        for &cell in &clue.cells { by_cell.entry(cell).or_default().push(c); }
+    }
+    let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); clues.len()];
+    for shared in by_cell.values() {
+        for &a in shared {
+            neighbours[a].extend(shared.iter().copied().filter(|&b| b != a));
+        }
+    }
+    for n in &mut neighbours { n.sort_unstable(); n.dedup(); }
+
+    let mut groups = HashSet::new();
+    for (a, near) in neighbours.iter().enumerate() {
+        if size == 2 {
+            groups.extend(near.iter().filter(|&&b| b > a).map(|&b| vec![a, b]));
+        } else {
+            // a connected triple always has a clue next to both others
+            for (&b, &c) in near.iter().tuple_combinations() {
+                let mut group = vec![a, b, c];
+                group.sort_unstable();
+                groups.insert(group);
+            }
+        }
+    }
+    let mut groups: Vec<Vec<usize>> = groups.into_iter().collect();
+    groups.sort();
+    groups
+}
+
+impl Algorithm for PatternSolver {
+    fn find_candidates(&mut self, board: &Board) -> SolverResult {
+        self.last.clear();
+        let mut known_mines: HashSet<usize> = HashSet::new();
+        loop {
+            let clues = collect_clues(board, &known_mines);
+            let mut safe: HashSet<usize> = HashSet::new();
+            let mut new_mines = false;
+            // smallest groups first, bigger ones only where those are stuck
+            for size in 1..=MAX_CLUES {
+                for group in clue_groups(&clues, size) {
+                    let Some(found) = self.match_group(&group, &clues) else { continue; };
+                    let mut useful = false;
+                    for &s in &found.safe { useful |= safe.insert(s); }
+                    for &m in &found.mines { useful |= known_mines.insert(m); }
+                    new_mines |= useful && !found.mines.is_empty();
+                    // only patterns that decided something new explain the move
+                    if useful { self.last.push(found); }
+                }
+                if !safe.is_empty() || new_mines { break; }
+            }
+
+            if !safe.is_empty() {
+                return SolverResult { candidates: safe.into_iter().collect(), is_guess: false };
+            }
+            if !new_mines { break; }
+        }
+        probability::safest_move(board)
+    }
+
+    fn last_explanation(&self) -> Vec<PatternMatch> {
+        self.last.clone()
+    }
+
+    fn load_patterns(&mut self, db: &PatternDb) {
+        self.db = Arc::new(db.clone());
+        self.learned.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip_keeps_every_pattern() {
+        let db = PatternDb::generate(2, 4);
+        let loaded = PatternDb::from_json(&db.to_json()).unwrap();
+        assert_eq!(loaded.patterns.len(), db.patterns.len());
+        for pattern in &db.patterns {
+            let found = loaded.get(&pattern.signature).flatten().expect("indexed");
+            assert_eq!((&found.safe, &found.mines), (&pattern.safe, &pattern.mines));
+        }
+    }
+
+    #[test]
+    fn tampered_patterns_are_rejected() {
+        let mut db = PatternDb::generate(1, 3);
+        let pattern = db.patterns.iter_mut().find(|p| !p.safe.is_empty()).expect("a pattern with safe regions");
+        // claim a forced-safe region is mined instead
+        pattern.mines.push(pattern.safe.pop().unwrap());
+        assert!(matches!(PatternDb::from_json(&db.to_json()), Err(MinesweeperError::InvalidPatternDatabase(_))));
+    }
+}
@@ -0,0 +1,565 @@
+/**
+ * Exact per-cell mine probabilities shared by every solver's guess step.
+ * The frontier is split into independent components, each component's assignments are
+ * enumerated once (counted per mine total), and the components are combined with the
+ * interior cells through binomial weights for the remaining global mine count.
+ * Components too large to enumerate hand over to the sampler in sampling.rs.
+ */
+use crate::board::Board;
+use crate::distribution::MineDistribution;
+use crate::algorithms::SolverResult;
+use crate::algorithms::sampling::{self, SamplerSettings};
+use std::collections::{HashMap, VecDeque};
+
+/// upper bound on search nodes per frontier component before we give up on exact enumeration
+const MAX_NODES_PER_COMPONENT: usize = 400_000;
+
+/// per-cell mine probabilities for the current board
+/// frontier cells are exact marginals, interior cells share the probability of the unconstrained rest
+pub struct ProbabilityMap {
+    /// one entry per board cell (revealed = 0, flagged = 1)
+    pub cell_probs: Vec<f64>,
+    /// hidden cells touching a revealed number
+    pub frontier: Vec<usize>,
+    /// probability for a hidden cell without any revealed neighbour (before the prior is applied)
+    pub interior_prob: f64,
+    /// ln of the number of mine layouts consistent with clues and mine count
+    pub log_weight: f64,
+    /// false if a component was too large to enumerate and local estimates were used instead,
+    /// or if the board's prior is not uniform (the interior then follows the prior's shape)
+    pub exact: bool,
+}
+
+impl ProbabilityMap {
+    /// hidden, unflagged cells with the lowest mine probability
+    pub fn safest_cells(&self, board: &Board) -> Vec<usize> {
+        let mut best = Vec::new();
+        let mut min_p = f64::INFINITY;
+        for (idx, &p) in self.cell_probs.iter().enumerate() {
+            let cell = &board.cells[idx];
+            if cell.is_revealed || cell.is_flagged { continue; }
+            if p < min_p - 1e-9 {
+                min_p = p;
+                best = vec![idx];
+            } else if (p - min_p).abs() <= 1e-9 {
+                best.push(idx);
+            }
+        }
+        best
+    }
+
+    /// the cells of `cells` a zero marginal proves safe, for a map whose frontier counts are
+    /// exact: under a non-uniform prior an interior zero can come from the prior alone, so
+    /// there only frontier cells and an interior without mines count
+    pub fn proven_safe(&self, cells: &[usize]) -> Vec<usize> {
+        cells.iter().copied()
+            .filter(|&c| self.cell_probs[c] <= 1e-12 && (self.exact || self.interior_prob <= 1e-12 || self.frontier.contains(&c)))
+            .collect()
+    }
+}
+
+/// a revealed number over its hidden neighbours: sum(cells) == mines
+#[derive(Clone, Debug)]
+pub struct LinearConstraint {
+    pub cells: Vec<usize>,
+    pub mines: usize,
+}
+
+/// constraints of every revealed cell with hidden neighbours (flags count as known mines)
+pub fn collect_constraints(board: &Board) -> Vec<LinearConstraint> {
+    let mut constraints = Vec::new();
+    for (idx, cell) in board.cells.iter().enumerate() {
+        if !cell.is_revealed { continue; }
+        let mut hidden = Vec::new();
+        let mut flags = 0;
+        for &n in &board.adjacency_map[idx] {
+            let nc = &board.cells[n];
+            if nc.is_flagged { flags += 1; } else if !nc.is_revealed { hidden.push(n); }
+        }
+        if !hidden.is_empty() {
+            constraints.push(LinearConstraint {
+                cells: hidden,
+                mines: (cell.adjacent_mines as usize).saturating_sub(flags),
+            });
+        }
+    }
+    constraints
+}
+
+/// splits constraints into independent groups (no shared hidden cell between groups)
+pub fn split_components(constraints: &[LinearConstraint]) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..constraints.len()).collect();
+    fn find(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+
+    let mut owner: HashMap<usize, usize> = HashMap::new();
+    for (ci, c) in constraints.iter().enumerate() {
+        for &cell in &c.cells {
+            if let Some(&other) = owner.get(&cell) {
+                let (a, b) = (find(&mut parent, ci), find(&mut parent, other));
+                if a != b { parent[a] = b; }
+            } else {
+                owner.insert(cell, ci);
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for ci in 0..constraints.len() {
+        let root = find(&mut parent, ci);
+        groups.entry(root).or_default().push(ci);
+    }
+    let mut result: Vec<Vec<usize>> = groups.into_values().collect();
+    result.sort();
+    result
+}
+
+/// exact marginals: enumerate each frontier component, then combine the components with
+/// binomial weights for the interior cells and the remaining mine count.
+/// components over the node budget are estimated by sampling instead
+pub fn compute_probabilities(board: &Board) -> ProbabilityMap {
+    compute_with_frontier(board).0
+}
+
+/// `compute_probabilities` and whether every frontier component was enumerated in full.
+/// only then does sampling have nothing to add (a non-uniform prior is not sampled either)
+fn compute_with_frontier(board: &Board) -> (ProbabilityMap, bool) {
+    let components = enumerate_components(board);
+    let map = combine(board, &components);
+    if components.iter().all(|c| c.exact) { return (map, true); }
+    let map = match sampling::estimate_probabilities(board, SamplerSettings::default()) {
+        Some(sampled) => sampled.into_map(map.log_weight),
+        None => map,
+    };
+    (map, false)
+}
+
+/// the enumeration part of `compute_probabilities` without the sampling fallback
+/// (components over the node budget keep their local estimate and `exact` is false)
+pub fn enumerate_probabilities(board: &Board) -> ProbabilityMap {
+    combine(board, &enumerate_components(board))
+}
+
+fn enumerate_components(board: &Board) -> Vec<ComponentCounts> {
+    let constraints = collect_constraints(board);
+    split_components(&constraints).into_iter()
+        .map(|group| {
+            let local: Vec<&LinearConstraint> = group.iter().map(|&ci| &constraints[ci]).collect();
+            enumerate_component(&local)
+        })
+        .collect()
+}
+
+/// mine-count distribution of one component, scaled so its largest entry is 1
+pub struct ComponentCounts {
+    pub cells: Vec<usize>,
+    /// counts[m] = (scaled) number of assignments with m mines
+    pub counts: Vec<f64>,
+    /// cell_counts[m][i] = (scaled) number of those assignments where cells[i] is a mine
+    pub cell_counts: Vec<Vec<f64>>,
+    /// ln of the scale factor that was divided out
+    pub log_scale: f64,
+    pub exact: bool,
+}
+
+struct Search<'a> {
+    vars: Vec<usize>,
+    var_constraints: Vec<Vec<usize>>,
+    constraints: Vec<&'a LinearConstraint>,
+    need: Vec<isize>,
+    free: Vec<usize>,
+    assign: Vec<u8>,
+    counts: Vec<f64>,
+    cell_counts: Vec<Vec<f64>>,
+    nodes: usize,
+}
+
+impl Search<'_> {
+    fn fits(&self, v: usize, value: u8) -> bool {
+        self.var_constraints[v].iter().all(|&c| {
+            let need = self.need[c] - value as isize;
+            need >= 0 && need <= (self.free[c] - 1) as isize
+        })
+    }
+
+    fn set(&mut self, v: usize, value: u8) {
+        self.assign[v] = value;
+        for &c in &self.var_constraints[v] {
+            self.need[c] -= value as isize;
+            self.free[c] -= 1;
+        }
+    }
+
+    fn unset(&mut self, v: usize) {
+        let value = self.assign[v];
+        for &c in &self.var_constraints[v] {
+            self.need[c] += value as isize;
+            self.free[c] += 1;
+        }
+        self.assign[v] = 2;
+    }
+
+    /// depth-first over the variables with an explicit stack (`next[d]` = next value to try
+    /// at depth d), so large components cannot overflow the call stack.
+    /// returns false once the node budget is exhausted
+    fn run(&mut self) -> bool {
+        let n = self.vars.len();
+        let mut next = vec![0u8; n];
+        let mut depth = 0;
+        let mut mines = 0;
+        self.nodes += 1;
+        if self.nodes > MAX_NODES_PER_COMPONENT { return false; }
+
+        loop {
+            if depth == n {
+                self.counts[mines] += 1.0;
+                for (i, &a) in self.assign.iter().enumerate() {
+                    if a == 1 { self.cell_counts[mines][i] += 1.0; }
+                }
+            } else {
+                let mut descended = false;
+                while next[depth] <= 1 {
+                    let value = next[depth];
+                    next[depth] += 1;
+                    if self.fits(depth, value) {
+                        self.set(depth, value);
+                        mines += value as usize;
+                        depth += 1;
+                        if depth < n { next[depth] = 0; }
+                        self.nodes += 1;
+                        if self.nodes > MAX_NODES_PER_COMPONENT { return false; }
+                        descended = true;
+                        break;
+                    }
+                }
+                if descended { continue; }
+            }
+            // leaf or exhausted: step back to the parent
+            if depth == 0 { return true; }
+            depth -= 1;
+            mines -= self.assign[depth] as usize;
+            self.unset(depth);
+        }
+    }
+}
+
+/// counts all assignments of one component (cells ordered by bfs so constraints close early)
+pub fn enumerate_component(constraints: &[&LinearConstraint]) -> ComponentCounts {
+    // bfs order over shared constraints
+    let mut cell_to_constraints: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (ci, c) in constraints.iter().enumerate() {
+        for &cell in &c.cells {
+            cell_to_constraints.entry(cell).or_default().push(ci);
+        }
+    }
+    let mut order: Vec<usize> = Vec::with_capacity(cell_to_constraints.len());
+    let mut seen: HashMap<usize, usize> = HashMap::new();
+    let mut constraint_done = vec![false; constraints.len()];
+    let mut queue = VecDeque::new();
+    if let Some(&first) = constraints.first().and_then(|c| c.cells.first()) {
+        queue.push_back(first);
+        seen.insert(first, 0);
+    }
+    while let Some(cell) = queue.pop_front() {
+        order.push(cell);
+        for &ci in &cell_to_constraints[&cell] {
+            if constraint_done[ci] { continue; }
+            constraint_done[ci] = true;
+            for &n in &constraints[ci].cells {
+                if !seen.contains_key(&n) {
+                    seen.insert(n, seen.len());
+                    queue.push_back(n);
+                }
+            }
+        }
+    }
+    let position: HashMap<usize, usize> = order.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+
+    let n = order.len();
+    let mut var_constraints = vec![Vec::new(); n];
+    for (ci, c) in constraints.iter().enumerate() {
+        for &cell in &c.cells {
+            var_constraints[position[&cell]].push(ci);
+        }
+    }
+
+    let mut search = Search {
+        vars: order.clone(),
+        var_constraints,
+        constraints: constraints.to_vec(),
+        need: constraints.iter().map(|c| c.mines as isize).collect(),
+        free: constraints.iter().map(|c| c.cells.len()).collect(),
+        assign: vec![2; n],
+        counts: vec![0.0; n + 1],
+        cell_counts: vec![vec![0.0; n]; n + 1],
+        nodes: 0,
+    };
+
+    if search.run() {
+        let max = search.counts.iter().cloned().fold(0.0, f64::max);
+        let scale = if max > 0.0 { max } else { 1.0 };
+        for c in &mut search.counts { *c /= scale; }
+        for row in &mut search.cell_counts {
+            for c in row.iter_mut() { *c /= scale; }
+        }
+        ComponentCounts {
+            cells: order,
+            counts: search.counts,
+            cell_counts: search.cell_counts,
+            log_scale: scale.ln(),
+            exact: true,
+        }
+    } else {
+        local_estimate(order, &search.constraints)
+    }
+}
+
+/// fallback for components too large to enumerate: max of the local clue ratios,
+/// with all of the weight on the expected mine count
+fn local_estimate(cells: Vec<usize>, constraints: &[&LinearConstraint]) -> ComponentCounts {
+    let n = cells.len();
+    let position: HashMap<usize, usize> = cells.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+    let mut probs = vec![0.0f64; n];
+    for c in constraints {
+        let p = c.mines as f64 / c.cells.len() as f64;
+        for cell in &c.cells {
+            let i = position[cell];
+            probs[i] = probs[i].max(p);
+        }
+    }
+    let expected = (probs.iter().sum::<f64>().round() as usize).min(n);
+    let mut counts = vec![0.0; n + 1];
+    let mut cell_counts = vec![vec![0.0; n]; n + 1];
+    counts[expected] = 1.0;
+    cell_counts[expected] = probs;
+    ComponentCounts { cells, counts, cell_counts, log_scale: 0.0, exact: false }
+}
+
+/// ln(n choose k) via a log-factorial table
+pub struct LogBinomial {
+    ln_fact: Vec<f64>,
+}
+
+impl LogBinomial {
+    pub fn new(max_n: usize) -> Self {
+        let mut ln_fact = vec![0.0; max_n + 1];
+        for i in 1..=max_n {
+            ln_fact[i] = ln_fact[i - 1] + (i as f64).ln();
+        }
+        Self { ln_fact }
+    }
+
+    pub fn ln_choose(&self, n: usize, k: usize) -> f64 {
+        if k > n { return f64::NEG_INFINITY; }
+        self.ln_fact[n] - self.ln_fact[k] - self.ln_fact[n - k]
+    }
+}
+
+/// product of two count polynomials (index = mine count)
+pub fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut out = vec![0.0; a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        if x == 0.0 { continue; }
+        for (j, &y) in b.iter().enumerate() {
+            out[i + j] += x * y;
+        }
+    }
+    out
+}
+
+/// combines independent components with the interior cells and the global mine count
+pub fn combine(board: &Board, components: &[ComponentCounts]) -> ProbabilityMap {
+    let total = board.cells.len();
+    let flags = board.cells.iter().filter(|c| c.is_flagged).count();
+    let remaining = board.mines.saturating_sub(flags);
+
+    let mut in_frontier = vec![false; total];
+    for comp in components {
+        for &c in &comp.cells { in_frontier[c] = true; }
+    }
+    let interior: Vec<usize> = (0..total)
+        .filter(|&i| !board.cells[i].is_revealed && !board.cells[i].is_flagged && !in_frontier[i])
+        .collect();
+    let interior_count = interior.len();
+    let binom = LogBinomial::new(total);
+
+    // prefix/suffix convolutions give "all components except c" in linear passes
+    let k = components.len();
+    let mut prefix = vec![vec![1.0]];
+    for comp in components {
+        let next = convolve(prefix.last().unwrap(), &comp.counts);
+        prefix.push(next);
+    }
+    let mut suffix = vec![vec![1.0]; k + 1];
+    for c in (0..k).rev() {
+        suffix[c] = convolve(&suffix[c + 1], &components[c].counts);
+    }
+    let all = &prefix[k];
+
+    // weight of a total frontier mine count: ways to put the rest into the interior
+    let ln_weights: Vec<f64> = (0..all.len())
+        .map(|m| if m <= remaining { binom.ln_choose(interior_count, remaining - m) } else { f64::NEG_INFINITY })
+        .collect();
+    let mut shift = ln_weights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let mut weights: Vec<f64> = ln_weights.iter().map(|&w| (w - shift).exp()).collect();
+    let mut z: f64 = all.iter().zip(&weights).map(|(a, w)| a * w).sum();
+
+    // inconsistent global count (e.g. density boards without a known total): drop the count constraint
+    let consistent = !(z.is_nan() || z <= 0.0 || !shift.is_finite());
+    if !consistent {
+        shift = 0.0;
+        weights = vec![1.0; all.len()];
+        z = all.iter().sum();
+    }
+
+    let mut cell_probs = vec![0.0; total];
+    for (i, cell) in board.cells.iter().enumerate() {
+        if cell.is_flagged { cell_probs[i] = 1.0; }
+    }
+
+    for (c, comp) in components.iter().enumerate() {
+        let others = convolve(&prefix[c], &suffix[c + 1]);
+        // wc[m] = weight of this component holding m mines
+        let wc: Vec<f64> = (0..comp.counts.len())
+            .map(|m| others.iter().enumerate().map(|(o, &x)| x * weights.get(m + o).copied().unwrap_or(0.0)).sum())
+            .collect();
+        for (i, &cell) in comp.cells.iter().enumerate() {
+            let mass: f64 = (0..comp.counts.len()).map(|m| comp.cell_counts[m][i] * wc[m]).sum();
+            cell_probs[cell] = if z > 0.0 { (mass / z).clamp(0.0, 1.0) } else { 0.5 };
+        }
+    }
+
+    let interior_prob = if interior_count > 0 && z > 0.0 {
+        let expected: f64 = all.iter().enumerate()
+            .map(|(m, &a)| a * weights[m] * remaining.saturating_sub(m) as f64)
+            .sum::<f64>() / z;
+        (expected / interior_count as f64).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    // interior cells follow the solver prior (`Board::prior`, uniform unless set)
+    for (&i, p) in interior.iter().zip(board.prior.interior_probs(board, &interior, interior_prob)) {
+        cell_probs[i] = p;
+    }
+
+    // no layout has the mine count, however many satisfy the clues alone
+    let log_weight = if consistent { z.ln() + shift + components.iter().map(|c| c.log_scale).sum::<f64>() } else { f64::NEG_INFINITY };
+
+    ProbabilityMap {
+        cell_probs,
+        frontier: components.iter().flat_map(|c| c.cells.iter().copied()).collect(),
+        interior_prob,
+        log_weight,
+        // the counts assume every layout is equally likely, so any other prior is an estimate
+        exact: matches!(board.prior, MineDistribution::Uniform) && components.iter().all(|c| c.exact),
+    }
+}
+
+/// guess step shared by the solvers: the safest cells by exact marginals.
+/// a zero probability from a fully enumerated frontier is a proof, not a guess
+/// (see `ProbabilityMap::proven_safe` for non-uniform priors)
+pub fn safest_move(board: &Board) -> SolverResult {
+    let (map, frontier_exact) = compute_with_frontier(board);
+    let candidates = map.safest_cells(board);
+    let proven = map.proven_safe(&candidates);
+    if frontier_exact && !proven.is_empty() {
+        return SolverResult { candidates: proven, is_guess: false };
+    }
+    SolverResult { candidates, is_guess: true }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+
+    /// marginals over every layout of the remaining mines that matches the revealed numbers
+    fn brute_force(board: &Board) -> Vec<f64> {
+        let hidden: Vec<usize> = (0..board.cells.len()).filter(|&i| !board.cells[i].is_revealed).collect();
+        let mut mine_counts = vec![0.0; board.cells.len()];
+        let mut total = 0.0;
+        let mut chosen = Vec::new();
+        fn walk(board: &Board, hidden: &[usize], start: usize, left: usize, chosen: &mut Vec<usize>, counts: &mut [f64], total: &mut f64) {
+            if left == 0 {
+                let consistent = board.cells.iter().enumerate().filter(|(_, c)| c.is_revealed).all(|(i, c)| {
+                    board.adjacency_map[i].iter().filter(|n| chosen.contains(n)).count() == c.adjacent_mines as usize
+                });
+                if consistent {
+                    *total += 1.0;
+                    for &m in chosen.iter() { counts[m] += 1.0; }
+                }
+                return;
+            }
+            for k in start..hidden.len() {
+                chosen.push(hidden[k]);
+                walk(board, hidden, k + 1, left - 1, chosen, counts, total);
+                chosen.pop();
+            }
+        }
+        walk(board, &hidden, 0, board.mines, &mut chosen, &mut mine_counts, &mut total);
+        mine_counts.iter().map(|c| c / total).collect()
+    }
+
+    #[test]
+    fn enumeration_matches_brute_force() {
+        for seed in 0..20 {
+            let mut sim = Simulator::new(vec![4, 5], 5, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            for _ in 0..4 {
+                if sim.board.game_over { break; }
+                let map = enumerate_probabilities(&sim.board);
+                let expected = brute_force(&sim.board);
+                for (i, cell) in sim.board.cells.iter().enumerate() {
+                    if cell.is_revealed { continue; }
+                    assert!((map.cell_probs[i] - expected[i]).abs() < 1e-9, "seed {seed} cell {i}: {} vs {}", map.cell_probs[i], expected[i]);
+                }
+                sim.run_step().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    /// dense enough that the gradient alone would push cells past 1
+    fn prior_shapes_the_interior_without_changing_its_mass() {
+        let mut checked = 0;
+        for seed in 0..20 {
+            let mut sim = Simulator::new(vec![6, 6], 20, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            for _ in 0..3 {
+                if sim.board.game_over { break; }
+                let uniform = enumerate_probabilities(&sim.board);
+                let uniform_move = safest_move(&sim.board);
+                sim.board.prior = MineDistribution::Gradient { axis: 0, strength: 1.0 };
+                let shaped = enumerate_probabilities(&sim.board);
+                let shaped_move = safest_move(&sim.board);
+                sim.board.prior = MineDistribution::Uniform;
+
+                assert!(uniform.exact && !shaped.exact);
+                for &c in &uniform.frontier {
+                    assert_eq!(uniform.cell_probs[c], shaped.cell_probs[c]);
+                }
+                let interior: Vec<usize> = (0..sim.board.cells.len())
+                    .filter(|&i| !sim.board.cells[i].is_revealed && !uniform.frontier.contains(&i))
+                    .collect();
+                let mass: f64 = interior.iter().map(|&i| shaped.cell_probs[i]).sum();
+                assert!((mass - uniform.interior_prob * interior.len() as f64).abs() < 1e-9, "seed {seed}");
+                assert!(interior.iter().all(|&i| (0.0..=1.0).contains(&shaped.cell_probs[i])));
+                // the prior can add zero-probability interior cells but never hides a proof
+                if !uniform_move.is_guess {
+                    assert!(!shaped_move.is_guess, "seed {seed}");
+                    assert!(shaped_move.candidates.iter().all(|c| uniform_move.candidates.contains(c)));
+                    checked += 1;
+                }
+                sim.run_step().unwrap();
+            }
+        }
+        assert!(checked > 0);
+    }
+}
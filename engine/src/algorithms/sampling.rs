@@ -0,0 +1,469 @@
+/**
+ * Monte Carlo estimate of per-cell mine probabilities for frontiers too large to enumerate.
+ * Block Gibbs sampling over frontier layouts: a connected block of cells is resampled exactly,
+ * conditioned on the rest of the frontier, the clues and the remaining mine count (the
+ * interior enters as a binomial weight). Chains start from a CDCL model and are seeded
+ * from the board, so seeded games stay reproducible. Independent chains give the
+ * confidence intervals.
+ */
+use crate::board::Board;
+use crate::algorithms::CardinalityEncoding;
+use crate::algorithms::cdcl::CdclSolver;
+use crate::algorithms::probability::{self, LinearConstraint, LogBinomial, ProbabilityMap};
+use crate::algorithms::sat_utils::{add_cardinality, bound_unary, lit, totalizer};
+use rand::Rng;
+use rand::seq::index;
+use rand::rngs::StdRng;
+use std::collections::{HashMap, VecDeque};
+
+/// rng stream of the sampler (combined with the click count, so every step draws fresh)
+const SAMPLING_STREAM: u64 = 0x3C6E_F372_FE94_F82B;
+/// most cells resampled together; chains of forced cells (walls) need long blocks to flip
+const MAX_BLOCK_SIZE: usize = 48;
+/// enumeration nodes per block update before the block is halved
+const MAX_BLOCK_NODES: usize = 20_000;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SamplerSettings {
+    pub chains: usize,
+    /// kept samples per chain
+    pub samples: usize,
+    /// sweeps thrown away at the start of each chain
+    pub burn_in: usize,
+}
+
+impl Default for SamplerSettings {
+    fn default() -> Self {
+        Self { chains: 4, samples: 100, burn_in: 10 }
+    }
+}
+
+/// estimated marginals with 95% confidence half-widths (from the spread between chains)
+#[derive(Clone, Debug)]
+pub struct SampledProbabilities {
+    /// one entry per board cell (revealed = 0, flagged = 1)
+    pub cell_probs: Vec<f64>,
+    pub ci_half_width: Vec<f64>,
+    pub frontier: Vec<usize>,
+    pub interior_prob: f64,
+    pub samples: usize,
+}
+
+impl SampledProbabilities {
+    pub fn into_map(self, log_weight: f64) -> ProbabilityMap {
+        ProbabilityMap {
+            cell_probs: self.cell_probs,
+            frontier: self.frontier,
+            interior_prob: self.interior_prob,
+            log_weight,
+            exact: false,
+        }
+    }
+}
+
+/// clues over frontier positions (instead of cell indices)
+struct Clues {
+    vars: Vec<Vec<usize>>,
+    mines: Vec<usize>,
+    /// clue indices per frontier position
+    var_clues: Vec<Vec<usize>>,
+    /// ln C(interior, remaining - m), indexed by frontier mine count m
+    ln_weight: Vec<f64>,
+}
+
+impl Clues {
+    fn weight(&self, mines: usize) -> f64 {
+        self.ln_weight.get(mines).copied().unwrap_or(f64::NEG_INFINITY)
+    }
+
+    /// up to `size` frontier positions around `start`, grown through shared clues
+    fn pick_block(&self, start: usize, size: usize, marked: &mut [bool]) -> Vec<usize> {
+        let mut block = vec![start];
+        marked[start] = true;
+        let mut queue = VecDeque::from([start]);
+        'grow: while let Some(v) = queue.pop_front() {
+            for &c in &self.var_clues[v] {
+                for &w in &self.vars[c] {
+                    if block.len() == size { break 'grow; }
+                    if !marked[w] {
+                        marked[w] = true;
+                        block.push(w);
+                        queue.push_back(w);
+                    }
+                }
+            }
+        }
+        for &v in &block { marked[v] = false; }
+        block
+    }
+}
+
+struct Chain<'a> {
+    clues: &'a Clues,
+    state: Vec<bool>,
+    mines: usize,
+    /// mines of the current state inside every clue
+    clue_count: Vec<usize>,
+    /// scratch: local index of a clue touched by the current block
+    local_index: Vec<Option<usize>>,
+}
+
+impl Chain<'_> {
+    fn new(clues: &Clues, state: Vec<bool>) -> Chain<'_> {
+        let clue_count = clues.vars.iter().map(|vars| vars.iter().filter(|&&v| state[v]).count()).collect();
+        Chain {
+            clues,
+            mines: state.iter().filter(|&&b| b).count(),
+            state,
+            clue_count,
+            local_index: vec![None; clues.vars.len()],
+        }
+    }
+
+    /// resamples the block exactly from its conditional distribution.
+    /// false if the block has too many consistent assignments to enumerate
+    fn update_block(&mut self, block: &[usize], rng: &mut StdRng) -> bool {
+        let old_block_mines = block.iter().filter(|&&v| self.state[v]).count();
+        let rest_mines = self.mines - old_block_mines;
+
+        // clues touching the block, with the mines they still want from it and its cells in them
+        let mut touched: Vec<usize> = Vec::new();
+        let mut need: Vec<isize> = Vec::new();
+        let mut left: Vec<usize> = Vec::new();
+        let mut local: Vec<Vec<usize>> = Vec::with_capacity(block.len());
+        for &v in block {
+            let mut cell_local = Vec::with_capacity(self.clues.var_clues[v].len());
+            for &c in &self.clues.var_clues[v] {
+                let k = *self.local_index[c].get_or_insert_with(|| {
+                    touched.push(c);
+                    need.push(self.clues.mines[c] as isize - self.clue_count[c] as isize);
+                    left.push(0);
+                    touched.len() - 1
+                });
+                need[k] += isize::from(self.state[v]);
+                left[k] += 1;
+                cell_local.push(k);
+            }
+            local.push(cell_local);
+        }
+        for &c in &touched { self.local_index[c] = None; }
+
+        let mut enumeration = BlockEnumeration { local: &local, need, left, options: Vec::new(), nodes: 0 };
+        if !enumeration.run() { return false; }
+        let options = enumeration.options;
+        if options.is_empty() { return true; } // the current state is always an option, only hit on bad input
+
+        // weight by the number of interior completions
+        let max = options.iter().map(|&(_, k)| self.clues.weight(rest_mines + k)).fold(f64::NEG_INFINITY, f64::max);
+        if !max.is_finite() { return true; }
+        let weights: Vec<f64> = options.iter().map(|&(_, k)| (self.clues.weight(rest_mines + k) - max).exp()).collect();
+        let mut pick = rng.gen::<f64>() * weights.iter().sum::<f64>();
+        let mut chosen = options.len() - 1;
+        for (i, w) in weights.iter().enumerate() {
+            if pick < *w { chosen = i; break; }
+            pick -= w;
+        }
+
+        let (bits, count) = options[chosen];
+        for (i, &v) in block.iter().enumerate() {
+            let mine = bits >> i & 1 == 1;
+            if mine == self.state[v] { continue; }
+            self.state[v] = mine;
+            for &c in &self.clues.var_clues[v] {
+                if mine { self.clue_count[c] += 1; } else { self.clue_count[c] -= 1; }
+            }
+        }
+        self.mines = rest_mines + count;
+        true
+    }
+}
+
+/// depth first enumeration of the block assignments that keep every touched clue satisfiable
+struct BlockEnumeration<'a> {
+    /// local clue indices per block cell
+    local: &'a [Vec<usize>],
+    need: Vec<isize>,
+    left: Vec<usize>,
+    /// (mine bits, mine count) per consistent assignment
+    options: Vec<(u64, usize)>,
+    nodes: usize,
+}
+
+impl BlockEnumeration<'_> {
+    /// sets block cell `depth` to `value`, false if a touched clue can no longer be met
+    fn assign(&mut self, depth: usize, value: bool) -> bool {
+        let mut ok = true;
+        for &k in &self.local[depth] {
+            self.need[k] -= isize::from(value);
+            self.left[k] -= 1;
+            ok &= self.need[k] >= 0 && self.need[k] <= self.left[k] as isize;
+        }
+        ok
+    }
+
+    fn unassign(&mut self, depth: usize, value: bool) {
+        for &k in &self.local[depth] {
+            self.need[k] += isize::from(value);
+            self.left[k] += 1;
+        }
+    }
+
+    /// depth first with an explicit stack (`next[d]` = values tried at depth d), no recursion
+    /// on the small wasm stack. false once the node budget is exhausted
+    fn run(&mut self) -> bool {
+        let n = self.local.len();
+        let mut next = vec![0u8; n];
+        let (mut depth, mut bits, mut count) = (0, 0u64, 0);
+        self.nodes += 1;
+        if self.nodes > MAX_BLOCK_NODES { return false; }
+
+        loop {
+            if depth == n {
+                self.options.push((bits, count));
+            } else {
+                let mut descended = false;
+                while next[depth] < 2 {
+                    let value = next[depth] == 1;
+                    next[depth] += 1;
+                    if !self.assign(depth, value) {
+                        self.unassign(depth, value);
+                        continue;
+                    }
+                    if value { bits |= 1 << depth; count += 1; }
+                    depth += 1;
+                    if depth < n { next[depth] = 0; }
+                    self.nodes += 1;
+                    if self.nodes > MAX_BLOCK_NODES { return false; }
+                    descended = true;
+                    break;
+                }
+                if descended { continue; }
+            }
+            // leaf or both values tried: undo the parent's current value
+            if depth == 0 { return true; }
+            depth -= 1;
+            let value = next[depth] == 2;
+            self.unassign(depth, value);
+            if value { bits &= !(1 << depth); count -= 1; }
+        }
+    }
+}
+
+/// clues, frontier and a starting layout, shared by the estimator and the layout sampler
+struct Prepared {
+    clues: Clues,
+    frontier: Vec<usize>,
+    interior: Vec<usize>,
+    remaining: usize,
+    start: Vec<bool>,
+}
+
+/// none if the clues admit no layout at all
+fn prepare(board: &Board) -> Option<Prepared> {
+    let constraints = probability::collect_constraints(board);
+    let mut frontier: Vec<usize> = constraints.iter().flat_map(|c| c.cells.iter().copied()).collect();
+    frontier.sort_unstable();
+    frontier.dedup();
+    let position: HashMap<usize, usize> = frontier.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+
+    let flags = board.cells.iter().filter(|c| c.is_flagged).count();
+    let remaining = board.mines.saturating_sub(flags);
+    let interior: Vec<usize> = (0..board.cells.len())
+        .filter(|&i| !board.cells[i].is_revealed && !board.cells[i].is_flagged && !position.contains_key(&i))
+        .collect();
+
+    let binom = LogBinomial::new(board.cells.len());
+    let mut clues = Clues {
+        vars: constraints.iter().map(|c| c.cells.iter().map(|cell| position[cell]).collect()).collect(),
+        mines: constraints.iter().map(|c| c.mines).collect(),
+        var_clues: vec![Vec::new(); frontier.len()],
+        ln_weight: (0..=frontier.len())
+            .map(|m| if m <= remaining { binom.ln_choose(interior.len(), remaining - m) } else { f64::NEG_INFINITY })
+            .collect(),
+    };
+    for (c, vars) in clues.vars.iter().enumerate() {
+        for &v in vars { clues.var_clues[v].push(c); }
+    }
+
+    let start = initial_layout(board, &constraints, &frontier, remaining, interior.len())?;
+    Some(Prepared { clues, frontier, interior, remaining, start })
+}
+
+/// one sweep resamples about as many cells as the frontier holds.
+/// `block_size` shrinks where the clues are loose and creeps back up after successful updates
+fn sweep(chain: &mut Chain, block_size: &mut usize, marked: &mut [bool], rng: &mut StdRng) {
+    let clues = chain.clues;
+    let n = chain.state.len();
+    let mut covered = 0;
+    while covered < n {
+        let block = clues.pick_block(rng.gen_range(0..n), *block_size, marked);
+        if chain.update_block(&block, rng) {
+            covered += block.len();
+            *block_size = (*block_size + 1).min(MAX_BLOCK_SIZE);
+        } else {
+            *block_size = (*block_size / 2).max(1);
+        }
+    }
+}
+
+/// rng for one chain, fresh for every click
+fn chain_rng(board: &Board, stream: u64, chain_idx: usize) -> StdRng {
+    board.rng(stream ^ (board.total_clicks as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ chain_idx as u64)
+}
+
+/// estimates per-cell mine probabilities by sampling consistent layouts.
+/// none if the clues admit no layout at all
+pub fn estimate_probabilities(board: &Board, settings: SamplerSettings) -> Option<SampledProbabilities> {
+    let Prepared { clues, frontier, interior, remaining, start } = prepare(board)?;
+    let chains = settings.chains.max(1);
+    let samples = settings.samples.max(1);
+
+    let mut chain_means: Vec<Vec<f64>> = Vec::with_capacity(chains);
+    let mut chain_interior: Vec<f64> = Vec::with_capacity(chains);
+    let mut marked = vec![false; frontier.len()];
+    for chain_idx in 0..chains {
+        let mut rng = chain_rng(board, SAMPLING_STREAM, chain_idx);
+        let mut chain = Chain::new(&clues, start.clone());
+        let mut block_size = MAX_BLOCK_SIZE;
+
+        let mut hits = vec![0.0; frontier.len()];
+        let mut interior_mines = 0.0;
+        for round in 0..settings.burn_in + samples {
+            if !frontier.is_empty() { sweep(&mut chain, &mut block_size, &mut marked, &mut rng); }
+            if round >= settings.burn_in {
+                for (h, &s) in hits.iter_mut().zip(&chain.state) {
+                    if s { *h += 1.0; }
+                }
+                interior_mines += remaining.saturating_sub(chain.mines) as f64;
+            }
+        }
+        chain_means.push(hits.into_iter().map(|h| h / samples as f64).collect());
+        chain_interior.push(if interior.is_empty() { 0.0 } else { interior_mines / samples as f64 / interior.len() as f64 });
+    }
+
+    let mut cell_probs = vec![0.0; board.cells.len()];
+    let mut ci_half_width = vec![0.0; board.cells.len()];
+    for (i, cell) in board.cells.iter().enumerate() {
+        if cell.is_flagged { cell_probs[i] = 1.0; }
+    }
+    for (pos, &cell) in frontier.iter().enumerate() {
+        let (mean, half) = mean_ci(chain_means.iter().map(|m| m[pos]));
+        cell_probs[cell] = mean;
+        ci_half_width[cell] = half;
+    }
+    let (interior_prob, interior_half) = mean_ci(chain_interior.iter().copied());
+    for (&i, p) in interior.iter().zip(board.prior.interior_probs(board, &interior, interior_prob)) {
+        cell_probs[i] = p;
+        ci_half_width[i] = if interior_prob > 0.0 { interior_half * p / interior_prob } else { interior_half };
+    }
+
+    Some(SampledProbabilities {
+        cell_probs,
+        ci_half_width,
+        frontier,
+        interior_prob,
+        samples: chains * samples,
+    })
+}
+
+/// `count` full mine layouts (cell indices, flags included) consistent with the board, one per
+/// sweep of a single chain after `burn_in` sweeps. interior mines are spread uniformly.
+/// `stream` picks the rng stream, so callers don't replay the estimator's draws
+pub fn sample_layouts(board: &Board, count: usize, burn_in: usize, stream: u64) -> Option<Vec<Vec<usize>>> {
+    let Prepared { clues, frontier, interior, remaining, start } = prepare(board)?;
+    let flagged: Vec<usize> = (0..board.cells.len()).filter(|&i| board.cells[i].is_flagged).collect();
+
+    let mut rng = chain_rng(board, stream, 0);
+    let mut chain = Chain::new(&clues, start);
+    let mut block_size = MAX_BLOCK_SIZE;
+    let mut marked = vec![false; frontier.len()];
+    let mut layouts = Vec::with_capacity(count);
+    for round in 0..burn_in + count {
+        if !frontier.is_empty() { sweep(&mut chain, &mut block_size, &mut marked, &mut rng); }
+        if round < burn_in { continue; }
+        let mut mines = flagged.clone();
+        mines.extend(frontier.iter().zip(&chain.state).filter(|&(_, &s)| s).map(|(&c, _)| c));
+        let rest = remaining.saturating_sub(chain.mines).min(interior.len());
+        mines.extend(index::sample(&mut rng, interior.len(), rest).into_iter().map(|i| interior[i]));
+        layouts.push(mines);
+    }
+    Some(layouts)
+}
+
+/// any layout consistent with the clues and the mine total, found by the cdcl solver
+fn initial_layout(board: &Board, constraints: &[LinearConstraint], frontier: &[usize], remaining: usize, interior: usize) -> Option<Vec<bool>> {
+    let mut clauses = Vec::new();
+    let mut next_var = board.cells.len();
+    for c in constraints {
+        add_cardinality(&mut clauses, &mut next_var, &c.cells, c.mines, c.mines, CardinalityEncoding::Totalizer);
+    }
+    let lits: Vec<isize> = frontier.iter().map(|&c| lit(c)).collect();
+    let outputs = totalizer(&mut clauses, &mut next_var, &lits, remaining + 1);
+    bound_unary(&mut clauses, &outputs, remaining.saturating_sub(interior), remaining);
+
+    let mut solver = CdclSolver::from_clauses(next_var, &clauses).ok()?;
+    if !solver.solve() { return None; }
+    Some(frontier.iter().map(|&c| solver.model_value(c)).collect())
+}
+
+/// two-sided 95% quantiles of student's t for 1..=30 degrees of freedom
+const T_975: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+/// mean over chains and the 95% half-width of the mean. with a handful of chains the
+/// spread estimate is itself noisy, so the quantile is student's t with chains - 1 degrees of freedom
+fn mean_ci(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let values: Vec<f64> = values.collect();
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    if values.len() < 2 { return (mean, 0.0); }
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let t = T_975.get(values.len() - 2).copied().unwrap_or(1.96);
+    (mean, t * (var / n).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn block_enumeration_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..200 {
+            let cells = rng.gen_range(0..10);
+            let clues = rng.gen_range(1..5);
+            let local: Vec<Vec<usize>> = (0..cells)
+                .map(|_| (0..clues).filter(|_| rng.gen_bool(0.5)).collect())
+                .collect();
+            let left: Vec<usize> = (0..clues).map(|k| local.iter().filter(|l| l.contains(&k)).count()).collect();
+            let need: Vec<isize> = left.iter().map(|&l| rng.gen_range(0..=l) as isize).collect();
+
+            let mut expected = Vec::new();
+            for bits in 0..1u64 << cells {
+                let fits = (0..clues).all(|k| {
+                    (0..cells).filter(|&i| bits >> i & 1 == 1 && local[i].contains(&k)).count() as isize == need[k]
+                });
+                if fits { expected.push((bits, bits.count_ones() as usize)); }
+            }
+
+            let mut enumeration = BlockEnumeration { local: &local, need: need.clone(), left: left.clone(), options: Vec::new(), nodes: 0 };
+            assert!(enumeration.run());
+            enumeration.options.sort_unstable();
+            assert_eq!(enumeration.options, expected);
+            // need and left are restored for the next block
+            assert_eq!((enumeration.need, enumeration.left), (need, left));
+        }
+    }
+
+    #[test]
+    fn half_width_uses_the_t_quantile() {
+        let (mean, half) = mean_ci([0.1, 0.2, 0.3, 0.4].into_iter());
+        let se = (0.05f64 / 3.0 / 4.0).sqrt();
+        assert!((mean - 0.25).abs() < 1e-12);
+        assert!((half - 3.182 * se).abs() < 1e-9);
+    }
+}
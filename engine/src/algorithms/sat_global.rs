@@ -1,76 +1,153 @@
-use crate::board::Board;
-use crate::algorithms::{Algorithm, SolverResult};
-use crate::algorithms::sat_utils::*;
-use std::collections::HashSet;
-
-/// global sat solver algorithm
-pub struct GlobalSatSolver {
-    _width: usize,
-    _height: usize,
-    mines: usize,
-}
-
-impl GlobalSatSolver {
-    pub fn new(width: usize, height: usize, mines: usize) -> Self {
-        Self { _width: width, _height: height, mines }
-    }
-}
-
-impl Algorithm for GlobalSatSolver {
-    fn find_candidates(&mut self, board: &Board) -> SolverResult {
-        let mut safe_cells = Vec::new();
-        let frontier = get_frontier(board);
-
-        // if no cells are currently revealed, we must use probability to guess
-        if frontier.is_empty() {
-            return SolverResult {
-                candidates: get_probabilistic_fallback(board, self._width, self._height, self.mines),
-                is_guess: true,
-            };
-        }
-
-        // build the base cnf from revealed cells
-        let mut base_clauses = Vec::new();
-        for (idx, cell) in board.cells.iter().enumerate() {
-            if cell.is_revealed && cell.adjacent_mines > 0 {
-                let neighbors: Vec<usize> = board.adjacency_map[idx].iter()
-                    .filter(|&&n| !board.cells[n].is_revealed && !board.cells[n].is_flagged)
-                    .cloned().collect();
-                
-                let flags = board.adjacency_map[idx].iter().filter(|&&n| board.cells[n].is_flagged).count();
-                let k = (cell.adjacent_mines as usize).saturating_sub(flags);
-                
-                if !neighbors.is_empty() {
-                    add_exactly_k_clauses(&mut base_clauses, &neighbors, k);
-                }
-            }
-        }
-
-        // proof by contradiction logic
-        for &idx in &frontier {
-            let mut test_clauses = base_clauses.clone();
-            
-            // add a clause forcing this specific cell to be a mine
-            test_clauses.push(Clause(vec![(idx as isize) + 1]));
-
-            // algo checks if this assumption is possible
-            if !dpll(test_clauses, HashSet::new()) {
-                safe_cells.push(idx);
-            }
-        }
-
-        // if logic finds no guaranteed safe spots, we fall back to probability
-        if safe_cells.is_empty() {
-            SolverResult {
-                candidates: get_probabilistic_fallback(board, self._width, self._height, self.mines),
-                is_guess: true,
-            }
-        } else {
-            // logical certainty found
-            SolverResult {
-                candidates: safe_cells,
-                is_guess: false,
-            }
-        }
-    }
-}
\ No newline at end of file
+use crate::board::Board;
+use crate::algorithms::{Algorithm, CardinalityEncoding, SolverConfig, SolverResult};
+use crate::algorithms::sat_utils::*;
+use crate::algorithms::cdcl::CdclSolver;
+use crate::algorithms::walksat::WalkSat;
+use crate::algorithms::knowledge::KnowledgeState;
+use crate::error::MinesweeperError;
+
+/// global sat solver algorithm
+pub struct GlobalSatSolver {
+    _width: usize,
+    _height: usize,
+    _mines: usize,
+    encoding: CardinalityEncoding,
+    global_mine_count: bool,
+    local_search_flips: usize,
+    local_search_seed: u64,
+}
+
+impl GlobalSatSolver {
+    pub fn new(width: usize, height: usize, mines: usize) -> Self {
+        Self { _width: width, _height: height, _mines: mines, encoding: CardinalityEncoding::default(), global_mine_count: false, local_search_flips: 0, local_search_seed: 0 }
+    }
+}
+
+impl Algorithm for GlobalSatSolver {
+    fn configure(&mut self, config: &SolverConfig) {
+        self.encoding = config.encoding;
+        self.global_mine_count = config.global_mine_count;
+        self.local_search_flips = config.local_search_flips;
+        self.local_search_seed = config.local_search_seed;
+    }
+
+    fn find_candidates(&mut self, board: &Board) -> SolverResult {
+        self.find_candidates_with(board, &mut KnowledgeState::from_board(board))
+    }
+
+    fn find_candidates_with(&mut self, board: &Board, knowledge: &mut KnowledgeState) -> SolverResult {
+        let frontier: Vec<usize> = knowledge.frontier().iter().copied().collect();
+
+        // if no cells are currently revealed, we must use probability to guess
+        if frontier.is_empty() {
+            return get_probabilistic_fallback(board);
+        }
+
+        // the cnf only uses vars it allocated, an error is a bug in an encoding: prove nothing
+        let safe_cells = self.deduce(board, knowledge, &frontier).unwrap_or_default();
+        for &c in &safe_cells { knowledge.record_safe(c); }
+
+        // if logic finds no guaranteed safe spots, we fall back to probability
+        if safe_cells.is_empty() {
+            get_probabilistic_fallback(board)
+        } else {
+            // logical certainty found
+            SolverResult {
+                candidates: safe_cells,
+                is_guess: false,
+            }
+        }
+    }
+}
+
+impl GlobalSatSolver {
+    /// every frontier cell (and with the global count the interior) that no model can make a mine
+    fn deduce(&self, board: &Board, knowledge: &KnowledgeState, frontier: &[usize]) -> Result<Vec<usize>, MinesweeperError> {
+        // build the base cnf from revealed cells (cells are vars 0..n, aux vars follow)
+        let mut base_clauses = Vec::new();
+        let mut clues = Vec::new();
+        let mut next_var = board.cells.len();
+        for clue in knowledge.clues().values() {
+            add_cardinality(&mut base_clauses, &mut next_var, &clue.cells, clue.mines, clue.mines, self.encoding);
+            clues.push((clue.cells.clone(), clue.mines));
+        }
+
+        // global count: interior cells are one aggregate, so the frontier holds between
+        // remaining - interior and remaining mines (totalizer outputs keep the count queryable)
+        let mut global = None;
+        let mut total = None;
+        if self.global_mine_count {
+            let (remaining, interior) = (knowledge.remaining_mines(board), knowledge.interior());
+            total = Some((remaining.saturating_sub(interior.len()), remaining));
+            let lits: Vec<isize> = frontier.iter().map(|&c| lit(c)).collect();
+            let outputs = totalizer(&mut base_clauses, &mut next_var, &lits, remaining + 1);
+            bound_unary(&mut base_clauses, &outputs, remaining.saturating_sub(interior.len()), remaining);
+            global = Some((outputs, remaining, interior));
+        }
+
+        // proof by contradiction logic: one solver, "idx is a mine" as an assumption per cell
+        let mut solver = CdclSolver::from_clauses(next_var, &base_clauses)?;
+        let (mut safe_cells, query) = pin_known(&mut solver, frontier, knowledge)?;
+        // walksat witnesses first, cdcl only for the cells local search can't rule out. it sees
+        // the whole frontier with the known cells fixed, so the clues and the total stay exact
+        let mut local = (self.local_search_flips > 0).then(|| {
+            let (cells, fixed) = local_search_cells(&query, &safe_cells, knowledge);
+            WalkSat::new(board, &cells, &clues, total, &fixed, self.local_search_flips, self.local_search_seed)
+        });
+        safe_cells.extend(find_provably_safe(&mut solver, &query, local.as_mut())?);
+
+        // endgame: if the frontier can't hold fewer than all remaining mines, the interior is safe
+        if let Some((outputs, remaining, interior)) = global {
+            let frontier_takes_all = remaining == 0
+                || (remaining <= outputs.len() && !solver.solve_with_assumptions(&[-outputs[remaining - 1]])?);
+            if frontier_takes_all && solver.solve() {
+                safe_cells.extend(interior);
+            }
+        }
+        Ok(safe_cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{probability, WasmAlgorithmType};
+    use crate::Simulator;
+
+    /// with the global count, whole games down to the endgame: the deductions are exactly
+    /// the cells no layout puts a mine on
+    #[test]
+    fn global_count_deductions_match_exact_marginals() {
+        let mut endgames = 0;
+        for (dims, mines) in [(vec![4, 5], 5), (vec![6, 6], 12), (vec![3, 3, 3], 4)] {
+            for seed in 0..20 {
+                let mut sim = Simulator::new(dims.clone(), mines, WasmAlgorithmType::ExactSolver).unwrap();
+                sim.set_seed(seed).unwrap();
+                sim.run_step().unwrap();
+                while !sim.board.game_over {
+                    let mut solver = GlobalSatSolver::new(0, 0, mines);
+                    solver.configure(&SolverConfig { global_mine_count: true, ..SolverConfig::default() });
+                    let result = solver.find_candidates(&sim.board);
+                    let map = probability::enumerate_probabilities(&sim.board);
+                    let mut safe: Vec<usize> = (0..sim.board.cells.len())
+                        .filter(|&i| !sim.board.cells[i].is_revealed && map.cell_probs[i] < 1e-12)
+                        .collect();
+                    if !safe.is_empty() {
+                        let mut candidates = result.candidates.clone();
+                        candidates.sort_unstable();
+                        candidates.dedup();
+                        safe.sort_unstable();
+                        assert!(!result.is_guess, "{dims:?} seed {seed}: guessed with safe cells left");
+                        assert_eq!(candidates, safe, "{dims:?} seed {seed}");
+                        // only the mine total can clear a cell no clue touches
+                        if safe.iter().any(|c| !map.frontier.contains(c)) { endgames += 1; }
+                    } else {
+                        assert!(result.is_guess, "{dims:?} seed {seed}: deduced a cell that can hold a mine");
+                    }
+                    sim.run_step().unwrap();
+                }
+            }
+        }
+        assert!(endgames > 0);
+    }
+}
@@ -1,107 +1,206 @@
-use crate::board::Board;
-use crate::algorithms::{Algorithm, SolverResult};
-use crate::algorithms::sat_utils::*;
-use std::collections::{HashSet, VecDeque};
-
-/// partitioned sat solver algorithm 
-pub struct PartitionedSatSolver {
-    _width: usize,
-    _height: usize,
-    mines: usize,
-}
-
-impl PartitionedSatSolver {
-    pub fn new(width: usize, height: usize, mines: usize) -> Self {
-        Self { _width: width, _height: height, mines }
-    }
-}
-
-impl Algorithm for PartitionedSatSolver {
-    fn find_candidates(&mut self, board: &Board) -> SolverResult {
-        let mut safe_cells = Vec::new();
-        let frontier = get_frontier(board);
-
-        if frontier.is_empty() {
-            return SolverResult {
-                candidates: get_probabilistic_fallback(board, self._width, self._height, self.mines),
-                is_guess: true,
-            };
-        }
-
-        // identify independent clusters via connected components
-        let clusters = self.get_connected_components(&frontier, board);
-
-        for cluster in clusters {
-            // build localized cnf for this cluster
-            let mut cluster_clauses = Vec::new();
-            let mut related_clues = HashSet::new();
-            for &idx in &cluster {
-                for &adj in &board.adjacency_map[idx] {
-                    if board.cells[adj].is_revealed { related_clues.insert(adj); }
-                }
-            }
-            for clue_idx in related_clues {
-                let neighbors: Vec<usize> = board.adjacency_map[clue_idx].iter()
-                    .filter(|&&n| !board.cells[n].is_revealed && !board.cells[n].is_flagged)
-                    .cloned().collect();
-                let flags = board.adjacency_map[clue_idx].iter().filter(|&&n| board.cells[n].is_flagged).count();
-                let k = (board.cells[clue_idx].adjacent_mines as usize).saturating_sub(flags);
-                if !neighbors.is_empty() { add_exactly_k_clauses(&mut cluster_clauses, &neighbors, k); }
-            }
-
-            // test each cell in the cluster
-            for &idx in &cluster {
-                let mut test_clauses = cluster_clauses.clone();
-                test_clauses.push(Clause(vec![(idx as isize) + 1]));
-                if !dpll(test_clauses, HashSet::new()) {
-                    safe_cells.push(idx);
-                }
-            }
-        }
-
-        if safe_cells.is_empty() {
-            SolverResult {
-                candidates: get_probabilistic_fallback(board, self._width, self._height, self.mines),
-                is_guess: true,
-            }
-        } else {
-            SolverResult {
-                candidates: safe_cells,
-                is_guess: false,
-            }
-        }
-    }
-}
-
-impl PartitionedSatSolver {
-    fn get_connected_components(&self, frontier: &[usize], board: &Board) -> Vec<Vec<usize>> {
-        // logic is simple: use dfs to find all non revealed 
-        // we can do it way more complicated but is it worth the overhead?
-        let mut visited = HashSet::new();
-        let mut clusters = Vec::new();
-        let frontier_set: HashSet<usize> = frontier.iter().cloned().collect();
-
-        for &start in frontier {
-            if visited.contains(&start) { continue; }
-            let mut cluster = Vec::new();
-            let mut queue = VecDeque::from([start]);
-            visited.insert(start);
-
-            while let Some(node) = queue.pop_front() {
-                cluster.push(node);
-                for &clue in &board.adjacency_map[node] {
-                    if board.cells[clue].is_revealed {
-                        for &neighbor in &board.adjacency_map[clue] {
-                            if frontier_set.contains(&neighbor) && !visited.contains(&neighbor) {
-                                visited.insert(neighbor);
-                                queue.push_back(neighbor);
-                            }
-                        }
-                    }
-                }
-            }
-            clusters.push(cluster);
-        }
-        clusters
-    }
-}
\ No newline at end of file
+use crate::board::Board;
+use crate::algorithms::{Algorithm, CardinalityEncoding, SolverConfig, SolverResult};
+use crate::algorithms::sat_utils::*;
+use crate::algorithms::cdcl::CdclSolver;
+use crate::algorithms::walksat::WalkSat;
+use crate::algorithms::knowledge::KnowledgeState;
+use crate::error::MinesweeperError;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// partitioned sat solver algorithm 
+pub struct PartitionedSatSolver {
+    _width: usize,
+    _height: usize,
+    _mines: usize,
+    encoding: CardinalityEncoding,
+    global_mine_count: bool,
+    local_search_flips: usize,
+    local_search_seed: u64,
+}
+
+impl PartitionedSatSolver {
+    pub fn new(width: usize, height: usize, mines: usize) -> Self {
+        Self { _width: width, _height: height, _mines: mines, encoding: CardinalityEncoding::default(), global_mine_count: false, local_search_flips: 0, local_search_seed: 0 }
+    }
+}
+
+impl Algorithm for PartitionedSatSolver {
+    fn configure(&mut self, config: &SolverConfig) {
+        self.encoding = config.encoding;
+        self.global_mine_count = config.global_mine_count;
+        self.local_search_flips = config.local_search_flips;
+        self.local_search_seed = config.local_search_seed;
+    }
+
+    fn find_candidates(&mut self, board: &Board) -> SolverResult {
+        self.find_candidates_with(board, &mut KnowledgeState::from_board(board))
+    }
+
+    fn find_candidates_with(&mut self, board: &Board, knowledge: &mut KnowledgeState) -> SolverResult {
+        if knowledge.frontier().is_empty() {
+            return get_probabilistic_fallback(board);
+        }
+
+        // the cnf only uses vars it allocated, an error is a bug in an encoding: prove nothing
+        let safe_cells = self.deduce(board, knowledge).unwrap_or_default();
+        for &c in &safe_cells { knowledge.record_safe(c); }
+
+        if safe_cells.is_empty() {
+            get_probabilistic_fallback(board)
+        } else {
+            SolverResult {
+                candidates: safe_cells,
+                is_guess: false,
+            }
+        }
+    }
+}
+
+impl PartitionedSatSolver {
+    /// cells no model of their cluster can make a mine, plus the interior when the
+    /// clusters must hold every remaining mine
+    fn deduce(&self, board: &Board, knowledge: &mut KnowledgeState) -> Result<Vec<usize>, MinesweeperError> {
+        let mut safe_cells = Vec::new();
+
+        // independent clusters are the connected components of the frontier
+        let components = knowledge.components().to_vec();
+        let mut solved = Vec::with_capacity(components.len());
+        let mut cluster_clues = Vec::with_capacity(components.len());
+        let mut queries = Vec::with_capacity(components.len());
+
+        for component in components {
+            // build localized cnf for this cluster
+            let cluster = component.cells;
+            let mut cluster_clauses = Vec::new();
+            let mut clues = Vec::new();
+            let mut next_var = board.cells.len();
+            for clue_idx in component.clues {
+                let clue = &knowledge.clues()[&clue_idx];
+                add_cardinality(&mut cluster_clauses, &mut next_var, &clue.cells, clue.mines, clue.mines, self.encoding);
+                clues.push((clue.cells.clone(), clue.mines));
+            }
+
+            // unary mine counter of the cluster, only needed for the global count
+            let outputs = if self.global_mine_count {
+                let lits: Vec<isize> = cluster.iter().map(|&c| lit(c)).collect();
+                totalizer(&mut cluster_clauses, &mut next_var, &lits, cluster.len())
+            } else {
+                Vec::new()
+            };
+
+            let mut solver = CdclSolver::from_clauses(next_var, &cluster_clauses)?;
+            let (known_safe, query) = pin_known(&mut solver, &cluster, knowledge)?;
+            safe_cells.extend(known_safe.iter().copied());
+            solved.push((cluster, solver, outputs));
+            cluster_clues.push(clues);
+            queries.push((query, known_safe));
+        }
+
+        let mut ranges = vec![None; solved.len()];
+        if self.global_mine_count {
+            safe_cells.extend(self.apply_global_count(board, knowledge, &mut solved, &mut ranges)?);
+        }
+
+        // test each cell in the cluster against one incremental solver, walksat witnesses first
+        // (clusters are independent, with the parallel feature they run on the thread pool).
+        // walksat sees the whole cluster with the known cells fixed, so the range covers it all
+        let probe = |i: usize, solver: &mut CdclSolver| {
+            let (query, known_safe) = &queries[i];
+            let mut local = (self.local_search_flips > 0).then(|| {
+                let (cells, fixed) = local_search_cells(query, known_safe, knowledge);
+                WalkSat::new(board, &cells, &cluster_clues[i], ranges[i], &fixed, self.local_search_flips, self.local_search_seed)
+            });
+            find_provably_safe(solver, query, local.as_mut())
+        };
+        #[cfg(feature = "parallel")]
+        let found: Vec<Vec<usize>> = solved.par_iter_mut().enumerate().map(|(i, (_, solver, _))| probe(i, solver)).collect::<Result<_, _>>()?;
+        #[cfg(not(feature = "parallel"))]
+        let found: Vec<Vec<usize>> = solved.iter_mut().enumerate().map(|(i, (_, solver, _))| probe(i, solver)).collect::<Result<_, _>>()?;
+        safe_cells.extend(found.into_iter().flatten());
+        Ok(safe_cells)
+    }
+
+    /// clusters only meet through the mine total: every cluster's feasible count range is
+    /// narrowed by what the other clusters and the interior can hold at most / at least.
+    /// returns the interior cells if the clusters must hold all remaining mines.
+    /// `ranges_out` gets every narrowed range, so local search works under the same bounds
+    fn apply_global_count(
+        &self,
+        board: &Board,
+        knowledge: &KnowledgeState,
+        solved: &mut [(Vec<usize>, CdclSolver, Vec<isize>)],
+        ranges_out: &mut [Option<(usize, usize)>],
+    ) -> Result<Vec<usize>, MinesweeperError> {
+        let (remaining, interior) = (knowledge.remaining_mines(board), knowledge.interior());
+        let mut ranges = Vec::with_capacity(solved.len());
+        for (cluster, solver, outputs) in solved.iter_mut() {
+            match count_range(solver, cluster, outputs)? {
+                Some(range) => ranges.push(range),
+                None => return Ok(Vec::new()), // contradicting clues, leave it to the per-cell queries
+            }
+        }
+        let min_total: usize = ranges.iter().map(|r| r.0).sum();
+        let max_total: usize = ranges.iter().map(|r| r.1).sum();
+
+        for (i, (_, solver, outputs)) in solved.iter_mut().enumerate() {
+            let (lo, hi) = ranges[i];
+            let others_min = min_total - lo;
+            let others_max = max_total - hi;
+            let tight_lo = lo.max(remaining.saturating_sub(others_max + interior.len()));
+            let tight_hi = hi.min(remaining.saturating_sub(others_min));
+            if tight_lo > tight_hi { continue; } // inconsistent total, keep the local view
+            if tight_lo > lo { solver.add_clause(&[outputs[tight_lo - 1]])?; }
+            if tight_hi < hi { solver.add_clause(&[-outputs[tight_hi]])?; }
+            ranges_out[i] = Some((tight_lo, tight_hi));
+        }
+
+        // clusters hold at least `remaining` mines -> none left for the interior
+        Ok(if min_total >= remaining { interior } else { Vec::new() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{probability, WasmAlgorithmType};
+    use crate::Simulator;
+
+    /// with the global count, whole games down to the endgame: the deductions are exactly
+    /// the cells no layout puts a mine on (the
+    /// narrowed count ranges of the clusters included)
+    #[test]
+    fn global_count_deductions_match_exact_marginals() {
+        let mut endgames = 0;
+        for (dims, mines) in [(vec![4, 5], 5), (vec![6, 6], 12), (vec![3, 3, 3], 4)] {
+            for seed in 0..20 {
+                let mut sim = Simulator::new(dims.clone(), mines, WasmAlgorithmType::ExactSolver).unwrap();
+                sim.set_seed(seed).unwrap();
+                sim.run_step().unwrap();
+                while !sim.board.game_over {
+                    let mut solver = PartitionedSatSolver::new(0, 0, mines);
+                    solver.configure(&SolverConfig { global_mine_count: true, ..SolverConfig::default() });
+                    let result = solver.find_candidates(&sim.board);
+                    let map = probability::enumerate_probabilities(&sim.board);
+                    let mut safe: Vec<usize> = (0..sim.board.cells.len())
+                        .filter(|&i| !sim.board.cells[i].is_revealed && map.cell_probs[i] < 1e-12)
+                        .collect();
+                    if !safe.is_empty() {
+                        let mut candidates = result.candidates.clone();
+                        candidates.sort_unstable();
+                        candidates.dedup();
+                        safe.sort_unstable();
+                        assert!(!result.is_guess, "{dims:?} seed {seed}: guessed with safe cells left");
+                        assert_eq!(candidates, safe, "{dims:?} seed {seed}");
+                        // only the mine total can clear a cell no clue touches
+                        if safe.iter().any(|c| !map.frontier.contains(c)) { endgames += 1; }
+                    } else {
+                        assert!(result.is_guess, "{dims:?} seed {seed}: deduced a cell that can hold a mine");
+                    }
+                    sim.run_step().unwrap();
+                }
+            }
+        }
+        assert!(endgames > 0);
+    }
+}
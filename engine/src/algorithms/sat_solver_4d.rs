@@ -1,52 +1,47 @@
 use crate::board::Board;
-use crate::algorithms::{Algorithm, SolverResult};
-use std::collections::{BTreeSet, HashMap, HashSet};
+use crate::algorithms::{probability, Algorithm, SolverResult};
+use crate::algorithms::knowledge::KnowledgeState;
+use crate::algorithms::cell_set::CellSet;
+use std::collections::{HashMap, HashSet};
+
+/// the full closure grows exponentially (differences of differences, and the global
+/// constraint minus any union of disjoint ones), so it stops after this many constraints.
+/// this is a cap, not a bound: the closure is only complete while it stays below it
+const MAX_CONSTRAINTS: usize = 1000;
 
 /// 4D-optimized logical SAT-lite solver
 /// Uses constraint reduction without full DPLL for speed
 pub struct SatSolver4D {
-    mines: usize,
+    _mines: usize,
 }
 
 impl SatSolver4D {
     pub fn new(_w: usize, _h: usize, mines: usize) -> Self {
-        Self { mines }
+        Self { _mines: mines }
     }
 
     // [2026-02-09] Helper: Basic flag/mine based deduction
     // Ensures simple patterns are solved without heavy SAT logic (improves 2D/3D win rate)
-    fn perform_simple_deduction(&self, board: &Board) -> Vec<usize> {
+    fn perform_simple_deduction(&self, knowledge: &KnowledgeState) -> Vec<usize> {
         let mut safe_indices = HashSet::new();
-        for idx in 0..board.cells.len() {
-            let cell = &board.cells[idx];
-            if cell.is_revealed && cell.adjacent_mines > 0 {
-                let neighbors = &board.adjacency_map[idx];
-                let mut flags = 0;
-                let mut hidden = Vec::new();
-
-                for &n_idx in neighbors {
-                    if board.cells[n_idx].is_flagged { flags += 1; }
-                    else if !board.cells[n_idx].is_revealed { hidden.push(n_idx); }
-                }
-
-                if (cell.adjacent_mines as usize).saturating_sub(flags) == 0 {
-                    for h_idx in hidden { safe_indices.insert(h_idx); }
-                }
+        for clue in knowledge.clues().values() {
+            if clue.mines == 0 {
+                for &h_idx in &clue.cells { safe_indices.insert(h_idx); }
             }
         }
         safe_indices.into_iter().collect()
     }
 
     // [2026-02-09] Helper: Higher-level subset inference logic
-    fn perform_subset_deduction(&self, board: &Board) -> Vec<usize> {
-        let mut constraints = self.collect_constraints(board);
+    fn perform_subset_deduction(&self, board: &Board, knowledge: &KnowledgeState) -> Vec<usize> {
+        let mut constraints = self.collect_constraints(board, knowledge);
         self.reduce_constraints(&mut constraints);
         self.find_safe(&constraints)
     }
 
     // [2026-02-09] Helper: Core SAT-lite matrix/probabilistic logic
-    fn run_complex_sat_probabilistic(&self, board: &Board) -> SolverResult {
-        let mut constraints = self.collect_constraints(board);
+    fn run_complex_sat_probabilistic(&self, board: &Board, knowledge: &KnowledgeState) -> SolverResult {
+        let mut constraints = self.collect_constraints(board, knowledge);
         self.reduce_constraints(&mut constraints);
 
         let safe_cells = self.find_safe(&constraints);
@@ -54,48 +49,23 @@ impl SatSolver4D {
             return SolverResult { candidates: safe_cells, is_guess: false };
         }
 
-        SolverResult {
-            candidates: self.guess(board, &constraints),
-            is_guess: true,
-        }
+        probability::safest_move(board)
     }
 
     // Collect frontier constraints
-    fn collect_constraints(&self, board: &Board) -> Vec<Constraint> {
-        let mut constraints = Vec::new();
-
-        for (idx, cell) in board.cells.iter().enumerate() {
-            if !cell.is_revealed || cell.adjacent_mines == 0 {
-                continue;
-            }
-
-            let mut hidden = BTreeSet::new();
-            let mut flagged = 0;
-
-            for &n in &board.adjacency_map[idx] {
-                let nc = &board.cells[n];
-                if nc.is_flagged {
-                    flagged += 1;
-                } else if !nc.is_revealed {
-                    hidden.insert(n);
-                }
-            }
-
-            if !hidden.is_empty() {
-                constraints.push(Constraint {
-                    cells: hidden,
-                    mines: (cell.adjacent_mines as usize).saturating_sub(flagged),
-                });
-            }
-        }
+    fn collect_constraints(&self, board: &Board, knowledge: &KnowledgeState) -> Vec<Constraint> {
+        let mut constraints: Vec<Constraint> = knowledge.clues().values()
+            .map(|clue| Constraint {
+                cells: CellSet::from(clue.cells.clone()),
+                mines: clue.mines,
+            })
+            .collect();
 
         // Global constraint: total mines remaining on board
-        let all_hidden: BTreeSet<usize> = board.cells.iter().enumerate()
-            .filter(|(_, c)| !c.is_revealed && !c.is_flagged)
-            .map(|(i, _)| i).collect();
-        
-        let flagged_count = board.cells.iter().filter(|c| c.is_flagged).count();
-        let remaining_total_mines = self.mines.saturating_sub(flagged_count);
+        let all_hidden: CellSet = knowledge.hidden().iter().copied().collect();
+
+        // board count is the ground truth (bernoulli boards draw it per game)
+        let remaining_total_mines = knowledge.remaining_mines(board);
 
         if !all_hidden.is_empty() {
             constraints.push(Constraint {
@@ -108,38 +78,48 @@ impl SatSolver4D {
     }
 
     // Core logic: Subset reduction
+    // the closure of "a subset of b adds b - a" over every constraint, derived ones and the
+    // global one included. each pair is compared once, when the later of the two is added. a
+    // subset of c has its first cell in c and a superset of c holds c's first cell, so only
+    // constraints indexed under those cells are candidates. past `MAX_CONSTRAINTS` only the
+    // clues themselves are still compared, so every pair of clues always is
     fn reduce_constraints(&self, constraints: &mut Vec<Constraint>) {
-        let mut changed = true;
-        while changed {
-            changed = false;
-            
-            constraints.sort_by(|a, b| a.cells.cmp(&b.cells));
-            constraints.dedup();
-    
-            let mut new_constraints = Vec::new();
-            for i in 0..constraints.len() {
-                for j in 0..constraints.len() {
-                    if i == j { continue; }
-                    
-                    if constraints[i].cells.is_subset(&constraints[j].cells) {
-                        let diff: BTreeSet<_> = constraints[j].cells
-                            .difference(&constraints[i].cells)
-                            .cloned().collect();
-                        
-                        if diff.is_empty() { continue; }
-                        
-                        let new_mines = constraints[j].mines.saturating_sub(constraints[i].mines);
-                        let new_c = Constraint { cells: diff, mines: new_mines };
-                        
-                        if !constraints.contains(&new_c) && !new_constraints.contains(&new_c) {
-                            new_constraints.push(new_c);
-                            changed = true;
-                        }
+        let mut seen = HashSet::new();
+        constraints.retain(|c| seen.insert(c.clone()));
+        // constraints per cell they hold, and per their first cell
+        let mut by_cell: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut by_first: HashMap<usize, Vec<usize>> = HashMap::new();
+        let clues = constraints.len();
+        let mut next = 0;
+        while next < constraints.len() && (next < clues || constraints.len() < MAX_CONSTRAINTS) {
+            let c = next;
+            next += 1;
+            let cells = constraints[c].cells.clone();
+            let Some(&first) = cells.as_slice().first() else { continue; };
+
+            let mut derived = Vec::new();
+            // c as the subset
+            for &j in by_cell.get(&first).into_iter().flatten() {
+                let other = &constraints[j];
+                if cells.is_subset(&other.cells) {
+                    derived.push(Constraint { cells: other.cells.difference(&cells), mines: other.mines.saturating_sub(constraints[c].mines) });
+                }
+            }
+            // c as the superset
+            for cell in &cells {
+                for &j in by_first.get(cell).into_iter().flatten() {
+                    let other = &constraints[j];
+                    if other.cells.is_subset(&cells) {
+                        derived.push(Constraint { cells: cells.difference(&other.cells), mines: constraints[c].mines.saturating_sub(other.mines) });
                     }
                 }
             }
-            constraints.extend(new_constraints);
-            if constraints.len() > 200 { break; } // Performance capping
+
+            for &cell in &cells { by_cell.entry(cell).or_default().push(c); }
+            by_first.entry(first).or_default().push(c);
+            for new_c in derived {
+                if !new_c.cells.is_empty() && seen.insert(new_c.clone()) { constraints.push(new_c); }
+            }
         }
     }
 
@@ -154,61 +134,86 @@ impl SatSolver4D {
         }
         safe.into_iter().collect()
     }
-
-    fn guess(&self, board: &Board, constraints: &[Constraint]) -> Vec<usize> {
-        let mut probs: HashMap<usize, f64> = HashMap::new();
-
-        for c in constraints {
-            if c.cells.is_empty() { continue; }
-            let p = c.mines as f64 / c.cells.len() as f64;
-            for &idx in &c.cells {
-                let entry = probs.entry(idx).or_insert(0.0);
-                *entry = entry.max(p);
-            }
-        }
-
-        let mut best = Vec::new();
-        let mut min_p = 1.1;
-
-        for (idx, cell) in board.cells.iter().enumerate() {
-            if cell.is_revealed || cell.is_flagged { continue; }
-
-            let p = *probs.get(&idx).unwrap_or(&0.5);
-
-            if p < min_p - 1e-6 {
-                min_p = p;
-                best = vec![idx];
-            } else if (p - min_p).abs() < 1e-6 {
-                best.push(idx);
-            }
-        }
-        best
-    }
 }
 
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct Constraint {
-    cells: BTreeSet<usize>,
+    cells: CellSet,
     mines: usize,
 }
 
 impl Algorithm for SatSolver4D {
     /// Primary entry point for the SAT solver
     fn find_candidates(&mut self, board: &Board) -> SolverResult {
+        self.find_candidates_with(board, &mut KnowledgeState::from_board(board))
+    }
+
+    fn find_candidates_with(&mut self, board: &Board, knowledge: &mut KnowledgeState) -> SolverResult {
         // [2026-02-09] Layered Inference Strategy:
         // 1. Simple Scan (Flags/Numbers)
-        let simple_safe = self.perform_simple_deduction(board);
+        let simple_safe = self.perform_simple_deduction(knowledge);
         if !simple_safe.is_empty() {
             return SolverResult { candidates: simple_safe, is_guess: false };
         }
 
         // 2. Subset Reduction Logic
-        let subset_safe = self.perform_subset_deduction(board);
+        let subset_safe = self.perform_subset_deduction(board, knowledge);
         if !subset_safe.is_empty() {
             return SolverResult { candidates: subset_safe, is_guess: false };
         }
 
         // 3. Complex SAT/Probabilistic Analysis
-        self.run_complex_sat_probabilistic(board)
+        self.run_complex_sat_probabilistic(board, knowledge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+
+    /// the closure by comparing every pair again until nothing new comes out
+    fn naive_closure(constraints: &[Constraint]) -> HashSet<Constraint> {
+        let mut all: HashSet<Constraint> = constraints.iter().cloned().collect();
+        loop {
+            let current: Vec<Constraint> = all.iter().cloned().collect();
+            let mut grown = false;
+            for a in &current {
+                for b in &current {
+                    if a != b && a.cells.is_subset(&b.cells) {
+                        let derived = Constraint { cells: b.cells.difference(&a.cells), mines: b.mines.saturating_sub(a.mines) };
+                        if !derived.cells.is_empty() && all.insert(derived) { grown = true; }
+                    }
+                }
+            }
+            if !grown { return all; }
+        }
+    }
+
+    #[test]
+    fn indexed_closure_matches_all_pairs() {
+        let mut compared = 0;
+        for (dims, mines) in [(vec![4, 4], 3), (vec![5, 4], 4), (vec![3, 3, 3], 4)] {
+            for seed in 0..10 {
+                let mut sim = Simulator::new(dims.clone(), mines, WasmAlgorithmType::ExactSolver).unwrap();
+                sim.set_seed(seed).unwrap();
+                for _ in 0..6 {
+                    if sim.board.game_over { break; }
+                    let solver = SatSolver4D::new(0, 0, mines);
+                    let knowledge = KnowledgeState::from_board(&sim.board);
+                    let clues = solver.collect_constraints(&sim.board, &knowledge);
+                    let mut indexed = clues.clone();
+                    solver.reduce_constraints(&mut indexed);
+                    if indexed.len() < MAX_CONSTRAINTS {
+                        let indexed: HashSet<Constraint> = indexed.into_iter().collect();
+                        assert!(indexed == naive_closure(&clues), "{dims:?} seed {seed}");
+                        compared += 1;
+                    }
+                    sim.run_step().unwrap();
+                }
+            }
+        }
+        assert!(compared > 20);
     }
-}
\ No newline at end of file
+}
@@ -1,100 +1,501 @@
-use crate::board::Board;
-use std::collections::{HashSet};
-use itertools::Itertools;
-
-/// represents a logical clause in conjunctive normal form (cnf)
-/// e.g. mine count of 2 and 3 hidden neighbors a,b,c: creates a clause
-/// (a or b) and (a or c) and (b or c) -> at least two are mines and 
-// not a or not b or not c -> not all three are mines
-/// positive integers represent a mine at (index - 1), negative represent safe
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub struct Clause(pub Vec<isize>); 
-
-/// dpll algorithm
-/// decides satisfiability of cnf clauses
-pub fn dpll(clauses: Vec<Clause>, mut assignments: HashSet<isize>) -> bool {
-    // if all clauses are removed, the formula is satisfied
-    if clauses.is_empty() { return true; }
-    
-    // if any clause is empty, it means we found a contradiction
-    if clauses.iter().any(|c| c.0.is_empty()) { return false; }
-
-    // if a clause has only one literal, that literal must be true
-    if let Some(unit) = clauses.iter().find(|c| c.0.len() == 1).map(|c| c.0[0]) {
-        assignments.insert(unit);
-        return dpll(simplify(clauses, unit), assignments);
-    }
-
-    // pick a literal and try assigning it true -> mine
-    let literal = clauses[0].0[0];
-    
-    // try assuming the literal is true
-    let mut with_lit = assignments.clone();
-    with_lit.insert(literal);
-    if dpll(simplify(clauses.clone(), literal), with_lit) {
-        return true;
-    }
-
-    // if true failed, try assuming it is false -> safe
-    let mut without_lit = assignments.clone();
-    without_lit.insert(-literal);
-    dpll(simplify(clauses, -literal), without_lit)
-}
-
-/// simplifies the clause set based on a new literal assignment
-pub fn simplify(clauses: Vec<Clause>, literal: isize) -> Vec<Clause> {
-    clauses.into_iter()
-        .filter(|c| !c.0.contains(&literal)) // remove clauses satisfied by this assignment
-        .map(|mut c| {
-            c.0.retain(|&l| l != -literal); // remove the contradiction of the literal from other clauses
-            c
-        })
-        .collect()
-}
-
-/// converts 'exactly k mines' into cnf clauses
-pub fn add_exactly_k_clauses(clauses: &mut Vec<Clause>, vars: &[usize], k: usize) {
-    let n = vars.len();
-
-    // at least k - in any subset of size n - k + 1 at least one must be a mine
-    for combo in vars.iter().combinations(n - k + 1) {
-        clauses.push(Clause(combo.into_iter().map(|&v| (v as isize) + 1).collect()));
-    }
-
-    // at most k - in any subset of size k + 1 at least one must be safe
-    for combo in vars.iter().combinations(k + 1) {
-        clauses.push(Clause(combo.into_iter().map(|&v| -((v as isize) + 1)).collect()));
-    }
-}
-
-/// identifies all hidden cells that touch a revealed number
-pub fn get_frontier(board: &Board) -> Vec<usize> {
-    let mut frontier = HashSet::new();
-    for (idx, cell) in board.cells.iter().enumerate() {
-        if cell.is_revealed && cell.adjacent_mines > 0 {
-            for &n in &board.adjacency_map[idx] {
-                if !board.cells[n].is_revealed && !board.cells[n].is_flagged {
-                    frontier.insert(n);
-                }
-            }
-        }
-    }
-    frontier.into_iter().collect()
-}
-
-/// provides a fallback choice when no logical certainty exists
-/// uses global mine density to pick a likely safe cell
-pub fn get_probabilistic_fallback(board: &Board, _width: usize, _height: usize, mines: usize) -> Vec<usize> {
-    let flag_count = board.cells.iter().filter(|c| c.is_flagged).count();
-    let remaining_cells = board.cells.len().saturating_sub(board.total_revealed);
-    let remaining_mines = mines.saturating_sub(flag_count);
-    
-    // standard density = mines / total_cells
-    let _prob = if remaining_cells > 0 { remaining_mines as f64 / remaining_cells as f64 } else { 1.0 };
-
-    board.cells.iter().enumerate()
-        .filter(|(_, c)| !c.is_revealed && !c.is_flagged)
-        .map(|(i, _)| i)
-        .take(1) 
-        .collect()
-}
\ No newline at end of file
+use crate::board::Board;
+use crate::algorithms::{probability, CardinalityEncoding, SolverResult};
+use crate::algorithms::cdcl::CdclSolver;
+use crate::algorithms::walksat::WalkSat;
+use crate::algorithms::knowledge::KnowledgeState;
+use crate::error::MinesweeperError;
+use std::collections::{HashSet};
+use std::ops::Range;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use itertools::Itertools;
+
+/// represents a logical clause in conjunctive normal form (cnf)
+/// e.g. mine count of 2 and 3 hidden neighbors a,b,c: creates a clause
+/// (a or b) and (a or c) and (b or c) -> at least two are mines and 
+// not a or not b or not c -> not all three are mines
+/// positive integers represent a mine at (index - 1), negative represent safe
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Clause(pub Vec<isize>); 
+
+/// positive literal of a cell / auxiliary variable
+pub fn lit(var: usize) -> isize {
+    (var as isize) + 1
+}
+
+/// converts 'exactly k mines' into cnf clauses
+pub fn add_exactly_k_clauses(clauses: &mut Vec<Clause>, vars: &[usize], k: usize) {
+    let lits: Vec<isize> = vars.iter().map(|&v| lit(v)).collect();
+    add_combinatorial(clauses, &lits, k, k);
+}
+
+/// converts 'between min and max of vars are mines' into cnf with the chosen encoding.
+/// the polynomial encodings allocate auxiliary variables starting at `next_var`
+/// (cells keep their index as variable, so start at board.cells.len())
+pub fn add_cardinality(
+    clauses: &mut Vec<Clause>,
+    next_var: &mut usize,
+    vars: &[usize],
+    min: usize,
+    max: usize,
+    encoding: CardinalityEncoding,
+) {
+    let lits: Vec<isize> = vars.iter().map(|&v| lit(v)).collect();
+    let n = lits.len();
+    let max = max.min(n);
+    if min > max {
+        clauses.push(Clause(Vec::new())); // unsatisfiable
+        return;
+    }
+    if min == 0 && max == n { return; }
+
+    match encoding {
+        CardinalityEncoding::Combinatorial => add_combinatorial(clauses, &lits, min, max),
+        CardinalityEncoding::SequentialCounter => {
+            at_most_sequential(clauses, next_var, &lits, max);
+            // at least min = at most n - min of the negations
+            let negated: Vec<isize> = lits.iter().map(|&l| -l).collect();
+            at_most_sequential(clauses, next_var, &negated, n - min);
+        }
+        CardinalityEncoding::Totalizer => {
+            let outputs = totalizer(clauses, next_var, &lits, max + 1);
+            bound_unary(clauses, &outputs, min, max);
+        }
+        CardinalityEncoding::CardinalityNetwork => {
+            let outputs = sorting_network(clauses, next_var, &lits);
+            bound_unary(clauses, &outputs, min, max);
+        }
+    }
+}
+
+/// naive encoding: every (n - min + 1)-subset holds a mine, every (max + 1)-subset a safe cell
+fn add_combinatorial(clauses: &mut Vec<Clause>, lits: &[isize], min: usize, max: usize) {
+    let n = lits.len();
+
+    // at least min - in any subset of size n - min + 1 at least one must be a mine
+    if min > 0 {
+        for combo in lits.iter().combinations(n - min + 1) {
+            clauses.push(Clause(combo.into_iter().copied().collect()));
+        }
+    }
+
+    // at most max - in any subset of size max + 1 at least one must be safe
+    if max < n {
+        for combo in lits.iter().combinations(max + 1) {
+            clauses.push(Clause(combo.into_iter().map(|&l| -l).collect()));
+        }
+    }
+}
+
+fn fresh(next_var: &mut usize) -> isize {
+    *next_var += 1;
+    *next_var as isize // == lit(next_var - 1)
+}
+
+/// sinz sequential counter: s[i][j] <=> at least j + 1 of lits[0..=i] are true
+fn at_most_sequential(clauses: &mut Vec<Clause>, next_var: &mut usize, lits: &[isize], k: usize) {
+    let n = lits.len();
+    if k >= n { return; }
+    if k == 0 {
+        for &l in lits { clauses.push(Clause(vec![-l])); }
+        return;
+    }
+
+    let s: Vec<Vec<isize>> = (0..n - 1).map(|_| (0..k).map(|_| fresh(next_var)).collect()).collect();
+
+    clauses.push(Clause(vec![-lits[0], s[0][0]]));
+    for &reg in &s[0][1..] {
+        clauses.push(Clause(vec![-reg]));
+    }
+    for i in 1..n - 1 {
+        clauses.push(Clause(vec![-lits[i], s[i][0]]));
+        clauses.push(Clause(vec![-s[i - 1][0], s[i][0]]));
+        for j in 1..k {
+            clauses.push(Clause(vec![-lits[i], -s[i - 1][j - 1], s[i][j]]));
+            clauses.push(Clause(vec![-s[i - 1][j], s[i][j]]));
+        }
+        clauses.push(Clause(vec![-lits[i], -s[i - 1][k - 1]]));
+    }
+    clauses.push(Clause(vec![-lits[n - 1], -s[n - 2][k - 1]]));
+}
+
+/// bailleux-boufkhad totalizer, returns unary outputs (outputs[i] <=> more than i lits are true).
+/// counts are saturated at `cap`, so only min(n, cap) outputs are built
+pub fn totalizer(clauses: &mut Vec<Clause>, next_var: &mut usize, lits: &[isize], cap: usize) -> Vec<isize> {
+    if lits.len() <= 1 { return lits.to_vec(); }
+
+    let (left, right) = lits.split_at(lits.len() / 2);
+    let a = totalizer(clauses, next_var, left, cap);
+    let b = totalizer(clauses, next_var, right, cap);
+    let size = (left.len() + right.len()).min(cap);
+    let r: Vec<isize> = (0..size).map(|_| fresh(next_var)).collect();
+
+    // a_i and b_j => r_(i + j)   (index 0 means "at least 0", always true)
+    for i in 0..=a.len() {
+        for j in 0..=b.len() {
+            if i + j == 0 { continue; }
+            let mut c = Vec::with_capacity(3);
+            if i > 0 { c.push(-a[i - 1]); }
+            if j > 0 { c.push(-b[j - 1]); }
+            c.push(r[(i + j).min(size) - 1]);
+            clauses.push(Clause(c));
+        }
+    }
+    // r_(i + j + 1) => a_(i + 1) or b_(j + 1)
+    for i in 0..=a.len() {
+        for j in 0..=b.len() {
+            if i + j >= size { continue; }
+            let mut c = Vec::with_capacity(3);
+            if i < a.len() { c.push(a[i]); }
+            if j < b.len() { c.push(b[j]); }
+            c.push(-r[i + j]);
+            clauses.push(Clause(c));
+        }
+    }
+    r
+}
+
+/// batcher odd-even merge sorter, returns the lits sorted descending (true first).
+/// each comparator is encoded in both directions so the outputs are exact counts
+fn sorting_network(clauses: &mut Vec<Clause>, next_var: &mut usize, lits: &[isize]) -> Vec<isize> {
+    let n = lits.len();
+    let mut padded = lits.to_vec();
+    if !n.is_power_of_two() {
+        // pad with constant false inputs, they sort to the end
+        let falsum = fresh(next_var);
+        clauses.push(Clause(vec![-falsum]));
+        padded.resize(n.next_power_of_two(), falsum);
+    }
+    let mut sorted = odd_even_sort(clauses, next_var, &padded);
+    sorted.truncate(n);
+    sorted
+}
+
+fn odd_even_sort(clauses: &mut Vec<Clause>, next_var: &mut usize, lits: &[isize]) -> Vec<isize> {
+    if lits.len() <= 1 { return lits.to_vec(); }
+    let (left, right) = lits.split_at(lits.len() / 2);
+    let a = odd_even_sort(clauses, next_var, left);
+    let b = odd_even_sort(clauses, next_var, right);
+    odd_even_merge(clauses, next_var, &a, &b)
+}
+
+fn odd_even_merge(clauses: &mut Vec<Clause>, next_var: &mut usize, a: &[isize], b: &[isize]) -> Vec<isize> {
+    if a.len() == 1 {
+        let (hi, lo) = comparator(clauses, next_var, a[0], b[0]);
+        return vec![hi, lo];
+    }
+    let evens = |x: &[isize]| x.iter().step_by(2).copied().collect::<Vec<_>>();
+    let odds = |x: &[isize]| x.iter().skip(1).step_by(2).copied().collect::<Vec<_>>();
+    let v = odd_even_merge(clauses, next_var, &evens(a), &evens(b));
+    let w = odd_even_merge(clauses, next_var, &odds(a), &odds(b));
+
+    let mut out = Vec::with_capacity(a.len() * 2);
+    out.push(v[0]);
+    for i in 0..w.len() - 1 {
+        let (hi, lo) = comparator(clauses, next_var, w[i], v[i + 1]);
+        out.push(hi);
+        out.push(lo);
+    }
+    out.push(w[w.len() - 1]);
+    out
+}
+
+/// hi <=> x or y, lo <=> x and y
+fn comparator(clauses: &mut Vec<Clause>, next_var: &mut usize, x: isize, y: isize) -> (isize, isize) {
+    let hi = fresh(next_var);
+    let lo = fresh(next_var);
+    clauses.push(Clause(vec![-x, hi]));
+    clauses.push(Clause(vec![-y, hi]));
+    clauses.push(Clause(vec![-x, -y, lo]));
+    clauses.push(Clause(vec![-hi, x, y]));
+    clauses.push(Clause(vec![-lo, x]));
+    clauses.push(Clause(vec![-lo, y]));
+    (hi, lo)
+}
+
+/// restricts unary outputs (outputs[i] <=> count > i) to min..=max
+pub fn bound_unary(clauses: &mut Vec<Clause>, outputs: &[isize], min: usize, max: usize) {
+    if min > outputs.len() || min > max {
+        clauses.push(Clause(Vec::new())); // more mines required than there are inputs
+        return;
+    }
+    if min > 0 { clauses.push(Clause(vec![outputs[min - 1]])); }
+    if max < outputs.len() { clauses.push(Clause(vec![-outputs[max]])); }
+}
+
+/// remaining mine total (minus flags) and the hidden cells that touch no clue
+pub fn global_counts(board: &Board, frontier: &[usize]) -> (usize, Vec<usize>) {
+    let flags = board.cells.iter().filter(|c| c.is_flagged).count();
+    let in_frontier: HashSet<usize> = frontier.iter().copied().collect();
+    let interior = (0..board.cells.len())
+        .filter(|&i| !board.cells[i].is_revealed && !board.cells[i].is_flagged && !in_frontier.contains(&i))
+        .collect();
+    (board.mines.saturating_sub(flags), interior)
+}
+
+/// smallest and largest mine count of `cells` over all models, using unary counter outputs
+/// (outputs[i] <=> more than i of the cells are mines). none if the clauses are unsat
+pub fn count_range(solver: &mut CdclSolver, cells: &[usize], outputs: &[isize]) -> Result<Option<(usize, usize)>, MinesweeperError> {
+    if !solver.solve() { return Ok(None); }
+    let count = |s: &CdclSolver| cells.iter().filter(|&&c| s.model_value(c)).count();
+    let first = count(solver);
+
+    // every model found strictly improves the bound, so both loops stay short
+    let mut hi = first;
+    while hi < outputs.len() && solver.solve_with_assumptions(&[outputs[hi]])? {
+        hi = count(solver);
+    }
+    let mut lo = first;
+    while lo > 0 && solver.solve_with_assumptions(&[-outputs[lo - 1]])? {
+        lo = count(solver);
+    }
+    Ok(Some((lo, hi)))
+}
+
+/// proof by contradiction against one incremental solver: a cell is safe if "cell is a mine"
+/// is unsat. every model found on the way clears all cells it shows as mines, so those
+/// never need their own query. proven safe cells are added as units to help later queries.
+/// with a local search (built over `cells`, same constraints as the solver) a witness layout
+/// is tried first and the solver only gets the cells it fails on.
+/// with the `parallel` feature the cells left after the first model are split into runs, each
+/// probed on its own copy of the solver. a cell is safe or not whatever the split, and the
+/// runs are joined in order, so the result is the same for any number of threads
+pub fn find_provably_safe(solver: &mut CdclSolver, cells: &[usize], local: Option<&mut WalkSat>) -> Result<Vec<usize>, MinesweeperError> {
+    let mut can_be_mine = vec![false; cells.len()];
+
+    // clues contradict each other (should not happen) -> nothing is provable
+    if !solver.solve() { return Ok(Vec::new()); }
+    for (i, &c) in cells.iter().enumerate() {
+        can_be_mine[i] = solver.model_value(c);
+    }
+
+    #[cfg(feature = "parallel")]
+    if let Some(runs) = parallel_runs(&can_be_mine) {
+        let local = local.map(|walk| &*walk);
+        let found: Vec<Vec<usize>> = runs.into_par_iter()
+            .map(|run| {
+                let mut solver = solver.clone();
+                let mut can_be_mine = can_be_mine.clone();
+                let mut local = local.cloned();
+                probe_cells(&mut solver, cells, run, &mut can_be_mine, local.as_mut())
+            })
+            .collect::<Result<_, _>>()?;
+        return Ok(found.into_iter().flatten().collect());
+    }
+
+    probe_cells(solver, cells, 0..cells.len(), &mut can_be_mine, local)
+}
+
+/// probes `cells[run]` one by one, models only clear cells of the same run
+fn probe_cells(solver: &mut CdclSolver, cells: &[usize], run: Range<usize>, can_be_mine: &mut [bool], mut local: Option<&mut WalkSat>) -> Result<Vec<usize>, MinesweeperError> {
+    let mut safe = Vec::new();
+    for i in run.clone() {
+        if can_be_mine[i] { continue; }
+        if let Some(layout) = local.as_mut().and_then(|walk| walk.witness(i)) {
+            for j in i..run.end {
+                if layout[j] { can_be_mine[j] = true; }
+            }
+            continue;
+        }
+        let c = cells[i];
+        if solver.solve_with_assumptions(&[lit(c)])? {
+            for j in i..run.end {
+                if solver.model_value(cells[j]) { can_be_mine[j] = true; }
+            }
+        } else {
+            safe.push(c);
+            solver.add_clause(&[-lit(c)])?;
+        }
+    }
+    Ok(safe)
+}
+
+/// fewest undecided cells per parallel run, below that one solver copy costs more than it saves
+#[cfg(feature = "parallel")]
+pub const MIN_PARALLEL_RUN: usize = 16;
+
+/// contiguous runs of `0..decided.len()` with about the same number of undecided cells, one
+/// per thread. none if there are too few for two runs
+#[cfg(feature = "parallel")]
+pub fn parallel_runs(decided: &[bool]) -> Option<Vec<Range<usize>>> {
+    let pending: Vec<usize> = (0..decided.len()).filter(|&i| !decided[i]).collect();
+    let per_run = pending.len().div_ceil(rayon::current_num_threads()).max(MIN_PARALLEL_RUN);
+    if pending.len() < 2 * MIN_PARALLEL_RUN || rayon::current_num_threads() < 2 { return None; }
+    let starts: Vec<usize> = pending.iter().step_by(per_run).copied().collect();
+    Some((0..starts.len())
+        .map(|k| starts[k]..starts.get(k + 1).copied().unwrap_or(decided.len()))
+        .collect())
+}
+
+/// cells of `cells` proven on earlier moves become units instead of queries again.
+/// returns (known safe, cells left to query)
+pub fn pin_known(solver: &mut CdclSolver, cells: &[usize], knowledge: &KnowledgeState) -> Result<(Vec<usize>, Vec<usize>), MinesweeperError> {
+    let mut known_safe = Vec::new();
+    let mut query = Vec::with_capacity(cells.len());
+    for &c in cells {
+        if knowledge.known_safe().contains(&c) {
+            solver.add_clause(&[-lit(c)])?;
+            known_safe.push(c);
+        } else {
+            if knowledge.known_mines().contains(&c) { solver.add_clause(&[lit(c)])?; }
+            query.push(c);
+        }
+    }
+    Ok((known_safe, query))
+}
+
+/// the cells `pin_known` split, back in one list for local search: the query first (so witness
+/// positions line up with `find_provably_safe`), then the known safe cells. known safe cells
+/// and known mines come with their fixed values
+pub fn local_search_cells(query: &[usize], known_safe: &[usize], knowledge: &KnowledgeState) -> (Vec<usize>, Vec<(usize, bool)>) {
+    let cells: Vec<usize> = query.iter().chain(known_safe).copied().collect();
+    let fixed = known_safe.iter().map(|&c| (c, false))
+        .chain(query.iter().filter(|c| knowledge.known_mines().contains(c)).map(|&c| (c, true)))
+        .collect();
+    (cells, fixed)
+}
+
+/// identifies all hidden cells that touch a revealed number
+pub fn get_frontier(board: &Board) -> Vec<usize> {
+    let mut frontier = HashSet::new();
+    for (idx, cell) in board.cells.iter().enumerate() {
+        if cell.is_revealed && cell.adjacent_mines > 0 {
+            for &n in &board.adjacency_map[idx] {
+                if !board.cells[n].is_revealed && !board.cells[n].is_flagged {
+                    frontier.insert(n);
+                }
+            }
+        }
+    }
+    frontier.into_iter().collect()
+}
+
+/// provides a fallback choice when no logical certainty exists
+/// picks the cells with the lowest exact mine probability (see algorithms::probability)
+pub fn get_probabilistic_fallback(board: &Board) -> SolverResult {
+    probability::safest_move(board)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODINGS: [CardinalityEncoding; 4] = [
+        CardinalityEncoding::Combinatorial,
+        CardinalityEncoding::SequentialCounter,
+        CardinalityEncoding::Totalizer,
+        CardinalityEncoding::CardinalityNetwork,
+    ];
+
+    /// the n inputs fixed to the bits of `bits`, as assumptions
+    fn fixed(n: usize, bits: u32) -> Vec<isize> {
+        (0..n).map(|i| if (bits >> i) & 1 == 1 { lit(i) } else { -lit(i) }).collect()
+    }
+
+    #[test]
+    fn encodings_accept_exactly_the_counts_in_range() {
+        for n in 0..=6 {
+            let vars: Vec<usize> = (0..n).collect();
+            for min in 0..=n + 1 {
+                for max in 0..=n + 1 {
+                    for encoding in ENCODINGS {
+                        let mut clauses = Vec::new();
+                        let mut next_var = n;
+                        add_cardinality(&mut clauses, &mut next_var, &vars, min, max, encoding);
+                        let mut solver = CdclSolver::from_clauses(next_var, &clauses).unwrap();
+                        for bits in 0..1u32 << n {
+                            let count = bits.count_ones() as usize;
+                            let expected = min <= count && count <= max;
+                            assert_eq!(solver.solve_with_assumptions(&fixed(n, bits)).unwrap(), expected,
+                                "{encoding:?} n={n} min={min} max={max} bits={bits:b}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// every output is forced to (count > i) once the inputs are fixed
+    fn assert_counts(n: usize, next_var: usize, clauses: &[Clause], outputs: &[isize]) {
+        let mut solver = CdclSolver::from_clauses(next_var, clauses).unwrap();
+        for bits in 0..1u32 << n {
+            let count = bits.count_ones() as usize;
+            let inputs = fixed(n, bits);
+            assert!(solver.solve_with_assumptions(&inputs).unwrap());
+            for (i, &out) in outputs.iter().enumerate() {
+                let mut wrong = inputs.clone();
+                wrong.push(if count > i { -out } else { out });
+                assert!(!solver.solve_with_assumptions(&wrong).unwrap(), "n={n} bits={bits:b} output {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn totalizer_outputs_are_unary_counts() {
+        for n in 1..=7 {
+            for cap in 1..=n + 1 {
+                let lits: Vec<isize> = (0..n).map(lit).collect();
+                let mut clauses = Vec::new();
+                let mut next_var = n;
+                let outputs = totalizer(&mut clauses, &mut next_var, &lits, cap);
+                assert_eq!(outputs.len(), if n == 1 { 1 } else { n.min(cap) });
+                assert_counts(n, next_var, &clauses, &outputs);
+            }
+        }
+    }
+
+    #[test]
+    fn sorting_network_outputs_are_unary_counts() {
+        for n in 1..=7 {
+            let lits: Vec<isize> = (0..n).map(lit).collect();
+            let mut clauses = Vec::new();
+            let mut next_var = n;
+            let outputs = sorting_network(&mut clauses, &mut next_var, &lits);
+            assert_eq!(outputs.len(), n);
+            assert_counts(n, next_var, &clauses, &outputs);
+        }
+    }
+
+    /// the probes give the same candidates on one thread as on several, for both sat solvers
+    /// and scip, with and without local search witnesses
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_probes_match_a_single_thread() {
+        use crate::algorithms::{Algorithm, SolverConfig, WasmAlgorithmType};
+        use crate::algorithms::sat_global::GlobalSatSolver;
+        use crate::algorithms::sat_partitioned::PartitionedSatSolver;
+        use crate::algorithms::scip_solver::SCIPSolver;
+        use crate::Simulator;
+        use rayon::ThreadPoolBuilder;
+
+        let single = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        // not the default size: a one-core machine would compare one thread with itself
+        let several = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        let mut split = 0;
+        for seed in 0..3 {
+            let mut sim = Simulator::new(vec![16, 30], 99, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            for _ in 0..30 {
+                if !sim.run_step().unwrap() { break; }
+                if KnowledgeState::from_board(&sim.board).frontier().len() >= 2 * MIN_PARALLEL_RUN { split += 1; }
+                for flips in [0, 200] {
+                    let config = SolverConfig { global_mine_count: true, local_search_flips: flips, ..SolverConfig::default() };
+                    let solvers: [fn() -> Box<dyn Algorithm>; 3] = [
+                        || Box::new(GlobalSatSolver::new(30, 16, 99)),
+                        || Box::new(PartitionedSatSolver::new(30, 16, 99)),
+                        || Box::new(SCIPSolver::new(30, 16, 99)),
+                    ];
+                    for make in solvers {
+                        let run = |pool: &rayon::ThreadPool| pool.install(|| {
+                            let mut solver = make();
+                            solver.configure(&config);
+                            let result = solver.find_candidates(&sim.board);
+                            (result.candidates, result.is_guess)
+                        });
+                        assert_eq!(run(&single), run(&several), "seed {seed} flips {flips}");
+                    }
+                }
+            }
+        }
+        assert!(split > 0);
+    }
+}
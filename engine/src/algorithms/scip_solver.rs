@@ -1,49 +1,87 @@
 // src/algorithms/scip_solver.rs
 use crate::board::Board;
-use crate::algorithms::{Algorithm, SolverResult};
+use crate::algorithms::{probability, Algorithm, SolverConfig, SolverResult};
+use crate::algorithms::probability::{ComponentCounts, LogBinomial, ProbabilityMap};
+use crate::algorithms::sat_utils::global_counts;
+use crate::algorithms::walksat::WalkSat;
+#[cfg(feature = "parallel")]
+use crate::algorithms::sat_utils::parallel_runs;
 use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
+use russcip::ffi;
 use russcip::model::{ProblemCreated, Model};
 use russcip::prelude::*;
+use russcip::scip_call_panic;
 use russcip::variable::Variable;
 
+/// layouts collected per frontier mine total before the shared probability engine takes over
+const MAX_COLLECTED: i64 = 200_000;
+/// branch-and-bound nodes per SCIPcount, same fallback past it
+const MAX_COUNT_NODES: i64 = 100_000;
+
+/// how `count_probabilities` gets the per-cell counts of a mine total
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CountMethod {
+    /// one SCIPcount per total and one more per cell with the cell fixed to a mine
+    PerCell,
+    /// one SCIPcount per total, per-cell counts read from the collected sparse solutions
+    #[default]
+    Collected,
+}
+
 #[allow(dead_code)]
 pub struct SCIPSolver {
     width: usize,
     height: usize,
     mines: usize,
+    global_mine_count: bool,
+    local_search_flips: usize,
+    local_search_seed: u64,
 }
 
 impl SCIPSolver {
     pub fn new(width: usize, height: usize, mines: usize) -> Self {
-        Self { width, height, mines }
+        Self { width, height, mines, global_mine_count: false, local_search_flips: 0, local_search_seed: 0 }
     }
 
     fn solve_exact(&self, board: &Board) -> SolverResult {
-        let mut constraints: Vec<Constraint> = Vec::new();
-
-        // 1) collect constraints from revealed numbered cells
-        for idx in 0..board.cells.len() {
-            let cell = &board.cells[idx];
-
-            if cell.is_revealed && cell.adjacent_mines > 0 {
-                let c = self.build_constraint(board, idx);
-                if !c.hidden_cells.is_empty() {
-                    constraints.push(c);
-                }
-            }
-        }
+        let constraints = collect_constraints(board);
 
         // if nothing constrained, fall back to global-best candidates (flagged as guess)
         if constraints.is_empty() {
-            return SolverResult {
-                candidates: self.get_best_probability_candidates(board),
-                is_guess: true,
-            };
+            return probability::safest_move(board);
         }
 
-        // 2) ilp-based "definitely safe"
-        let safe = self.find_all_definitely_safe_via_scip(&constraints, board);
+        // optional global count: (remaining mines, interior cells as one integer aggregate)
+        let frontier = frontier_of(&constraints);
+        let (remaining, interior) = global_counts(board, &frontier);
+        let mut model = MoveModel::new(&constraints, remaining, interior.len(), self.global_mine_count);
+
+        // walksat witnesses over the same constraints save most of the probes
+        let mut local = (self.local_search_flips > 0).then(|| {
+            let clues: Vec<(Vec<usize>, usize)> = constraints.iter().map(|c| (c.hidden_cells.clone(), c.remaining_mines())).collect();
+            let total = self.global_mine_count.then(|| (remaining.saturating_sub(interior.len()), remaining));
+            WalkSat::new(board, &model.cells, &clues, total, &[], self.local_search_flips, self.local_search_seed)
+        });
+
+        // 1) ilp-based "definitely safe", one model for every probe
+        let endgame = self.global_mine_count && !interior.is_empty();
+        let Some((possible_mine, interior_mine)) = model.find_safe(endgame, local.as_mut()) else {
+            // contradictory clues: leave it to the probability engine
+            return probability::safest_move(board);
+        };
+        let mut safe: Vec<usize> = model.cells.iter().zip(&possible_mine)
+            .filter(|&(_, &mine)| !mine)
+            .map(|(&idx, _)| idx)
+            .collect();
+
+        // endgame: the interior can't hold a single mine -> all of it is safe
+        if endgame && !interior_mine {
+            safe.extend(interior);
+        }
         if !safe.is_empty() {
             return SolverResult {
                 candidates: safe,
@@ -51,56 +89,145 @@ impl SCIPSolver {
             };
         }
 
-        // 3) fallback to probabilistic if no logic applies
-        SolverResult {
-            candidates: self.get_best_probability_candidates(board),
-            is_guess: true,
+        // 2) no logic applies: safest cell by counted marginals
+        match model.count_probabilities(board, remaining, CountMethod::Collected) {
+            Some(map) => {
+                let candidates = map.safest_cells(board);
+                let proven = map.proven_safe(&candidates);
+                if proven.is_empty() {
+                    SolverResult { candidates, is_guess: true }
+                } else {
+                    SolverResult { candidates: proven, is_guess: false }
+                }
+            }
+            None => probability::safest_move(board),
         }
     }
 
-    /// build a constraint from a revealed numbered cell:
-    /// sum_{hidden neighbors} x_j == (adjacent_mines - flagged_neighbors)
-    fn build_constraint(&self, board: &Board, idx: usize) -> Constraint {
-        let cell = &board.cells[idx];
-        let mut hidden = Vec::new();
-        let mut flags = 0usize;
+    /// the counted marginals the guess step uses (global count on), by either method. none
+    /// where the solver falls back to the shared probability engine
+    pub fn counted_probabilities(board: &Board, method: CountMethod) -> Option<ProbabilityMap> {
+        let constraints = collect_constraints(board);
+        if constraints.is_empty() { return None; }
+        let frontier = frontier_of(&constraints);
+        let (remaining, interior) = global_counts(board, &frontier);
+        let mut model = MoveModel::new(&constraints, remaining, interior.len(), true);
+        model.count_probabilities(board, remaining, method)
+    }
 
-        for &n_idx in &board.adjacency_map[idx] {
-            let nb = &board.cells[n_idx];
-            if nb.is_flagged {
-                flags += 1;
-            } else if !nb.is_revealed {
-                hidden.push(n_idx);
-            }
+    /// frontier cells that are mines in the most likely frontier layout, none if the clues
+    /// and the mine count contradict each other.
+    /// every full layout is equally likely, so a frontier layout with m mines stands for
+    /// C(interior, remaining - m) of them. ln C is concave in the interior count y, so a
+    /// variable t kept below every chord of it is exact at integer y, and maximising t is a
+    /// linear objective
+    pub fn most_likely_layout(board: &Board) -> Option<Vec<usize>> {
+        let constraints = collect_constraints(board);
+        let frontier = frontier_of(&constraints);
+        let (remaining, interior) = global_counts(board, &frontier);
+        let mut model = MoveModel::new(&constraints, remaining, interior.len(), true);
+        model.maximize_layout_weight(remaining, interior.len());
+        match model.resolve() {
+            Resolve::Layout { mines, .. } => Some(mines.into_iter().map(|i| model.cells[i]).collect()),
+            Resolve::Infeasible | Resolve::Unknown => None,
         }
+    }
+}
 
-        Constraint {
-            total_mines: cell.adjacent_mines as usize,
-            flagged: flags,
-            hidden_cells: hidden,
+/// a count that ran to the end: the search proved it saw every solution
+fn is_complete(status: ffi::SCIP_Status) -> bool {
+    matches!(status, ffi::SCIP_Status_SCIP_STATUS_OPTIMAL | ffi::SCIP_Status_SCIP_STATUS_INFEASIBLE)
+}
+
+/// constraints from revealed numbered cells with hidden neighbours
+fn collect_constraints(board: &Board) -> Vec<Constraint> {
+    (0..board.cells.len())
+        .filter(|&idx| board.cells[idx].is_revealed && board.cells[idx].adjacent_mines > 0)
+        .map(|idx| build_constraint(board, idx))
+        .filter(|c| !c.hidden_cells.is_empty())
+        .collect()
+}
+
+/// build a constraint from a revealed numbered cell:
+/// sum_{hidden neighbors} x_j == (adjacent_mines - flagged_neighbors)
+fn build_constraint(board: &Board, idx: usize) -> Constraint {
+    let cell = &board.cells[idx];
+    let mut hidden = Vec::new();
+    let mut flags = 0usize;
+
+    for &n_idx in &board.adjacency_map[idx] {
+        let nb = &board.cells[n_idx];
+        if nb.is_flagged {
+            flags += 1;
+        } else if !nb.is_revealed {
+            hidden.push(n_idx);
         }
     }
 
-    /// base ilp model for current frontier:
-    /// - binary var x_i for each hidden frontier cell i
-    /// - for each constraint: sum x_i == remaining_mines
-    fn build_base_model(
-        &self,
-        constraints: &[Constraint],
-    ) -> (Model<ProblemCreated>, HashMap<usize, Variable>) {
-        let mut frontier: HashSet<usize> = HashSet::new();
-        for c in constraints {
-            frontier.extend(c.hidden_cells.iter().copied());
-        }
+    Constraint {
+        total_mines: cell.adjacent_mines as usize,
+        flagged: flags,
+        hidden_cells: hidden,
+    }
+}
+
+/// hidden cells of the constraints, sorted
+fn frontier_of(constraints: &[Constraint]) -> Vec<usize> {
+    let mut frontier: Vec<usize> = constraints.iter()
+        .flat_map(|c| c.hidden_cells.iter().copied())
+        .collect::<HashSet<usize>>()
+        .into_iter()
+        .collect();
+    frontier.sort_unstable();
+    frontier
+}
+
+/// marks the mines of a layout (and whether it puts one in the interior)
+fn record(outcome: &Resolve, possible_mine: &mut [bool], interior_mine: &mut bool) {
+    if let Resolve::Layout { mines, interior } = outcome {
+        for &i in mines { possible_mine[i] = true; }
+        *interior_mine |= *interior >= 1.0;
+    }
+}
 
+/// outcome of one solve of the move model
+enum Resolve {
+    Infeasible,
+    /// `mines` are positions in `MoveModel::cells`, `interior` the value of y
+    Layout { mines: Vec<usize>, interior: f64 },
+    /// a limit was hit before SCIP decided
+    Unknown,
+}
+
+/// ilp of one move, built once and solved again under different variable bounds:
+/// - binary var x_i for each hidden frontier cell i
+/// - for each constraint: sum x_i == remaining_mines
+/// - integer y for the interior and sum x_i + y == remaining total. with the global count
+///   y is in [0, interior], otherwise its bounds are wide enough that the total never binds
+struct MoveModel {
+    /// none only while SCIP has it
+    model: Option<Model<ProblemCreated>>,
+    cells: Vec<usize>,
+    vars: Vec<Variable>,
+    interior: Variable,
+    interior_bounds: (f64, f64),
+    /// what `new` got, so copies can be built for parallel probes
+    #[cfg(feature = "parallel")]
+    spec: (Vec<Constraint>, usize, usize, bool),
+}
+
+impl MoveModel {
+    fn new(constraints: &[Constraint], remaining: usize, interior: usize, global: bool) -> Self {
+        let cells = frontier_of(constraints);
         let mut model = Model::default().minimize();
 
         // vars: x_idx in {0,1}
-        let mut xvars: HashMap<usize, Variable> = HashMap::new();
-        for idx in frontier {
+        let mut position = HashMap::new();
+        let mut vars = Vec::with_capacity(cells.len());
+        for (i, &idx) in cells.iter().enumerate() {
             let name = format!("x_{}", idx);
-            let v = model.add(var().bin().obj(0.0).name(&name));
-            xvars.insert(idx, v);
+            vars.push(model.add(var().bin().obj(0.0).name(&name)));
+            position.insert(idx, i);
         }
 
         // constraints: sum x == remaining
@@ -109,95 +236,298 @@ impl SCIPSolver {
             let cname = format!("c_{}", i);
 
             let mut lin = cons().name(&cname).eq(rhs);
-            for &hid in &c.hidden_cells {
-                if let Some(v) = xvars.get(&hid) {
-                    lin = lin.coef(v, 1.0);
-                }
+            for hid in &c.hidden_cells {
+                lin = lin.coef(&vars[position[hid]], 1.0);
             }
             model.add(lin);
         }
 
-        (model, xvars)
-    }
-
-    /// check feasibility when x_cell is fixed to value (0 or 1)
-    fn feasible_with_fix(&self, constraints: &[Constraint], cell_idx: usize, value: i32) -> bool {
-        let (mut model, xvars) = self.build_base_model(constraints);
-
-        // if not in frontier, it's unconstrained by current knowledge → feasible
-        let Some(v) = xvars.get(&cell_idx) else {
-            return true;
+        let (low, high) = if global {
+            (0, interior as isize)
+        } else {
+            (remaining as isize - cells.len() as isize, remaining as isize)
         };
-
-        model.add(cons().name("fix").coef(v, 1.0).eq(value as f64));
+        let y = model.add(var().int(low..=high).obj(0.0).name("interior"));
+        let mut total = cons().name("mine_total").eq(remaining as f64).coef(&y, 1.0);
+        for v in &vars {
+            total = total.coef(v, 1.0);
+        }
+        model.add(total);
 
         // quiet output
         model = model.set_param("display/verblevel", 0);
 
-        let solved = model.solve();
-        !matches!(solved.status(), Status::Infeasible)
+        Self {
+            model: Some(model),
+            cells,
+            vars,
+            interior: y,
+            interior_bounds: (low as f64, high as f64),
+            #[cfg(feature = "parallel")]
+            spec: (constraints.to_vec(), remaining, interior, global),
+        }
     }
 
-    /// return all indices that are guaranteed safe (x=1 infeasible)
-    fn find_all_definitely_safe_via_scip(
-        &self,
-        constraints: &[Constraint],
-        board: &Board,
-    ) -> Vec<usize> {
-        let mut frontier: HashSet<usize> = HashSet::new();
-        for c in constraints {
-            frontier.extend(c.hidden_cells.iter().copied());
+    fn scip(&self) -> *mut ffi::SCIP {
+        self.model.as_ref().expect("model is back after every solve").scip_ptr()
+    }
+
+    fn set_int_param(&mut self, name: &str, value: i32) {
+        self.model = self.model.take().map(|model| model.set_param(name, value));
+    }
+
+    fn set_bool_param(&mut self, name: &str, value: bool) {
+        self.model = self.model.take().map(|model| model.set_param(name, value));
+    }
+
+    fn set_long_param(&mut self, name: &str, value: i64) {
+        self.model = self.model.take().map(|model| model.set_param(name, value));
+    }
+
+    /// changes the problem bounds of `var`; lb never passes ub in between
+    fn set_bounds(&self, var: &Variable, lb: f64, ub: f64) {
+        let scip = self.scip();
+        if lb > var.ub() {
+            scip_call_panic!(ffi::SCIPchgVarUb(scip, var.inner(), ub));
+            scip_call_panic!(ffi::SCIPchgVarLb(scip, var.inner(), lb));
+        } else {
+            scip_call_panic!(ffi::SCIPchgVarLb(scip, var.inner(), lb));
+            scip_call_panic!(ffi::SCIPchgVarUb(scip, var.inner(), ub));
         }
+    }
+
+    /// solves under the current bounds and frees the transformed problem for the next change
+    fn resolve(&mut self) -> Resolve {
+        let solved = self.model.take().expect("model is back after every solve").solve();
+        let outcome = match (solved.status(), solved.best_sol()) {
+            (Status::Infeasible, _) => Resolve::Infeasible,
+            (_, Some(sol)) => Resolve::Layout {
+                mines: (0..self.vars.len()).filter(|&i| sol.val(&self.vars[i]) > 0.5).collect(),
+                interior: sol.val(&self.interior).round(),
+            },
+            _ => Resolve::Unknown,
+        };
+        self.model = Some(solved.free_transform());
+        outcome
+    }
 
-        let mut safe = Vec::new();
+    /// counts the layouts under the current bounds in one SCIPcount and reads the collected
+    /// sparse solutions: (layouts, layouts with each cell a mine). a sparse solution is a box
+    /// of lb..=ub per counted variable, every point of which is a layout. none if the count hit
+    /// a limit, overflowed, or the boxes don't add up to SCIP's own count
+    fn count_collected(&mut self) -> Option<(f64, Vec<f64>)> {
+        let scip = self.scip();
+        scip_call_panic!(ffi::SCIPcount(scip));
+        let result = unsafe { self.read_sparse_sols(scip) };
+        scip_call_panic!(ffi::SCIPfreeTransform(scip));
+        result
+    }
 
-        for idx in frontier {
-            let cell = &board.cells[idx];
-            if cell.is_revealed || cell.is_flagged {
-                continue;
+    /// number of layouts under the current bounds, none if the count is partial or overflows
+    fn count(&mut self) -> Option<f64> {
+        let scip = self.scip();
+        scip_call_panic!(ffi::SCIPcount(scip));
+        let result = unsafe {
+            let mut valid = 0;
+            let n = ffi::SCIPgetNCountedSols(scip, &mut valid);
+            (is_complete(ffi::SCIPgetStatus(scip)) && valid != 0).then_some(n as f64)
+        };
+        scip_call_panic!(ffi::SCIPfreeTransform(scip));
+        result
+    }
+
+    /// # Safety
+    /// `scip` is this model's instance, counted with collection on and not yet freed
+    unsafe fn read_sparse_sols(&self, scip: *mut ffi::SCIP) -> Option<(f64, Vec<f64>)> {
+        // only a finished search counted everything; any limit or interrupt leaves it partial.
+        // matched by name, the numeric order of the statuses differs between scip versions
+        if !is_complete(ffi::SCIPgetStatus(scip)) { return None; }
+        let mut valid = 0;
+        let total = ffi::SCIPgetNCountedSols(scip, &mut valid);
+        if valid == 0 { return None; }
+
+        let (mut vars, mut nvars) = (std::ptr::null_mut(), 0);
+        let (mut sols, mut nsols) = (std::ptr::null_mut(), 0);
+        ffi::SCIPgetCountedSparseSols(scip, &mut vars, &mut nvars, &mut sols, &mut nsols);
+        let counted: HashMap<*mut ffi::SCIP_VAR, usize> = (0..nvars as usize).map(|k| (*vars.add(k), k)).collect();
+
+        // each cell's transformed variable: its slot in the boxes, or the value SCIP fixed it to
+        enum Slot { Counted(usize), Fixed(bool) }
+        let mut slots = Vec::with_capacity(self.vars.len());
+        for v in &self.vars {
+            let mut t = std::ptr::null_mut();
+            scip_call_panic!(ffi::SCIPgetTransformedVar(scip, v.inner(), &mut t));
+            match counted.get(&t) {
+                Some(&k) => slots.push(Slot::Counted(k)),
+                None if ffi::SCIPvarGetLbGlobal(t) == ffi::SCIPvarGetUbGlobal(t) => slots.push(Slot::Fixed(ffi::SCIPvarGetLbGlobal(t) > 0.5)),
+                None => return None,
             }
+        }
 
-            // if "idx is a mine" is impossible → idx is definitely safe
-            let feasible_if_mine = self.feasible_with_fix(constraints, idx, 1);
-            if !feasible_if_mine {
-                safe.push(idx);
+        let mut layouts = 0.0;
+        let mut mines = vec![0.0; self.vars.len()];
+        for s in 0..nsols as usize {
+            let sol = *sols.add(s);
+            let lbs = std::slice::from_raw_parts(ffi::SCIPsparseSolGetLbs(sol), nvars as usize);
+            let ubs = std::slice::from_raw_parts(ffi::SCIPsparseSolGetUbs(sol), nvars as usize);
+            let size: f64 = lbs.iter().zip(ubs).map(|(&lb, &ub)| (ub - lb + 1) as f64).product();
+            layouts += size;
+            for (i, slot) in slots.iter().enumerate() {
+                // share of the box with x_i = 1
+                let share = match *slot {
+                    Slot::Fixed(mine) => f64::from(u8::from(mine)),
+                    Slot::Counted(k) => (ubs[k] - lbs[k].max(1) + 1).max(0) as f64 / (ubs[k] - lbs[k] + 1) as f64,
+                };
+                mines[i] += size * share;
             }
         }
+        ((layouts - total as f64).abs() < 0.5).then_some((layouts, mines))
+    }
 
-        safe
+    /// which frontier cells can be mines and (if `check_interior`) whether the interior can hold
+    /// one, none if the model is infeasible. a cell is probed (x fixed to 1) only if no layout
+    /// found so far has a mine there, so most cells never get a solve of their own.
+    /// a local search witness, if there is one, stands in for the probe.
+    /// with the `parallel` feature the undecided cells are split into runs, each probed on its
+    /// own copy of the model (SCIP instances are not shared between threads). the runs are
+    /// merged, so the answer does not depend on the number of threads
+    fn find_safe(&mut self, check_interior: bool, local: Option<&mut WalkSat>) -> Option<(Vec<bool>, bool)> {
+        // any layout will do: stop at the first one
+        self.set_int_param("limits/solutions", 1);
+
+        let mut possible_mine = vec![false; self.cells.len()];
+        let mut interior_mine = false;
+
+        let first = self.resolve();
+        if matches!(first, Resolve::Infeasible) { return None; }
+        record(&first, &mut possible_mine, &mut interior_mine);
+
+        #[cfg(feature = "parallel")]
+        if let Some(runs) = parallel_runs(&possible_mine) {
+            let (constraints, remaining, interior, global) = &self.spec;
+            let (start, local) = (&possible_mine, local.map(|walk| &*walk));
+            let found: Vec<(Vec<bool>, bool)> = runs.into_par_iter()
+                .map(|run| {
+                    let mut copy = MoveModel::new(constraints, *remaining, *interior, *global);
+                    copy.set_int_param("limits/solutions", 1);
+                    let mut possible_mine = start.clone();
+                    let mut interior_mine = false;
+                    let mut local = local.cloned();
+                    copy.probe(run, &mut possible_mine, &mut interior_mine, local.as_mut());
+                    (possible_mine, interior_mine)
+                })
+                .collect();
+            for (possible, interior) in found {
+                for (mine, p) in possible_mine.iter_mut().zip(possible) { *mine |= p; }
+                interior_mine |= interior;
+            }
+        } else {
+            self.probe(0..self.cells.len(), &mut possible_mine, &mut interior_mine, local);
+        }
+        #[cfg(not(feature = "parallel"))]
+        self.probe(0..self.cells.len(), &mut possible_mine, &mut interior_mine, local);
+
+        if check_interior && !interior_mine {
+            let (low, high) = self.interior_bounds;
+            self.set_bounds(&self.interior, low.max(1.0), high);
+            let outcome = self.resolve();
+            self.set_bounds(&self.interior, low, high);
+            interior_mine = !matches!(outcome, Resolve::Infeasible);
+        }
+        Some((possible_mine, interior_mine))
     }
 
-    /// same fallback you already use in 3d: global baseline probability
-    fn get_best_probability_candidates(&self, board: &Board) -> Vec<usize> {
-        let flag_count = board.cells.iter().filter(|c| c.is_flagged).count();
-        // subtracting flags from total unrevealed cells for accurate density
-        let remaining_cells = board.cells.len()
-            .saturating_sub(board.total_revealed)
-            .saturating_sub(flag_count);
-        let remaining_mines = self.mines.saturating_sub(flag_count);
+    /// probes the undecided cells of `run`, every layout found marks its mines
+    fn probe(&mut self, run: Range<usize>, possible_mine: &mut [bool], interior_mine: &mut bool, mut local: Option<&mut WalkSat>) {
+        for i in run {
+            if possible_mine[i] { continue; }
+            if let Some(layout) = local.as_mut().and_then(|walk| walk.witness(i)) {
+                for (j, mine) in possible_mine.iter_mut().enumerate() { *mine |= layout[j]; }
+                continue;
+            }
+            self.set_bounds(&self.vars[i], 1.0, 1.0);
+            let outcome = self.resolve();
+            self.set_bounds(&self.vars[i], 0.0, 1.0);
+            match outcome {
+                Resolve::Infeasible => {}
+                // undecided counts as a possible mine, never as safe
+                Resolve::Unknown => possible_mine[i] = true,
+                layout => record(&layout, possible_mine, interior_mine),
+            }
+        }
+    }
 
-        let global_prob = if remaining_cells > 0 {
-            remaining_mines as f64 / remaining_cells as f64
-        } else {
-            1.0
-        };
+    /// exact marginals by model counting: for every frontier mine total m (y fixed to
+    /// remaining - m) one SCIPcount collects the frontier layouts, and the collected solutions
+    /// give the ones where each cell is a mine. `probability::combine` weights the totals with
+    /// the interior binomials, so the map matches the shared engine's. `CountMethod::PerCell`
+    /// counts again with each cell fixed to a mine instead (the older, slower way).
+    /// none past `MAX_COLLECTED` layouts or `MAX_COUNT_NODES` nodes for one count
+    fn count_probabilities(&mut self, board: &Board, remaining: usize, method: CountMethod) -> Option<ProbabilityMap> {
+        self.set_int_param("limits/solutions", -1);
+        scip_call_panic!(ffi::SCIPsetParamsCountsols(self.scip()));
+        self.set_long_param("limits/nodes", MAX_COUNT_NODES);
+        if method == CountMethod::Collected {
+            // collected solutions are over transformed variables, without presolving those are the model's own
+            scip_call_panic!(ffi::SCIPsetPresolving(self.scip(), ffi::SCIP_ParamSetting_SCIP_PARAMSETTING_OFF, 1));
+            self.set_bool_param("constraints/countsols/collect", true);
+            self.set_long_param("constraints/countsols/sollimit", MAX_COLLECTED);
+        }
 
-        let mut best = Vec::new();
-        let mut min_prob = 1.1;
-
-        for idx in 0..board.cells.len() {
-            let c = &board.cells[idx];
-            if !c.is_revealed && !c.is_flagged {
-                let prob = global_prob;
-                if prob < min_prob - 1e-9 {
-                    min_prob = prob;
-                    best = vec![idx];
-                } else if (prob - min_prob).abs() < 1e-9 {
-                    best.push(idx);
+        let n = self.cells.len();
+        let mut counts = vec![0.0; n + 1];
+        let mut cell_counts = vec![vec![0.0; n]; n + 1];
+        for m in 0..=n {
+            let y = remaining as f64 - m as f64;
+            self.set_bounds(&self.interior, y, y);
+            match method {
+                CountMethod::Collected => (counts[m], cell_counts[m]) = self.count_collected()?,
+                CountMethod::PerCell => {
+                    counts[m] = self.count()?;
+                    if counts[m] == 0.0 { continue; }
+                    for (i, cell_count) in cell_counts[m].iter_mut().enumerate() {
+                        self.set_bounds(&self.vars[i], 1.0, 1.0);
+                        let count = self.count();
+                        self.set_bounds(&self.vars[i], 0.0, 1.0);
+                        *cell_count = count?;
+                    }
                 }
             }
         }
-        best
+
+        // scaled like the enumerator's counts
+        let scale = counts.iter().cloned().fold(0.0, f64::max);
+        if scale <= 0.0 { return None; }
+        for (count, cells) in counts.iter_mut().zip(&mut cell_counts) {
+            *count /= scale;
+            for c in cells.iter_mut() { *c /= scale; }
+        }
+        let component = ComponentCounts {
+            cells: self.cells.clone(),
+            counts,
+            cell_counts,
+            log_scale: scale.ln(),
+            exact: true,
+        };
+        Some(probability::combine(board, &[component]))
+    }
+
+    /// objective for `SCIPSolver::most_likely_layout`: maximise t <= ln C(interior, y)
+    fn maximize_layout_weight(&mut self, remaining: usize, interior: usize) {
+        let binom = LogBinomial::new(interior);
+        let top = interior.min(remaining);
+        let ln_weight = |y: usize| binom.ln_choose(interior, y);
+        let peak = (0..=top).map(ln_weight).fold(f64::NEG_INFINITY, f64::max);
+
+        let y = self.interior.clone();
+        let model = self.model.as_mut().expect("model is back after every solve");
+        let t = model.add(var().cont(..=peak).obj(-1.0).name("ln_weight"));
+        // chord between y = v and y = v + 1: t - slope * y <= ln C(v) - slope * v
+        for v in 0..top {
+            let slope = ln_weight(v + 1) - ln_weight(v);
+            let name = format!("chord_{}", v);
+            model.add(cons().name(&name).le(ln_weight(v) - slope * v as f64).coef(&t, 1.0).coef(&y, -slope));
+        }
     }
 }
 
@@ -215,8 +545,61 @@ impl Constraint {
 }
 
 impl Algorithm for SCIPSolver {
+    fn configure(&mut self, config: &SolverConfig) {
+        self.global_mine_count = config.global_mine_count;
+        self.local_search_flips = config.local_search_flips;
+        self.local_search_seed = config.local_search_seed;
+    }
+
     fn find_candidates(&mut self, board: &Board) -> SolverResult {
         // agent handles first move, solver provides next logical steps
         self.solve_exact(board)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+
+    #[test]
+    fn only_finished_counts_are_complete() {
+        assert!(is_complete(ffi::SCIP_Status_SCIP_STATUS_OPTIMAL));
+        assert!(is_complete(ffi::SCIP_Status_SCIP_STATUS_INFEASIBLE));
+        for partial in [
+            ffi::SCIP_Status_SCIP_STATUS_UNKNOWN,
+            ffi::SCIP_Status_SCIP_STATUS_USERINTERRUPT,
+            ffi::SCIP_Status_SCIP_STATUS_NODELIMIT,
+            ffi::SCIP_Status_SCIP_STATUS_TIMELIMIT,
+            ffi::SCIP_Status_SCIP_STATUS_SOLLIMIT,
+        ] {
+            assert!(!is_complete(partial));
+        }
+    }
+
+    /// both ways of counting give the enumerator's marginals
+    #[test]
+    fn counted_probabilities_match_enumeration() {
+        let mut counted = 0;
+        for seed in 0..10 {
+            let mut sim = Simulator::new(vec![5, 5], 5, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            for _ in 0..4 {
+                if sim.board.game_over { break; }
+                let expected = probability::enumerate_probabilities(&sim.board);
+                for method in [CountMethod::PerCell, CountMethod::Collected] {
+                    let Some(map) = SCIPSolver::counted_probabilities(&sim.board, method) else { continue; };
+                    counted += 1;
+                    for (i, cell) in sim.board.cells.iter().enumerate() {
+                        if cell.is_revealed { continue; }
+                        assert!((map.cell_probs[i] - expected.cell_probs[i]).abs() < 1e-9, "seed {seed} cell {i} {method:?}");
+                    }
+                }
+                sim.run_step().unwrap();
+            }
+        }
+        // a count that never completes would fall back every time and prove nothing here
+        assert!(counted > 20);
+    }
+}
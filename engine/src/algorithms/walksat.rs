@@ -0,0 +1,169 @@
+/**
+ * WalkSAT-style local search for witness layouts. A cell is only provably safe if no layout
+ * makes it a mine, so ruling a cell out just takes one layout where it is a mine. Local search
+ * finds those fast: the cell is pinned as a mine, a random violated clue is picked, and one of
+ * its cells is flipped toward the clue. The flip is random with probability `NOISE`, otherwise
+ * the one that breaks the fewest other clues. Only cells without a witness within the flip
+ * budget go to the solver's complete search (CDCL / SCIP), which is also the only thing that
+ * can prove a cell safe. The rng is seeded from the config and the click count, so runs
+ * repeat.
+ */
+use crate::board::Board;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use std::collections::HashMap;
+
+/// chance of a random flip instead of the greedy one
+const NOISE: f64 = 0.4;
+
+/// local search over "between low and high of these cells are mines" constraints
+#[derive(Clone)]
+pub struct WalkSat {
+    /// (vars, low, high), vars are positions in the caller's cell list
+    constraints: Vec<(Vec<usize>, usize, usize)>,
+    var_constraints: Vec<Vec<usize>>,
+    /// vars pinned to a value (known safe cells and known mines), never flipped
+    frozen: Vec<bool>,
+    flips: usize,
+    rng: StdRng,
+    /// last layout found, the next search starts from it
+    state: Vec<bool>,
+}
+
+impl WalkSat {
+    /// `clues` as (cells, mines) over board cells, `total` an optional range for the mines
+    /// among `cells`. clue cells outside `cells` become extra vars after them. `fixed` pins
+    /// board cells to a value (true = mine), a layout never changes them
+    pub fn new(board: &Board, cells: &[usize], clues: &[(Vec<usize>, usize)], total: Option<(usize, usize)>, fixed: &[(usize, bool)], flips: usize, seed: u64) -> Self {
+        let mut position: HashMap<usize, usize> = cells.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+        let mut constraints: Vec<(Vec<usize>, usize, usize)> = Vec::with_capacity(clues.len() + 1);
+        for (clue_cells, mines) in clues {
+            let vars = clue_cells.iter()
+                .map(|c| {
+                    let next = position.len();
+                    *position.entry(*c).or_insert(next)
+                })
+                .collect();
+            constraints.push((vars, *mines, *mines));
+        }
+        if let Some((low, high)) = total {
+            constraints.push(((0..cells.len()).collect(), low, high));
+        }
+        for &(cell, _) in fixed {
+            let next = position.len();
+            position.entry(cell).or_insert(next);
+        }
+        let vars = position.len();
+        let mut state = vec![false; vars];
+        let mut frozen = vec![false; vars];
+        for &(cell, mine) in fixed {
+            state[position[&cell]] = mine;
+            frozen[position[&cell]] = true;
+        }
+        let mut var_constraints = vec![Vec::new(); vars];
+        for (c, (vars, _, _)) in constraints.iter().enumerate() {
+            for &v in vars { var_constraints[v].push(c); }
+        }
+        let stream = seed ^ (board.total_clicks as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        Self {
+            constraints,
+            var_constraints,
+            frozen,
+            flips,
+            rng: StdRng::seed_from_u64(stream),
+            state,
+        }
+    }
+
+    fn violation(&self, c: usize, ones: usize) -> usize {
+        let (_, low, high) = self.constraints[c];
+        low.saturating_sub(ones) + ones.saturating_sub(high)
+    }
+
+    /// change of the total violation if `v` is flipped
+    fn delta(&self, v: usize, ones: &[usize]) -> isize {
+        let step = if self.state[v] { -1 } else { 1 };
+        self.var_constraints[v].iter()
+            .map(|&c| {
+                let after = (ones[c] as isize + step) as usize;
+                self.violation(c, after) as isize - self.violation(c, ones[c]) as isize
+            })
+            .sum()
+    }
+
+    /// a layout (per var: mine or not, `cells` first) with `var` a mine, none within the flip budget
+    pub fn witness(&mut self, var: usize) -> Option<Vec<bool>> {
+        if self.frozen[var] && !self.state[var] { return None; }
+        self.state[var] = true;
+        let mut ones: Vec<usize> = self.constraints.iter()
+            .map(|(vars, _, _)| vars.iter().filter(|&&v| self.state[v]).count())
+            .collect();
+        // violated constraints with their position in the list for O(1) removal
+        let mut violated = Vec::new();
+        let mut slot = vec![usize::MAX; self.constraints.len()];
+        for c in 0..self.constraints.len() {
+            if self.violation(c, ones[c]) > 0 {
+                slot[c] = violated.len();
+                violated.push(c);
+            }
+        }
+
+        let mut candidates = Vec::new();
+        for _ in 0..self.flips {
+            if violated.is_empty() { return Some(self.state.clone()); }
+            let c = violated[self.rng.gen_range(0..violated.len())];
+            // too few mines: set one, too many: clear one (never the pinned cell or a fixed one)
+            let want_mine = ones[c] < self.constraints[c].1;
+            candidates.clear();
+            candidates.extend(self.constraints[c].0.iter().copied().filter(|&v| self.state[v] != want_mine && v != var && !self.frozen[v]));
+            if candidates.is_empty() { return None; }
+
+            let pick = if self.rng.gen_bool(NOISE) {
+                candidates[self.rng.gen_range(0..candidates.len())]
+            } else {
+                *candidates.iter().min_by_key(|&&v| self.delta(v, &ones)).expect("candidates is not empty")
+            };
+
+            self.state[pick] = !self.state[pick];
+            for i in 0..self.var_constraints[pick].len() {
+                let d = self.var_constraints[pick][i];
+                if self.state[pick] { ones[d] += 1; } else { ones[d] -= 1; }
+                let bad = self.violation(d, ones[d]) > 0;
+                if bad && slot[d] == usize::MAX {
+                    slot[d] = violated.len();
+                    violated.push(d);
+                } else if !bad && slot[d] != usize::MAX {
+                    let last = *violated.last().expect("d is in the list");
+                    violated.swap_remove(slot[d]);
+                    if last != d { slot[last] = slot[d]; }
+                    slot[d] = usize::MAX;
+                }
+            }
+        }
+        violated.is_empty().then(|| self.state.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+
+    /// clues {0, 1} = 1 and {1, 2} = 1 with cell 0 known safe: 1 is a mine and 2 is safe, which a
+    /// free cell 0 would hide behind the layout {0, 2}
+    #[test]
+    fn fixed_cells_never_flip() {
+        let sim = Simulator::new(vec![4, 5], 5, WasmAlgorithmType::ExactSolver).unwrap();
+        let clues = vec![(vec![0, 1], 1), (vec![1, 2], 1)];
+        for seed in 0..20 {
+            let mut free = WalkSat::new(&sim.board, &[1, 2], &clues, None, &[], 1000, seed);
+            assert!(free.witness(1).is_some());
+            let mut fixed = WalkSat::new(&sim.board, &[1, 2, 0], &clues, None, &[(0, false)], 1000, seed);
+            let layout = fixed.witness(0).expect("1 can be a mine");
+            assert_eq!(layout, vec![true, false, false]);
+            assert!(fixed.witness(1).is_none(), "seed {seed}: 2 is safe once 0 is");
+            assert!(fixed.witness(2).is_none(), "seed {seed}: 0 is fixed safe");
+        }
+    }
+}
@@ -3,10 +3,13 @@
  * This module defines the Board and Cell structures for the 6-faced Minesweeper cube.
  */
 use serde::{Serialize, Deserialize};
-use rand::{thread_rng, SeedableRng}; 
+use rand::{thread_rng, Rng, SeedableRng}; 
 use rand::rngs::StdRng; 
 use rand::seq::SliceRandom;
 use std::collections::VecDeque; 
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::error::MinesweeperError;
+use crate::distribution::MineDistribution;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Cell {
@@ -18,10 +21,76 @@ pub struct Cell {
     pub coordinates: Vec<usize>, 
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// time-dependent variant: every `interval` clicks each hidden mine
+/// moves to a random adjacent hidden cell with `move_probability`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DynamicMines {
+    pub interval: usize,
+    pub move_probability: f64,
+}
+
+/// hints revealed before the first move, so solvers can be studied mid-game
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum InitialReveal {
+    /// reveal this many random safe cells
+    RandomSafe(usize),
+    /// keep the given cells mine-free and reveal them
+    Region(Vec<usize>),
+    /// reveal every zero-cell (and its opening) inside the given area
+    ZeroCells(Vec<usize>),
+}
+
+const INITIAL_REVEAL_STREAM: u64 = 0x5DEE_CE66_D1CE_4E5B;
+
+/// epochs are unique across all boards, so a state synced to one board never matches another
+static NEXT_EPOCH: AtomicU64 = AtomicU64::new(1);
+
+fn next_epoch() -> u64 {
+    NEXT_EPOCH.fetch_add(1, Ordering::Relaxed)
+}
+
+/// how many mines a board gets: an exact number or a fraction of all cells
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MineSpec {
+    Count(usize),
+    Density(f64),
+}
+
+impl MineSpec {
+    /// resolves to (mine count, density) for a board with `total_cells` cells
+    pub fn resolve(&self, total_cells: usize) -> Result<(usize, f64), MinesweeperError> {
+        match *self {
+            MineSpec::Count(mines) => Ok((mines, mines as f64 / total_cells.max(1) as f64)),
+            MineSpec::Density(density) => {
+                if !(0.0..1.0).contains(&density) {
+                    return Err(MinesweeperError::InvalidDensity(density));
+                }
+                Ok(((density * total_cells as f64).round() as usize, density))
+            }
+        }
+    }
+}
+
+/// exact count shuffles exactly `mines` mines, bernoulli flips an independent coin
+/// per free cell with the board density (the actual count is written back to `mines`)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Placement {
+    #[default]
+    ExactCount,
+    Bernoulli,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Board {
     pub dimensions: Vec<usize>, 
     pub mines: usize,
+    pub mine_density: f64,
+    pub placement: Placement,
+    pub distribution: MineDistribution,
+    /// what the solvers assume about mine placement when they guess; independent of
+    /// `distribution` so a mismatched (or oracle) prior is an explicit choice
+    #[serde(default)]
+    pub prior: MineDistribution,
     pub cells: Vec<Cell>,
     pub game_over: bool,
     pub game_won: bool,
@@ -31,14 +100,67 @@ pub struct Board {
     pub last_click_idx: usize,         
     pub adjacency_map: Vec<Vec<usize>>,
     pub seed: Option<u64>, 
+    pub mines_placed: bool,
+    /// explicit layout (replayed on every reset) instead of random placement
+    pub fixed_mines: Option<Vec<usize>>,
+    /// fixed opening click for the agent, defaults to the board centre
+    pub first_click: Option<usize>,
+    pub dynamic_mines: Option<DynamicMines>,
+    pub total_mine_moves: usize,
+    /// revealed cells in order, so a `KnowledgeState` only replays what is new
+    #[serde(default)]
+    pub reveal_log: Vec<usize>,
+    /// changes whenever the board changes in any other way than new reveals
+    /// (reset, flags, new mine layout, moving mines); states synced to an older epoch rebuild.
+    /// clones and deserialized boards get a fresh one
+    #[serde(skip, default = "next_epoch")]
+    pub epoch: u64,
+    /// id of the current game: new on every reset, kept by clones (they continue the same game).
+    /// drawn from the epoch counter, so it is unique across boards too
+    #[serde(skip, default = "next_epoch")]
+    pub game: u64,
+}
+
+/// a clone can be changed apart from the original, so it gets its own epoch
+impl Clone for Board {
+    fn clone(&self) -> Self {
+        Board {
+            dimensions: self.dimensions.clone(),
+            mines: self.mines,
+            mine_density: self.mine_density,
+            placement: self.placement,
+            distribution: self.distribution,
+            prior: self.prior,
+            cells: self.cells.clone(),
+            game_over: self.game_over,
+            game_won: self.game_won,
+            total_revealed: self.total_revealed,
+            total_clicks: self.total_clicks,
+            total_guesses: self.total_guesses,
+            last_click_idx: self.last_click_idx,
+            adjacency_map: self.adjacency_map.clone(),
+            seed: self.seed,
+            mines_placed: self.mines_placed,
+            fixed_mines: self.fixed_mines.clone(),
+            first_click: self.first_click,
+            dynamic_mines: self.dynamic_mines,
+            total_mine_moves: self.total_mine_moves,
+            reveal_log: self.reveal_log.clone(),
+            epoch: next_epoch(),
+            game: self.game,
+        }
+    }
 }
 
 impl Board {
-    pub fn new(dims: Vec<usize>, mines: usize, adjacency_map: Vec<Vec<usize>>) -> Self {
+    pub fn new(dims: Vec<usize>, mines: usize, adjacency_map: Vec<Vec<usize>>) -> Result<Self, MinesweeperError> {
         let total_cells = adjacency_map.len(); 
         
+        if dims.is_empty() || dims.contains(&0) || total_cells == 0 {
+            return Err(MinesweeperError::InvalidDimensions(dims));
+        }
         if mines >= total_cells {
-            panic!("Too many mines for board size!");
+            return Err(MinesweeperError::TooManyMines { mines, max: total_cells - 1 });
         }
         
         let mut cells = Vec::with_capacity(total_cells);
@@ -69,9 +191,13 @@ impl Board {
             });
         }
 
-        Board {
+        Ok(Board {
             dimensions: dims,
             mines,
+            mine_density: mines as f64 / total_cells as f64,
+            placement: Placement::ExactCount,
+            distribution: MineDistribution::Uniform,
+            prior: MineDistribution::Uniform,
             cells,
             game_over: false,
             game_won: false,
@@ -81,53 +207,236 @@ impl Board {
             last_click_idx: 0,
             adjacency_map,
             seed: None,
+            mines_placed: false,
+            fixed_mines: None,
+            first_click: None,
+            dynamic_mines: None,
+            total_mine_moves: 0,
+            reveal_log: Vec::new(),
+            epoch: next_epoch(),
+            game: next_epoch(),
+        })
+    }
+
+    /// builds a board from a count or density spec with the given placement process
+    pub fn with_spec(dims: Vec<usize>, spec: MineSpec, placement: Placement, adjacency_map: Vec<Vec<usize>>) -> Result<Self, MinesweeperError> {
+        let (mines, density) = spec.resolve(adjacency_map.len())?;
+        let mut board = Board::new(dims, mines, adjacency_map)?;
+        board.mine_density = density;
+        board.placement = placement;
+        Ok(board)
+    }
+
+    /// builds a board with an explicit mine layout, skipping random placement entirely
+    pub fn with_mines(dims: Vec<usize>, mine_indices: &[usize], adjacency_map: Vec<Vec<usize>>) -> Result<Self, MinesweeperError> {
+        let total_cells = adjacency_map.len();
+        if let Some(&index) = mine_indices.iter().find(|&&i| i >= total_cells) {
+            return Err(MinesweeperError::CellOutOfRange { index, total_cells });
         }
+        let mut unique = mine_indices.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+
+        let mut board = Board::new(dims, unique.len(), adjacency_map)?;
+        board.fixed_mines = Some(unique);
+        board.reset();
+        Ok(board)
     }
 
-    pub fn place_mines_after_first_click(&mut self, first_idx: usize) {
-        let mut indices: Vec<usize> = (0..self.cells.len()).collect();
-        indices.retain(|&idx| idx != first_idx && !self.adjacency_map[first_idx].contains(&idx));
-        
-        if let Some(s) = self.seed {
-            let mut rng = StdRng::seed_from_u64(s);
-            indices.shuffle(&mut rng);
-        } else {
-            let mut rng = thread_rng();
-            indices.shuffle(&mut rng);
+    /// puts mines exactly on the given cells and recomputes all clue numbers
+    pub fn place_mines_at(&mut self, mine_indices: &[usize]) -> Result<(), MinesweeperError> {
+        self.check_indices(mine_indices)?;
+        self.set_mines(mine_indices);
+        Ok(())
+    }
+
+    fn set_mines(&mut self, mine_indices: &[usize]) {
+        self.epoch = next_epoch();
+        for cell in &mut self.cells {
+            cell.is_mine = false;
         }
+        for &idx in mine_indices {
+            self.cells[idx].is_mine = true;
+        }
+        self.mines = self.cells.iter().filter(|c| c.is_mine).count();
 
-        for &idx in indices.iter().take(self.mines) {
+        self.update_adjacent_mines();
+        self.mines_placed = true;
+    }
+
+    pub fn place_mines_after_first_click(&mut self, first_idx: usize) -> Result<(), MinesweeperError> {
+        self.check_indices(&[first_idx])?;
+        let mut excluded = self.adjacency_map[first_idx].clone();
+        excluded.push(first_idx);
+        self.place_mines_excluding(&excluded)
+    }
+
+    /// places the mines over every cell not listed in `excluded`.
+    /// exact-count placement fails instead of placing fewer mines when the free cells run out
+    pub fn place_mines_excluding(&mut self, excluded: &[usize]) -> Result<(), MinesweeperError> {
+        self.check_indices(excluded)?;
+        let mut is_excluded = vec![false; self.cells.len()];
+        for &idx in excluded {
+            is_excluded[idx] = true;
+        }
+        let indices: Vec<usize> = (0..self.cells.len()).filter(|&idx| !is_excluded[idx]).collect();
+        let mut rng = self.rng(0);
+
+        let chosen = match self.placement {
+            Placement::ExactCount => {
+                if indices.len() < self.mines {
+                    return Err(MinesweeperError::NotEnoughFreeCells { mines: self.mines, available: indices.len() });
+                }
+                self.distribution.sample_exact(self, &indices, self.mines, &mut rng)?
+            }
+            Placement::Bernoulli => {
+                let chosen = self.distribution.sample_bernoulli(self, &indices, self.mine_density, &mut rng);
+                self.mines = chosen.len();
+                chosen
+            }
+        };
+        for idx in chosen {
             self.cells[idx].is_mine = true;
         }
 
+        self.update_adjacent_mines();
+        self.mines_placed = true;
+        Ok(())
+    }
+
+    fn check_indices(&self, indices: &[usize]) -> Result<(), MinesweeperError> {
+        match indices.iter().find(|&&i| i >= self.cells.len()) {
+            Some(&index) => Err(MinesweeperError::CellOutOfRange { index, total_cells: self.cells.len() }),
+            None => Ok(()),
+        }
+    }
+
+    /// seeded boards get one reproducible rng stream per purpose (placement, dynamic moves, ...)
+    pub(crate) fn rng(&self, stream: u64) -> StdRng {
+        match self.seed {
+            Some(s) => StdRng::seed_from_u64(s ^ stream),
+            None => StdRng::from_rng(thread_rng()).expect("thread rng never fails"),
+        }
+    }
+
+    /// recomputes the clue number of every cell from the current mine layout
+    fn update_adjacent_mines(&mut self) {
         for i in 0..self.cells.len() {
-            if !self.cells[i].is_mine {
-                let count = self.adjacency_map[i].iter()
+            self.cells[i].adjacent_mines = if self.cells[i].is_mine {
+                0
+            } else {
+                self.adjacency_map[i].iter()
                     .filter(|&&n| self.cells[n].is_mine)
-                    .count();
-                self.cells[i].adjacent_mines = count as u8;
+                    .count() as u8
+            };
+        }
+    }
+
+    /// dynamic mines: lets every hidden, unflagged mine hop to an adjacent hidden cell.
+    /// seeded boards derive the rng from the seed and click count, so runs stay reproducible
+    pub fn shift_mines(&mut self) {
+        let Some(dynamic) = self.dynamic_mines else { return; };
+        let mut rng = self.rng((self.total_clicks as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        self.shift_mines_with(&mut rng, dynamic.move_probability);
+    }
+
+    /// sets up the mines (unless a fixed layout is already in place) and reveals
+    /// the initial hints without counting any clicks. expects a fresh board (new or reset)
+    pub fn apply_initial_reveal(&mut self, reveal: &InitialReveal) -> Result<(), MinesweeperError> {
+        match reveal {
+            InitialReveal::RandomSafe(count) => {
+                if !self.mines_placed { self.place_mines_excluding(&[])?; }
+                let mut safe: Vec<usize> = (0..self.cells.len()).filter(|&i| !self.cells[i].is_mine).collect();
+                safe.shuffle(&mut self.rng(INITIAL_REVEAL_STREAM));
+                for idx in safe.into_iter().take(*count) {
+                    self.flood_fill(idx);
+                }
+            }
+            InitialReveal::Region(region) => {
+                self.check_indices(region)?;
+                if !self.mines_placed { self.place_mines_excluding(region)?; }
+                for &idx in region {
+                    if !self.cells[idx].is_mine {
+                        self.flood_fill(idx);
+                    }
+                }
+            }
+            InitialReveal::ZeroCells(area) => {
+                self.check_indices(area)?;
+                if !self.mines_placed { self.place_mines_excluding(&[])?; }
+                for &idx in area {
+                    if !self.cells[idx].is_mine && self.cells[idx].adjacent_mines == 0 {
+                        self.flood_fill(idx);
+                    }
+                }
             }
         }
+
+        if self.total_revealed == self.cells.len() - self.mines {
+            self.game_over = true;
+            self.game_won = true;
+        }
+        Ok(())
     }
 
-    pub fn reveal_cell(&mut self, idx: usize) {
+    fn shift_mines_with<R: Rng>(&mut self, rng: &mut R, move_probability: f64) {
+        let mut mines: Vec<usize> = (0..self.cells.len())
+            .filter(|&i| self.cells[i].is_mine && !self.cells[i].is_revealed && !self.cells[i].is_flagged)
+            .collect();
+        mines.shuffle(rng);
+
+        let mut moved = false;
+        for idx in mines {
+            if !rng.gen_bool(move_probability.clamp(0.0, 1.0)) { continue; }
+
+            let targets: Vec<usize> = self.adjacency_map[idx].iter()
+                .filter(|&&n| {
+                    let c = &self.cells[n];
+                    !c.is_mine && !c.is_revealed && !c.is_flagged
+                })
+                .cloned().collect();
+
+            if let Some(&target) = targets.choose(rng) {
+                self.cells[idx].is_mine = false;
+                self.cells[target].is_mine = true;
+                self.total_mine_moves += 1;
+                moved = true;
+            }
+        }
+
+        // revealed clue numbers have to match the new layout
+        if moved {
+            self.update_adjacent_mines();
+            self.epoch = next_epoch();
+        }
+    }
+
+    pub fn reveal_cell(&mut self, idx: usize) -> Result<(), MinesweeperError> {
+        self.check_indices(&[idx])?;
         if self.game_over || self.cells[idx].is_revealed || self.cells[idx].is_flagged {
-            return;
+            return Ok(());
         }
-        if self.total_clicks == 0 {
-            self.place_mines_after_first_click(idx);
+        if !self.mines_placed {
+            self.place_mines_after_first_click(idx)?;
         }
         self.last_click_idx = idx;
         self.total_clicks += 1;
         if self.cells[idx].is_mine {
             self.game_over = true;
-            return;
+            return Ok(());
         }
         self.flood_fill(idx);
         if self.total_revealed == self.cells.len() - self.mines {
             self.game_over = true;
             self.game_won = true;
+            return Ok(());
+        }
+        if let Some(dynamic) = self.dynamic_mines {
+            if dynamic.interval > 0 && self.total_clicks.is_multiple_of(dynamic.interval) {
+                self.shift_mines();
+            }
         }
+        Ok(())
     }
 
     fn flood_fill(&mut self, start_idx: usize) {
@@ -135,6 +444,7 @@ impl Board {
         if !self.cells[start_idx].is_revealed {
             self.cells[start_idx].is_revealed = true;
             self.total_revealed += 1;
+            self.reveal_log.push(start_idx);
         }
         if self.cells[start_idx].adjacent_mines > 0 { return; }
         while let Some(curr_idx) = stack.pop() {
@@ -142,6 +452,7 @@ impl Board {
                 if !self.cells[n_idx].is_revealed && !self.cells[n_idx].is_mine && !self.cells[n_idx].is_flagged {
                     self.cells[n_idx].is_revealed = true;
                     self.total_revealed += 1;
+                    self.reveal_log.push(n_idx);
                     if self.cells[n_idx].adjacent_mines == 0 {
                         stack.push(n_idx);
                     }
@@ -153,6 +464,7 @@ impl Board {
     pub fn toggle_flag(&mut self, idx: usize) {
         if idx < self.cells.len() && !self.cells[idx].is_revealed {
             self.cells[idx].is_flagged = !self.cells[idx].is_flagged;
+            self.epoch = next_epoch();
         }
     }
 
@@ -173,7 +485,20 @@ impl Board {
         self.total_revealed = 0;
         self.total_clicks = 0;
         self.total_guesses = 0;
-        // mine placement is done on first click
+        self.total_mine_moves = 0;
+        self.mines_placed = false;
+        self.reveal_log.clear();
+        self.epoch = next_epoch();
+        self.game = next_epoch();
+        if self.placement == Placement::Bernoulli {
+            // back to the expected count until the next placement draws a new one
+            self.mines = (self.mine_density * self.cells.len() as f64).round() as usize;
+        }
+        // mine placement is done on first click (or by apply_initial_reveal)
+        if let Some(layout) = self.fixed_mines.take() {
+            self.set_mines(&layout);
+            self.fixed_mines = Some(layout);
+        }
     }
 
     /// Clculates distances to all cells from start_idx using BFS
@@ -216,4 +541,216 @@ impl Board {
             1
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::Simulator;
+
+    fn layout(board: &Board) -> Vec<usize> {
+        (0..board.cells.len()).filter(|&i| board.cells[i].is_mine).collect()
+    }
+
+    #[test]
+    fn shift_mines_keeps_the_count_and_the_revealed_cells() {
+        for seed in 0..10 {
+            let mut sim = Simulator::new(vec![8, 8], 12, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            sim.set_dynamic_mines(1, 1.0);
+            sim.run_step().unwrap();
+            for _ in 0..10 {
+                let revealed: Vec<usize> = (0..sim.board.cells.len()).filter(|&i| sim.board.cells[i].is_revealed).collect();
+                let before = sim.board.total_mine_moves;
+                sim.board.shift_mines();
+                assert!(sim.board.total_mine_moves >= before);
+                assert_eq!(layout(&sim.board).len(), sim.board.mines, "seed {seed}");
+                assert!(revealed.iter().all(|&i| !sim.board.cells[i].is_mine), "seed {seed}");
+                for &i in &revealed {
+                    let around = sim.board.adjacency_map[i].iter().filter(|&&n| sim.board.cells[n].is_mine).count();
+                    assert_eq!(sim.board.cells[i].adjacent_mines as usize, around);
+                }
+                if !sim.run_step().unwrap() { break; }
+            }
+            assert!(sim.board.total_mine_moves > 0, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn shift_mines_is_deterministic_per_seed() {
+        let moved = |seed: u64| {
+            let mut sim = Simulator::new(vec![8, 8], 12, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            sim.set_dynamic_mines(1, 0.5);
+            sim.run_step().unwrap();
+            let mut layouts = Vec::new();
+            for _ in 0..5 {
+                sim.board.shift_mines();
+                sim.board.total_clicks += 1;
+                layouts.push(layout(&sim.board));
+            }
+            (layouts, sim.board.total_mine_moves)
+        };
+        for seed in 0..5 {
+            assert_eq!(moved(seed), moved(seed));
+        }
+        assert_ne!(moved(0), moved(1));
+    }
+
+    #[test]
+    fn reset_starts_a_new_game_and_clones_keep_it() {
+        let mut sim = Simulator::new(vec![5, 5], 4, WasmAlgorithmType::ExactSolver).unwrap();
+        sim.set_seed(3).unwrap();
+        sim.run_step().unwrap();
+        let game = sim.board.game;
+        assert_eq!(sim.board.clone().game, game);
+        assert_ne!(sim.board.clone().epoch, sim.board.epoch);
+        sim.reset().unwrap();
+        assert_ne!(sim.board.game, game);
+    }
+
+    #[test]
+    fn explicit_layouts_survive_resets_and_open_at_the_first_click() {
+        let mut sim = Simulator::from_mines(vec![5, 5], &[0, 4, 4, 24], WasmAlgorithmType::ExactSolver, Some(12)).unwrap();
+        assert_eq!((layout(&sim.board), sim.board.mines), (vec![0, 4, 24], 3));
+        assert_eq!(sim.board.cells[1].adjacent_mines, 1);
+        sim.run_step().unwrap();
+        assert_eq!(sim.board.last_click_idx, 12);
+        assert!(sim.board.cells[12].is_revealed);
+        sim.reset().unwrap();
+        assert_eq!(layout(&sim.board), vec![0, 4, 24]);
+        assert!(sim.board.cells.iter().all(|c| !c.is_revealed));
+
+        let by_coords = Simulator::from_mine_coordinates(vec![5, 5], &[vec![0, 0], vec![0, 4], vec![4, 4]], WasmAlgorithmType::ExactSolver, None).unwrap();
+        assert_eq!(layout(&by_coords.board), vec![0, 4, 24]);
+        // three dimensions are the cube: [face, y, x]
+        let cube = Simulator::from_mine_coordinates(vec![6, 3, 3], &[vec![2, 1, 1]], WasmAlgorithmType::ExactSolver, None).unwrap();
+        assert_eq!(layout(&cube.board), vec![22]);
+        assert_eq!(cube.board.cells[22].coordinates, vec![2, 1, 1]);
+    }
+
+    #[test]
+    fn explicit_layouts_reject_cells_outside_the_board() {
+        let from = |mines: &[usize], first: Option<usize>| Simulator::from_mines(vec![5, 5], mines, WasmAlgorithmType::ExactSolver, first).err();
+        assert_eq!(from(&[3, 25], None), Some(MinesweeperError::CellOutOfRange { index: 25, total_cells: 25 }));
+        assert_eq!(from(&[3], Some(30)), Some(MinesweeperError::CellOutOfRange { index: 30, total_cells: 25 }));
+        assert_eq!(from(&(0..25).collect::<Vec<_>>(), None), Some(MinesweeperError::TooManyMines { mines: 25, max: 24 }));
+
+        let coords = |c: Vec<usize>| Simulator::from_mine_coordinates(vec![5, 5], &[vec![1, 1], c.clone()], WasmAlgorithmType::ExactSolver, None).err();
+        assert_eq!(coords(vec![5, 0]), Some(MinesweeperError::CoordinatesOutOfRange(vec![5, 0])));
+        assert_eq!(coords(vec![1]), Some(MinesweeperError::CoordinatesOutOfRange(vec![1])));
+        assert_eq!(coords(vec![1, 2, 3]), Some(MinesweeperError::CoordinatesOutOfRange(vec![1, 2, 3])));
+    }
+
+    fn revealed(board: &Board) -> Vec<usize> {
+        (0..board.cells.len()).filter(|&i| board.cells[i].is_revealed).collect()
+    }
+
+    fn with_reveal(reveal: InitialReveal, seed: u64) -> Result<Simulator, MinesweeperError> {
+        let mut sim = Simulator::new(vec![8, 8], 10, WasmAlgorithmType::ExactSolver)?;
+        sim.set_seed(seed)?;
+        sim.set_initial_reveal(Some(reveal))?;
+        Ok(sim)
+    }
+
+    #[test]
+    fn initial_reveals_open_safe_cells_without_clicks() {
+        for seed in 0..10 {
+            let sim = with_reveal(InitialReveal::RandomSafe(3), seed).unwrap();
+            let open = revealed(&sim.board);
+            assert!(open.len() >= 3 && open.iter().all(|&i| !sim.board.cells[i].is_mine), "seed {seed}");
+            assert_eq!((sim.board.total_clicks, sim.board.total_revealed, layout(&sim.board).len()), (0, open.len(), 10));
+            // the hints follow the seed, and a reset lays them out again
+            assert_eq!(revealed(&with_reveal(InitialReveal::RandomSafe(3), seed).unwrap().board), open);
+            let mut again = with_reveal(InitialReveal::RandomSafe(3), seed).unwrap();
+            again.reset().unwrap();
+            assert_eq!(revealed(&again.board), open);
+
+            let region = vec![0, 1, 2, 8, 9, 10];
+            let sim = with_reveal(InitialReveal::Region(region.clone()), seed).unwrap();
+            assert!(region.iter().all(|&i| !sim.board.cells[i].is_mine && sim.board.cells[i].is_revealed), "seed {seed}");
+
+            let sim = with_reveal(InitialReveal::ZeroCells((0..64).collect()), seed).unwrap();
+            let board = &sim.board;
+            for i in 0..64 {
+                let cell = &board.cells[i];
+                let zero = !cell.is_mine && cell.adjacent_mines == 0;
+                let opened = board.adjacency_map[i].iter().any(|&n| board.cells[n].is_revealed && board.cells[n].adjacent_mines == 0);
+                assert_eq!(cell.is_revealed, zero || (!cell.is_mine && opened), "seed {seed} cell {i}");
+            }
+        }
+
+        // the solver plays on from the hints: an opening click on the revealed centre would not count
+        let mut sim = with_reveal(InitialReveal::Region(vec![36]), 1).unwrap();
+        sim.run_step().unwrap();
+        assert_eq!(sim.board.total_clicks, 1);
+    }
+
+    #[test]
+    fn initial_reveals_reject_cells_outside_the_board() {
+        let out = MinesweeperError::CellOutOfRange { index: 64, total_cells: 64 };
+        assert_eq!(with_reveal(InitialReveal::Region(vec![3, 64]), 0).err(), Some(out.clone()));
+        assert_eq!(with_reveal(InitialReveal::ZeroCells(vec![64]), 0).err(), Some(out));
+        // a region that leaves no room for the mines
+        let err = with_reveal(InitialReveal::Region((0..60).collect()), 0).err();
+        assert_eq!(err, Some(MinesweeperError::NotEnoughFreeCells { mines: 10, available: 4 }));
+    }
+
+    #[test]
+    fn bad_configurations_are_errors() {
+        let new = |dims: Vec<usize>, mines: usize| Simulator::new(dims, mines, WasmAlgorithmType::ExactSolver).err();
+        assert_eq!(new(vec![], 1), Some(MinesweeperError::InvalidDimensions(vec![])));
+        assert_eq!(new(vec![4, 0], 1), Some(MinesweeperError::InvalidDimensions(vec![4, 0])));
+        assert_eq!(new(vec![4, 4], 16), Some(MinesweeperError::TooManyMines { mines: 16, max: 15 }));
+        assert_eq!(new(vec![4, 4], 15), None);
+
+        for density in [1.0, -0.1, f64::NAN] {
+            let err = Simulator::with_spec(vec![4, 4], MineSpec::Density(density), Placement::Bernoulli, WasmAlgorithmType::ExactSolver).err();
+            assert!(matches!(err, Some(MinesweeperError::InvalidDensity(d)) if d.to_bits() == density.to_bits()), "{density}");
+        }
+
+        // the opening click and its neighbours cover the whole 3x3 board
+        let mut sim = Simulator::new(vec![3, 3], 1, WasmAlgorithmType::ExactSolver).unwrap();
+        assert_eq!(sim.run_step(), Err(MinesweeperError::NotEnoughFreeCells { mines: 1, available: 0 }));
+
+        let mut board = Simulator::new(vec![4, 4], 2, WasmAlgorithmType::ExactSolver).unwrap().board;
+        let out = Some(MinesweeperError::CellOutOfRange { index: 16, total_cells: 16 });
+        assert_eq!(board.reveal_cell(16).err(), out);
+        assert_eq!(board.place_mines_at(&[1, 16]).err(), out);
+        assert_eq!(board.place_mines_after_first_click(16).err(), out);
+        assert!(layout(&board).is_empty() && !board.mines_placed);
+
+        let err = MinesweeperError::TooManyMines { mines: 16, max: 15 };
+        assert_eq!(err.to_string(), "too many mines for board size: 16 requested, at most 15 allowed");
+    }
+
+    #[test]
+    fn density_specs_place_the_expected_mines() {
+        assert_eq!(MineSpec::Density(0.2).resolve(100), Ok((20, 0.2)));
+        assert_eq!(MineSpec::Count(25).resolve(100), Ok((25, 0.25)));
+
+        let spec = |placement: Placement, seed: u64| {
+            let mut sim = Simulator::with_spec(vec![20, 20], MineSpec::Density(0.2), placement, WasmAlgorithmType::ExactSolver).unwrap();
+            sim.set_seed(seed).unwrap();
+            sim.run_step().unwrap();
+            sim
+        };
+        let mut total = 0;
+        for seed in 0..20 {
+            assert_eq!(layout(&spec(Placement::ExactCount, seed).board).len(), 80);
+
+            // bernoulli writes the drawn count back, and a reset goes back to the expected one
+            let mut sim = spec(Placement::Bernoulli, seed);
+            let placed = layout(&sim.board).len();
+            assert_eq!(sim.board.mines, placed);
+            assert_eq!(sim.get_state_internal().mine_density, 0.2);
+            total += placed;
+            sim.reset().unwrap();
+            assert_eq!(sim.board.mines, 80);
+        }
+        // 391 free cells after the opening click: 78.2 mines expected, the mean of 20 boards has sd 1.8
+        let mean = total as f64 / 20.0;
+        assert!((mean - 78.2).abs() < 6.0, "{mean}");
+    }
+}
@@ -0,0 +1,323 @@
+/**
+ * Mine distributions for research boards.
+ * Uniform shuffling is the classic game; the other variants correlate mines in space
+ * (clusters, hard-core repulsion, density gradients). Every variant is seeded through
+ * the board rng and also provides a prior; solvers only use it when it is set as `Board::prior`.
+ */
+use serde::{Serialize, Deserialize};
+use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use std::collections::{HashSet, VecDeque};
+
+use crate::board::Board;
+use crate::error::MinesweeperError;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum MineDistribution {
+    #[default]
+    Uniform,
+    /// poisson cluster process: on average `parents` cluster centres, mines scattered
+    /// around them with a gaussian fall-off of `spread` (in adjacency steps)
+    Clustered { parents: f64, spread: f64 },
+    /// hard-core process: no two mines are adjacent
+    Repulsive,
+    /// density rises linearly along coordinate `axis`; strength 0 = uniform, 1 = empty at the low end
+    Gradient { axis: usize, strength: f64 },
+}
+
+impl MineDistribution {
+    /// picks exactly `count` mines among `candidates`
+    pub fn sample_exact(&self, board: &Board, candidates: &[usize], count: usize, rng: &mut StdRng) -> Result<Vec<usize>, MinesweeperError> {
+        let mut order = candidates.to_vec();
+        match self {
+            MineDistribution::Uniform => {
+                order.shuffle(rng);
+                order.truncate(count);
+                Ok(order)
+            }
+            MineDistribution::Repulsive => {
+                order.shuffle(rng);
+                let mines = hard_core(board, order, |_| true);
+                if mines.len() < count {
+                    return Err(MinesweeperError::PlacementFailed { mines: count, placed: mines.len() });
+                }
+                Ok(mines.into_iter().take(count).collect())
+            }
+            MineDistribution::Clustered { .. } | MineDistribution::Gradient { .. } => {
+                // weighted sampling without replacement (efraimidis-spirakis keys u^(1/w))
+                let weights = self.layout_weights(board, rng);
+                let mut keyed: Vec<(f64, usize)> = order.into_iter()
+                    .map(|idx| {
+                        let u: f64 = rng.gen_range(f64::EPSILON..1.0);
+                        let w = weights[idx];
+                        (if w > 0.0 { u.ln() / w } else { f64::NEG_INFINITY }, idx)
+                    })
+                    .collect();
+                keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+                Ok(keyed.into_iter().take(count).map(|(_, idx)| idx).collect())
+            }
+        }
+    }
+
+    /// independent per-cell draws with mean density `density`, reshaped by the distribution
+    pub fn sample_bernoulli(&self, board: &Board, candidates: &[usize], density: f64, rng: &mut StdRng) -> Vec<usize> {
+        let p = density.clamp(0.0, 1.0);
+        match self {
+            MineDistribution::Uniform => candidates.iter().copied().filter(|_| rng.gen_bool(p)).collect(),
+            MineDistribution::Repulsive => {
+                let mut order = candidates.to_vec();
+                order.shuffle(rng);
+                let coins: Vec<bool> = order.iter().map(|_| rng.gen_bool(p)).collect();
+                let accepted: HashSet<usize> = order.iter().zip(coins).filter(|(_, c)| *c).map(|(&i, _)| i).collect();
+                hard_core(board, order, |idx| accepted.contains(&idx))
+            }
+            MineDistribution::Clustered { .. } | MineDistribution::Gradient { .. } => {
+                let weights = self.layout_weights(board, rng);
+                let mean = candidates.iter().map(|&i| weights[i]).sum::<f64>() / candidates.len().max(1) as f64;
+                candidates.iter().copied()
+                    .filter(|&i| {
+                        let pi = if mean > 0.0 { p * weights[i] / mean } else { p };
+                        rng.gen_bool(pi.clamp(0.0, 1.0))
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// relative mine likelihood per cell for one layout (clustered draws its parents here)
+    fn layout_weights(&self, board: &Board, rng: &mut StdRng) -> Vec<f64> {
+        match *self {
+            MineDistribution::Clustered { parents, spread } => {
+                let count = poisson(parents.max(0.0), rng).max(1);
+                let centres: Vec<usize> = (0..count).map(|_| rng.gen_range(0..board.cells.len())).collect();
+                let mut weights = vec![1e-9; board.cells.len()];
+                for c in centres {
+                    for (w, d) in weights.iter_mut().zip(board.get_distance_map(c)) {
+                        *w += kernel(d, spread);
+                    }
+                }
+                weights
+            }
+            MineDistribution::Gradient { .. } => self.gradient_weights(board),
+            _ => vec![1.0; board.cells.len()],
+        }
+    }
+
+    fn gradient_weights(&self, board: &Board) -> Vec<f64> {
+        let MineDistribution::Gradient { axis, strength } = *self else {
+            return vec![1.0; board.cells.len()];
+        };
+        let extent = board.dimensions.get(axis).copied().unwrap_or(1).saturating_sub(1).max(1) as f64;
+        board.cells.iter()
+            .map(|c| {
+                let t = c.coordinates.get(axis).copied().unwrap_or(0) as f64 / extent;
+                (1.0 + strength.clamp(0.0, 1.0) * (2.0 * t - 1.0)).max(0.0)
+            })
+            .collect()
+    }
+
+    /// prior for solvers: relative mine likelihood of every cell given the mines they already know.
+    /// normalised so the hidden, not-known cells average 1 (multiply with the global density)
+    pub fn prior_weights(&self, board: &Board, known_mines: &[usize]) -> Vec<f64> {
+        let mut known: Vec<usize> = known_mines.to_vec();
+        known.extend((0..board.cells.len()).filter(|&i| board.cells[i].is_flagged));
+        let known_mines = &known[..];
+
+        let mut weights = match *self {
+            MineDistribution::Uniform => vec![1.0; board.cells.len()],
+            MineDistribution::Gradient { .. } => self.gradient_weights(board),
+            MineDistribution::Clustered { parents, spread } => {
+                // mines attract: cells near a known mine are up to one cluster size more likely
+                let cluster_size = board.mines as f64 / parents.max(1.0);
+                distance_to_nearest(board, known_mines).into_iter()
+                    .map(|d| 1.0 + cluster_size * kernel(d, spread))
+                    .collect()
+            }
+            MineDistribution::Repulsive => {
+                let mut w = vec![1.0; board.cells.len()];
+                for &m in known_mines {
+                    for &n in &board.adjacency_map[m] { w[n] = 0.0; }
+                }
+                w
+            }
+        };
+
+        let open: Vec<usize> = (0..board.cells.len())
+            .filter(|&i| !board.cells[i].is_revealed && !board.cells[i].is_flagged && !known_mines.contains(&i))
+            .collect();
+        let mean = open.iter().map(|&i| weights[i]).sum::<f64>() / open.len().max(1) as f64;
+        if mean > 0.0 {
+            for w in &mut weights { *w /= mean; }
+        }
+        weights
+    }
+
+    /// mine probabilities of the unconstrained `cells` (one per entry) that average `density`,
+    /// shaped by the prior: weights are normalised over these cells only, and cells the prior
+    /// would push past 1 are capped with their excess spread over the others
+    pub fn interior_probs(&self, board: &Board, cells: &[usize], density: f64) -> Vec<f64> {
+        let density = density.clamp(0.0, 1.0);
+        if matches!(self, MineDistribution::Uniform) || cells.is_empty() {
+            return vec![density; cells.len()];
+        }
+        let prior = self.prior_weights(board, &[]);
+        let weights: Vec<f64> = cells.iter().map(|&c| prior[c].max(0.0)).collect();
+
+        let mut probs = vec![0.0; cells.len()];
+        let mut capped = vec![false; cells.len()];
+        let mut left = density * cells.len() as f64;
+        loop {
+            let free: Vec<usize> = (0..cells.len()).filter(|&i| !capped[i]).collect();
+            if free.is_empty() { break; }
+            let mass: f64 = free.iter().map(|&i| weights[i]).sum();
+            // no prior weight left where mines must still go: spread the rest evenly
+            let share = |i: usize| if mass > 0.0 { left * weights[i] / mass } else { left / free.len() as f64 };
+            let over: Vec<usize> = free.iter().copied().filter(|&i| share(i) > 1.0).collect();
+            if over.is_empty() {
+                for &i in &free { probs[i] = share(i); }
+                break;
+            }
+            for i in over {
+                capped[i] = true;
+                probs[i] = 1.0;
+                left -= 1.0;
+            }
+        }
+        probs
+    }
+}
+
+/// greedy hard-core pass: accepts cells in order unless a neighbour is already a mine
+fn hard_core(board: &Board, order: Vec<usize>, wanted: impl Fn(usize) -> bool) -> Vec<usize> {
+    let mut blocked = vec![false; board.cells.len()];
+    let mut mines = Vec::new();
+    for idx in order {
+        if blocked[idx] || !wanted(idx) { continue; }
+        mines.push(idx);
+        blocked[idx] = true;
+        for &n in &board.adjacency_map[idx] { blocked[n] = true; }
+    }
+    mines
+}
+
+fn kernel(distance: usize, spread: f64) -> f64 {
+    if distance == usize::MAX { return 0.0; }
+    let s = spread.max(1e-6);
+    (-((distance * distance) as f64) / (2.0 * s * s)).exp()
+}
+
+/// multi-source bfs over the adjacency map
+fn distance_to_nearest(board: &Board, sources: &[usize]) -> Vec<usize> {
+    let mut dist = vec![usize::MAX; board.cells.len()];
+    let mut queue = VecDeque::new();
+    for &s in sources {
+        if dist[s] != 0 {
+            dist[s] = 0;
+            queue.push_back(s);
+        }
+    }
+    while let Some(cur) = queue.pop_front() {
+        for &n in &board.adjacency_map[cur] {
+            if dist[n] == usize::MAX {
+                dist[n] = dist[cur] + 1;
+                queue.push_back(n);
+            }
+        }
+    }
+    dist
+}
+
+/// knuth's multiplication method, fine for the small means used for cluster parents
+fn poisson(mean: f64, rng: &mut StdRng) -> usize {
+    let limit = (-mean).exp();
+    let mut k = 0;
+    let mut p: f64 = rng.gen();
+    while p > limit {
+        k += 1;
+        p *= rng.gen::<f64>();
+    }
+    k
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::WasmAlgorithmType;
+    use crate::board::Placement;
+    use crate::Simulator;
+
+    const VARIANTS: [MineDistribution; 4] = [
+        MineDistribution::Uniform,
+        MineDistribution::Clustered { parents: 3.0, spread: 1.5 },
+        MineDistribution::Repulsive,
+        MineDistribution::Gradient { axis: 0, strength: 1.0 },
+    ];
+
+    fn placed(distribution: MineDistribution, placement: Placement, seed: u64) -> Board {
+        let mut sim = Simulator::new(vec![10, 10], 15, WasmAlgorithmType::ExactSolver).unwrap();
+        sim.set_seed(seed).unwrap();
+        sim.set_distribution(distribution);
+        sim.board.placement = placement;
+        sim.board.place_mines_after_first_click(44).unwrap();
+        sim.board
+    }
+
+    fn mines(board: &Board) -> Vec<usize> {
+        (0..board.cells.len()).filter(|&i| board.cells[i].is_mine).collect()
+    }
+
+    #[test]
+    fn exact_placement_hits_the_count_outside_the_first_click() {
+        for distribution in VARIANTS {
+            for seed in 0..20 {
+                let board = placed(distribution, Placement::ExactCount, seed);
+                let layout = mines(&board);
+                assert_eq!(layout.len(), 15, "{distribution:?} seed {seed}");
+                assert!(!layout.contains(&44) && board.adjacency_map[44].iter().all(|n| !layout.contains(n)));
+            }
+        }
+    }
+
+    #[test]
+    fn repulsive_mines_are_never_adjacent() {
+        for placement in [Placement::ExactCount, Placement::Bernoulli] {
+            for seed in 0..20 {
+                let board = placed(MineDistribution::Repulsive, placement, seed);
+                let layout = mines(&board);
+                assert_eq!(layout.len(), board.mines);
+                for &m in &layout {
+                    assert!(board.adjacency_map[m].iter().all(|&n| !board.cells[n].is_mine), "{placement:?} seed {seed}");
+                }
+            }
+        }
+        // more mines than a hard-core layout can hold is an error, not a short board
+        let mut sim = Simulator::new(vec![6, 6], 20, WasmAlgorithmType::ExactSolver).unwrap();
+        sim.set_seed(0).unwrap();
+        sim.set_distribution(MineDistribution::Repulsive);
+        assert!(matches!(sim.board.place_mines_after_first_click(0), Err(MinesweeperError::PlacementFailed { mines: 20, .. })));
+    }
+
+    #[test]
+    fn prior_weights_average_one_over_the_open_cells() {
+        for distribution in VARIANTS {
+            for seed in 0..5 {
+                let mut sim = Simulator::new(vec![10, 10], 15, WasmAlgorithmType::ExactSolver).unwrap();
+                sim.set_seed(seed).unwrap();
+                sim.set_distribution(distribution);
+                for _ in 0..3 {
+                    if !sim.run_step().unwrap() { break; }
+                }
+                let board = &sim.board;
+                let known: Vec<usize> = mines(board).into_iter().take(2).collect();
+                let weights = distribution.prior_weights(board, &known);
+                let open: Vec<usize> = (0..board.cells.len())
+                    .filter(|&i| !board.cells[i].is_revealed && !board.cells[i].is_flagged && !known.contains(&i))
+                    .collect();
+                let mean = open.iter().map(|&i| weights[i]).sum::<f64>() / open.len() as f64;
+                assert!((mean - 1.0).abs() < 1e-9, "{distribution:?} seed {seed}: {mean}");
+                assert!(weights.iter().all(|&w| w >= 0.0));
+            }
+        }
+    }
+}
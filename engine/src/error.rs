@@ -0,0 +1,62 @@
+/**
+ * Error type shared by the board and the simulator.
+ * Bad configurations fail loudly instead of panicking or being clamped silently;
+ * the wasm interface maps every variant to a JsError.
+ */
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MinesweeperError {
+    /// empty dimension list or a zero-sized axis
+    InvalidDimensions(Vec<usize>),
+    /// at least one cell has to stay safe
+    TooManyMines { mines: usize, max: usize },
+    /// densities live in [0, 1)
+    InvalidDensity(f64),
+    /// the opening click (and its neighbours) leave too few cells for the requested mines
+    NotEnoughFreeCells { mines: usize, available: usize },
+    /// the distribution (e.g. hard-core repulsion) could not fit all mines
+    PlacementFailed { mines: usize, placed: usize },
+    CellOutOfRange { index: usize, total_cells: usize },
+    CoordinatesOutOfRange(Vec<usize>),
+    /// pattern database json that does not parse or has malformed patterns
+    InvalidPatternDatabase(String),
+    /// cnf literal 0 or a variable the sat solver was not sized for
+    InvalidLiteral { literal: isize, num_vars: usize },
+}
+
+impl fmt::Display for MinesweeperError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MinesweeperError::InvalidDimensions(dims) => {
+                write!(f, "invalid board dimensions {:?}", dims)
+            }
+            MinesweeperError::TooManyMines { mines, max } => {
+                write!(f, "too many mines for board size: {} requested, at most {} allowed", mines, max)
+            }
+            MinesweeperError::InvalidDensity(density) => {
+                write!(f, "mine density {} is outside [0, 1)", density)
+            }
+            MinesweeperError::NotEnoughFreeCells { mines, available } => {
+                write!(f, "cannot place {} mines, only {} cells are free after the first click", mines, available)
+            }
+            MinesweeperError::PlacementFailed { mines, placed } => {
+                write!(f, "mine distribution only fits {} of {} mines", placed, mines)
+            }
+            MinesweeperError::CellOutOfRange { index, total_cells } => {
+                write!(f, "cell index {} out of range for a board with {} cells", index, total_cells)
+            }
+            MinesweeperError::CoordinatesOutOfRange(coords) => {
+                write!(f, "coordinates {:?} are outside the board", coords)
+            }
+            MinesweeperError::InvalidPatternDatabase(reason) => {
+                write!(f, "invalid pattern database: {}", reason)
+            }
+            MinesweeperError::InvalidLiteral { literal, num_vars } => {
+                write!(f, "cnf literal {} is outside the solver's {} variables", literal, num_vars)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MinesweeperError {}
@@ -9,12 +9,22 @@
  
  pub mod board;
  pub mod algorithms;
+ pub mod error;
+ pub mod distribution;
  
- use board::Board;
+ use board::{Board, DynamicMines, InitialReveal, MineSpec, Placement};
+ use error::MinesweeperError;
+ use distribution::MineDistribution;
  use crate::algorithms::{
      MinesweeperAgent, AlgorithmFactory, WasmAlgorithmType, 
-     TspObjective, SolverResult
+     TspObjective, SolverResult, CardinalityEncoding, SolverConfig, GuessPolicy
  };
+ use crate::algorithms::sampling::{self, SampledProbabilities, SamplerSettings};
+ use crate::algorithms::probability::{self, ProbabilityMap};
+ use crate::algorithms::belief_propagation;
+ use crate::algorithms::cp::{self, ValueCount};
+ use crate::algorithms::model_count::{self, ModelCount};
+ use crate::algorithms::patterns::{PatternDb, PatternMatch};
  
  #[cfg(target_arch = "wasm32")]
  use js_sys::Date;
@@ -28,7 +38,11 @@
      pub game_won: bool,
      pub total_revealed: usize,
      pub total_cells: usize,
+     pub total_guesses: usize,
+     /// actual number of mines (drawn per game under bernoulli placement)
      pub mines: usize,
+     pub mine_density: f64,
+     pub total_mine_moves: usize,
  }
  
  #[wasm_bindgen]
@@ -38,6 +52,7 @@
      pub(crate) algorithm_type: WasmAlgorithmType,
      pub(crate) steps: usize,
      pub(crate) time_ms: f64,
+     pub(crate) initial_reveal: Option<InitialReveal>,
  }
  
  // ==========================================
@@ -46,50 +61,29 @@
  #[wasm_bindgen]
  impl Simulator {
      #[wasm_bindgen(constructor)]
-     pub fn new(dims: Vec<usize>, mines: usize, algorithm_type: WasmAlgorithmType) -> Self {
-         // 1. Compute actual dimensions
-         let actual_dims = if dims.len() == 3 && dims[0] != 6 {
-             vec![6, dims[1], dims[2]]
-         } else {
-             dims.clone() // Important: clone() allows reuse of dims later
-         };
- 
-         // 2. Compute total cells and safe number of mines
-         let total_cells: usize = if actual_dims.len() == 3 && actual_dims[0] == 6 {
-             6 * actual_dims[1] * actual_dims[2]
-         } else {
-             actual_dims.iter().product()
-         };
-         
-         // Apply safe_mines to avoid creating more mines than possible
-         let safe_mines = mines.min(total_cells - 1);
- 
-         // 3. Generate adjacency map
-         let adj_map = if actual_dims.len() == 3 && actual_dims[0] == 6 {
-             Self::generate_cube_adjacency(actual_dims[2], actual_dims[1])
-         } else {
-             Self::generate_nd_adjacency(&actual_dims)
-         };
-         
-         // 4. Create board and agent
-         let board = Board::new(actual_dims.clone(), safe_mines, adj_map);
-         
-         let h = if actual_dims.len() >= 2 { actual_dims[actual_dims.len()-2] } else { actual_dims[0] };
-         let w = if actual_dims.len() >= 1 { actual_dims[actual_dims.len()-1] } else { 1 };
+     pub fn wasm_new(dims: Vec<usize>, mines: usize, algorithm_type: WasmAlgorithmType) -> Result<Simulator, JsError> {
+         Ok(Self::new(dims, mines, algorithm_type)?)
+     }
  
-         let agent = AlgorithmFactory::create_agent(
-             algorithm_type,
-             TspObjective::MinDistance,
-             w, h, safe_mines
-         );
+     /// mines as a fraction of all cells; bernoulli = independent coin per cell instead of an exact count
+     #[wasm_bindgen(js_name = withDensity)]
+     pub fn wasm_with_density(dims: Vec<usize>, density: f64, bernoulli: bool, algorithm_type: WasmAlgorithmType) -> Result<Simulator, JsError> {
+         let placement = if bernoulli { Placement::Bernoulli } else { Placement::ExactCount };
+         Ok(Self::with_spec(dims, MineSpec::Density(density), placement, algorithm_type)?)
+     }
  
-         Simulator {
-             board,
-             agent,
-             algorithm_type,
-             steps: 0,
-             time_ms: 0.0,
-         }
+     /// replays an explicit mine layout (cell indices) instead of random placement
+     #[wasm_bindgen(js_name = fromMines)]
+     pub fn wasm_from_mines(dims: Vec<usize>, mines: Vec<usize>, algorithm_type: WasmAlgorithmType, first_click: Option<usize>) -> Result<Simulator, JsError> {
+         Ok(Self::from_mines(dims, &mines, algorithm_type, first_click)?)
+     }
+
+     /// same as fromMines, but mines are given as flattened coordinates (dims.len() values per mine)
+     #[wasm_bindgen(js_name = fromMineCoordinates)]
+     pub fn wasm_from_mine_coordinates(dims: Vec<usize>, coords: Vec<usize>, algorithm_type: WasmAlgorithmType, first_click: Option<usize>) -> Result<Simulator, JsError> {
+         let stride = dims.len().max(1);
+         let coords: Vec<Vec<usize>> = coords.chunks(stride).map(|c| c.to_vec()).collect();
+         Ok(Self::from_mine_coordinates(dims, &coords, algorithm_type, first_click)?)
      }
  
      #[wasm_bindgen(js_name = getState)]
@@ -107,34 +101,37 @@
              "total_revealed": self.board.total_revealed,
              "total_cells": total_cells,
              "mines": total_mines,
+             "mine_density": self.board.mine_density,
              "cells": self.board.cells,
              "dimensions": self.board.dimensions,
              "time_ms": self.time_ms,
              "completion": completion,
              "algorithm": self.algorithm_type.as_str(),
              "total_clicks": self.steps,
-             "total_guesses": self.board.total_guesses
+             "total_guesses": self.board.total_guesses,
+             "total_mine_moves": self.board.total_mine_moves
          });
      
          state_json.serialize(&Serializer::json_compatible()).unwrap()
      }
  
      #[wasm_bindgen(js_name = runBatch)]
-     pub fn run_batch(&mut self, games: usize) -> JsValue {
+     pub fn run_batch(&mut self, games: usize) -> Result<JsValue, JsError> {
          let mut results = Vec::new();
-         let mines = self.board.mines;
          let total_cells = self.board.cells.len();
  
          for game_idx in 0..games {
-             self.reset(); 
+             self.reset()?; 
              
              #[cfg(target_arch = "wasm32")]
              let start = Date::now();
              
              while !self.board.game_over && !self.board.game_won {
-                 self.run_step();
+                 self.run_step()?;
              }
              let current_guesses = self.board.total_guesses;
+             // bernoulli placement draws a different count every game
+             let mines = self.board.mines;
  
              #[cfg(target_arch = "wasm32")]
              let duration = Date::now() - start;
@@ -148,23 +145,217 @@
                  "steps": self.steps,
                  "total_revealed": self.board.total_revealed,
                  "total_cells": total_cells,
+                 "mines": mines,
                  "time_ms": duration,
                  "algorithm": self.algorithm_type.as_str(),
                  "completion": (self.board.total_revealed as f64 / (total_cells - mines) as f64) * 100.0
              }));
          }
  
-         serde_wasm_bindgen::to_value(&results).unwrap()
+         Ok(serde_wasm_bindgen::to_value(&results)?)
      }
  
      #[wasm_bindgen(js_name = runStep)]
-     pub fn run_step(&mut self) -> bool {
-         if self.board.game_over || self.board.game_won { return false; }
+     pub fn wasm_run_step(&mut self) -> Result<bool, JsError> {
+         Ok(self.run_step()?)
+     }
+ 
+     #[wasm_bindgen(js_name = runFullGame)]
+     pub fn wasm_run_full_game(&mut self) -> Result<JsValue, JsError> {
+         self.run_full_game()?;
+         Ok(self.get_state())
+     }
+ 
+     #[wasm_bindgen(js_name = reset)]
+     pub fn wasm_reset(&mut self) -> Result<(), JsError> {
+         Ok(self.reset()?)
+     }
+ 
+     #[wasm_bindgen(js_name = setAlgorithm)]
+     pub fn set_algorithm(&mut self, algorithm_type: WasmAlgorithmType) {
+         self.algorithm_type = algorithm_type;
+         let dims = &self.board.dimensions;
+         let h = if dims.len() >= 2 { dims[dims.len()-2] } else { dims[0] };
+         let w = if !dims.is_empty() { dims[dims.len()-1] } else { 1 };
+         let config = self.agent.config;
+         self.agent = AlgorithmFactory::create_agent(algorithm_type, self.agent.objective, w, h, self.board.mines);
+         self.agent.configure(config);
+     }
+ 
+     #[wasm_bindgen(js_name = setSeed)]
+     pub fn wasm_set_seed(&mut self, seed: u64) -> Result<(), JsError> {
+         Ok(self.set_seed(seed)?)
+     }
+
+     #[wasm_bindgen(js_name = setInitialRevealRandom)]
+     pub fn wasm_set_initial_reveal_random(&mut self, count: usize) -> Result<(), JsError> {
+         Ok(self.set_initial_reveal(Some(InitialReveal::RandomSafe(count)))?)
+     }
+
+     #[wasm_bindgen(js_name = setInitialRevealRegion)]
+     pub fn wasm_set_initial_reveal_region(&mut self, cells: Vec<usize>) -> Result<(), JsError> {
+         Ok(self.set_initial_reveal(Some(InitialReveal::Region(cells)))?)
+     }
+
+     #[wasm_bindgen(js_name = setInitialRevealZeroCells)]
+     pub fn wasm_set_initial_reveal_zero_cells(&mut self, area: Vec<usize>) -> Result<(), JsError> {
+         Ok(self.set_initial_reveal(Some(InitialReveal::ZeroCells(area)))?)
+     }
+
+     #[wasm_bindgen(js_name = clearInitialReveal)]
+     pub fn wasm_clear_initial_reveal(&mut self) -> Result<(), JsError> {
+         Ok(self.set_initial_reveal(None)?)
+     }
+ 
+     /// interval 0 switches the dynamic mines variant off
+     #[wasm_bindgen(js_name = setDynamicMines)]
+     pub fn wasm_set_dynamic_mines(&mut self, interval: usize, move_probability: f64) {
+         self.set_dynamic_mines(interval, move_probability);
+     }
+ 
+     #[wasm_bindgen(js_name = setUniformMines)]
+     pub fn wasm_set_uniform_mines(&mut self) { self.set_distribution(MineDistribution::Uniform); }
+
+     /// poisson cluster process with `parents` clusters on average and a gaussian spread (in cells)
+     #[wasm_bindgen(js_name = setClusteredMines)]
+     pub fn wasm_set_clustered_mines(&mut self, parents: f64, spread: f64) {
+         self.set_distribution(MineDistribution::Clustered { parents, spread });
+     }
+
+     /// hard-core placement: no two mines touch
+     #[wasm_bindgen(js_name = setRepulsiveMines)]
+     pub fn wasm_set_repulsive_mines(&mut self) { self.set_distribution(MineDistribution::Repulsive); }
+
+     #[wasm_bindgen(js_name = setGradientMines)]
+     pub fn wasm_set_gradient_mines(&mut self, axis: usize, strength: f64) {
+         self.set_distribution(MineDistribution::Gradient { axis, strength });
+     }
+ 
+     #[wasm_bindgen(js_name = setUniformPrior)]
+     pub fn wasm_set_uniform_prior(&mut self) { self.set_prior(MineDistribution::Uniform); }
+
+     #[wasm_bindgen(js_name = setClusteredPrior)]
+     pub fn wasm_set_clustered_prior(&mut self, parents: f64, spread: f64) {
+         self.set_prior(MineDistribution::Clustered { parents, spread });
+     }
+
+     #[wasm_bindgen(js_name = setRepulsivePrior)]
+     pub fn wasm_set_repulsive_prior(&mut self) { self.set_prior(MineDistribution::Repulsive); }
+
+     #[wasm_bindgen(js_name = setGradientPrior)]
+     pub fn wasm_set_gradient_prior(&mut self, axis: usize, strength: f64) {
+         self.set_prior(MineDistribution::Gradient { axis, strength });
+     }
+ 
+     #[wasm_bindgen(js_name = setTspObjective)]
+     pub fn wasm_set_tsp_objective(&mut self, objective: TspObjective) { self.agent.objective = objective; }
+
+     /// cnf encoding of the clue cardinalities for the sat solvers
+     #[wasm_bindgen(js_name = setCardinalityEncoding)]
+     pub fn wasm_set_cardinality_encoding(&mut self, encoding: CardinalityEncoding) { self.set_cardinality_encoding(encoding); }
+
+     /// lets the sat / ilp solvers use the total mine count (endgame deductions)
+     #[wasm_bindgen(js_name = setGlobalMineCount)]
+     pub fn wasm_set_global_mine_count(&mut self, enabled: bool) { self.set_global_mine_count(enabled); }
+
+     /// how guesses are chosen when no cell is provably safe
+     #[wasm_bindgen(js_name = setGuessPolicy)]
+     pub fn wasm_set_guess_policy(&mut self, policy: GuessPolicy) { self.set_guess_policy(policy); }
+
+     /// playouts per guess for the mcts solver (0 = default, kept small in wasm)
+     #[wasm_bindgen(js_name = setPlayoutBudget)]
+     pub fn wasm_set_playout_budget(&mut self, playouts: usize) { self.set_playout_budget(playouts); }
+
+     /// walksat flips per witness before the sat solvers run complete search (0 = off)
+     #[wasm_bindgen(js_name = setLocalSearch)]
+     pub fn wasm_set_local_search(&mut self, flips: usize, seed: u64) { self.set_local_search(flips, seed); }
+ 
+     /// sampled mine probabilities of the current board, per cell with 95% half-widths
+     #[wasm_bindgen(js_name = estimateMineProbabilities)]
+     pub fn wasm_estimate_mine_probabilities(&self, samples: usize, chains: usize) -> JsValue {
+         let Some(estimate) = self.estimate_mine_probabilities(samples, chains) else {
+             return JsValue::NULL;
+         };
+         let json = serde_json::json!({
+             "probabilities": estimate.cell_probs,
+             "ci_half_width": estimate.ci_half_width,
+             "frontier": estimate.frontier,
+             "interior_probability": estimate.interior_prob,
+             "samples": estimate.samples
+         });
+         json.serialize(&Serializer::json_compatible()).unwrap()
+     }
+
+     /// patterns behind the last move of the pattern solver (empty array for the other solvers)
+     #[wasm_bindgen(js_name = lastExplanation)]
+     pub fn wasm_last_explanation(&self) -> JsValue {
+         serde_json::json!(self.last_explanation()).serialize(&Serializer::json_compatible()).unwrap()
+     }
+
+     /// replaces the pattern solver's database with a json export (`PatternDb::to_json`)
+     #[wasm_bindgen(js_name = loadPatternDatabase)]
+     pub fn wasm_load_pattern_database(&mut self, json: &str) -> Result<(), JsError> {
+         Ok(self.load_pattern_database(json)?)
+     }
+
+     #[wasm_bindgen(js_name = getSteps)]
+     pub fn wasm_get_steps(&self) -> usize { self.steps }
+ }
+ 
+ // ==========================================
+ // 2. Native Runner interface (for cargo run)
+ // No #[wasm_bindgen] to prevent errors
+ // ==========================================
+ impl Simulator {
+     pub fn new(dims: Vec<usize>, mines: usize, algorithm_type: WasmAlgorithmType) -> Result<Self, MinesweeperError> {
+         Self::with_spec(dims, MineSpec::Count(mines), Placement::ExactCount, algorithm_type)
+     }
+
+     /// mines given as a count or a density, placed as an exact count or per-cell bernoulli draws
+     pub fn with_spec(dims: Vec<usize>, spec: MineSpec, placement: Placement, algorithm_type: WasmAlgorithmType) -> Result<Self, MinesweeperError> {
+         // 1. Compute actual dimensions
+         let actual_dims = Self::resolve_dims(dims);
+ 
+         // 2. Generate adjacency map
+         let adj_map = Self::generate_adjacency(&actual_dims);
+         
+         // 3. Create board (validates dimensions and mine count) and agent
+         let board = Board::with_spec(actual_dims, spec, placement, adj_map)?;
+         Ok(Self::with_board(board, algorithm_type))
+     }
+
+     /// builds a simulator on a known board, e.g. to replay hard layouts or imported games
+     pub fn from_mines(dims: Vec<usize>, mines: &[usize], algorithm_type: WasmAlgorithmType, first_click: Option<usize>) -> Result<Self, MinesweeperError> {
+         let actual_dims = Self::resolve_dims(dims);
+         let adj_map = Self::generate_adjacency(&actual_dims);
+         let mut board = Board::with_mines(actual_dims, mines, adj_map)?;
+         if let Some(index) = first_click.filter(|&i| i >= board.cells.len()) {
+             return Err(MinesweeperError::CellOutOfRange { index, total_cells: board.cells.len() });
+         }
+         board.first_click = first_click;
+         Ok(Self::with_board(board, algorithm_type))
+     }
+
+     /// like `from_mines`, with every mine given by its coordinates ([face, y, x] on the cube)
+     pub fn from_mine_coordinates(dims: Vec<usize>, coords: &[Vec<usize>], algorithm_type: WasmAlgorithmType, first_click: Option<usize>) -> Result<Self, MinesweeperError> {
+         let actual_dims = Self::resolve_dims(dims.clone());
+         let mut mines = Vec::with_capacity(coords.len());
+         for c in coords {
+             if c.len() != actual_dims.len() || c.iter().zip(&actual_dims).any(|(&v, &d)| v >= d) {
+                 return Err(MinesweeperError::CoordinatesOutOfRange(c.clone()));
+             }
+             mines.push(Self::coords_to_index(c, &actual_dims));
+         }
+         Self::from_mines(dims, &mines, algorithm_type, first_click)
+     }
+
+     pub fn run_step(&mut self) -> Result<bool, MinesweeperError> {
+         if self.board.game_over || self.board.game_won { return Ok(false); }
  
          if let Some(result) = self.agent.next_move(&self.board) {
              if result.candidates.is_empty() {
                  self.board.game_over = true; // No moves possible
-                 return false;
+                 return Ok(false);
              }
             
              if result.is_guess {
@@ -172,73 +363,111 @@
              }
              
              let choice = self.agent.pick_best_from_candidates(&self.board, result);
-             self.board.reveal_cell(choice);
+             self.board.reveal_cell(choice)?;
              self.steps += 1;
-             true
+             Ok(true)
          } else {
              self.board.game_over = true;
-             false
+             Ok(false)
          }
      }
- 
-     #[wasm_bindgen(js_name = runFullGame)]
-     pub fn run_full_game(&mut self) -> JsValue {
+
+     pub fn run_full_game(&mut self) -> Result<(), MinesweeperError> {
          #[cfg(target_arch = "wasm32")]
          let start = Date::now();
          #[cfg(not(target_arch = "wasm32"))]
          let start_inst = Instant::now();
  
          while !self.board.game_over && !self.board.game_won {
-             if !self.run_step() { break; }
+             if !self.run_step()? { break; }
          }
  
          #[cfg(target_arch = "wasm32")]
          { self.time_ms = Date::now() - start; }
          #[cfg(not(target_arch = "wasm32"))]
          { self.time_ms = start_inst.elapsed().as_secs_f64() * 1000.0; }
- 
-         self.get_state()
+         Ok(())
      }
- 
-     #[wasm_bindgen(js_name = reset)]
-     pub fn reset(&mut self) {
+
+     pub fn reset(&mut self) -> Result<(), MinesweeperError> {
          self.board.reset();
          self.agent.first_move = true; 
          self.steps = 0;
          self.time_ms = 0.0;
+         if let Some(reveal) = &self.initial_reveal {
+             self.board.apply_initial_reveal(reveal)?;
+         }
+         Ok(())
      }
- 
-     #[wasm_bindgen(js_name = setAlgorithm)]
-     pub fn set_algorithm(&mut self, algorithm_type: WasmAlgorithmType) {
-         self.algorithm_type = algorithm_type;
-         let dims = &self.board.dimensions;
-         let h = if dims.len() >= 2 { dims[dims.len()-2] } else { dims[0] };
-         let w = if dims.len() >= 1 { dims[dims.len()-1] } else { 1 };
-         self.agent = AlgorithmFactory::create_agent(algorithm_type, self.agent.objective, w, h, self.board.mines);
-     }
- 
-     #[wasm_bindgen(js_name = setSeed)]
-     pub fn wasm_set_seed(&mut self, seed: u64) { self.board.seed = Some(seed); }
- 
-     #[wasm_bindgen(js_name = setTspObjective)]
-     pub fn wasm_set_tsp_objective(&mut self, objective: TspObjective) { self.agent.objective = objective; }
- 
-     #[wasm_bindgen(js_name = getSteps)]
-     pub fn wasm_get_steps(&self) -> usize { self.steps }
- }
- 
- // ==========================================
- // 2. Native Runner interface (for cargo run)
- // No #[wasm_bindgen] to prevent errors
- // ==========================================
- impl Simulator {
-     pub fn set_seed(&mut self, seed: u64) {
+
+     pub fn set_seed(&mut self, seed: u64) -> Result<(), MinesweeperError> {
          self.board.seed = Some(seed);
+         // pre-revealed boards are laid out up front, so they must follow the new seed
+         if self.initial_reveal.is_some() {
+             self.reset()?;
+         }
+         Ok(())
+     }
+
+     /// starts every game (including after reset) from the given hints instead of an opening click
+     pub fn set_initial_reveal(&mut self, reveal: Option<InitialReveal>) -> Result<(), MinesweeperError> {
+         self.initial_reveal = reveal;
+         self.reset()
      }
  
      pub fn set_tsp_objective(&mut self, objective: TspObjective) {
          self.agent.objective = objective;
      }
+
+     pub fn set_cardinality_encoding(&mut self, encoding: CardinalityEncoding) {
+         let mut config: SolverConfig = self.agent.config;
+         config.encoding = encoding;
+         self.agent.configure(config);
+     }
+
+     pub fn set_global_mine_count(&mut self, enabled: bool) {
+         let mut config: SolverConfig = self.agent.config;
+         config.global_mine_count = enabled;
+         self.agent.configure(config);
+     }
+
+     pub fn set_guess_policy(&mut self, policy: GuessPolicy) {
+         let mut config: SolverConfig = self.agent.config;
+         config.guess_policy = policy;
+         self.agent.configure(config);
+     }
+
+     pub fn set_playout_budget(&mut self, playouts: usize) {
+         let mut config: SolverConfig = self.agent.config;
+         config.playouts = playouts;
+         self.agent.configure(config);
+     }
+
+     pub fn set_local_search(&mut self, flips: usize, seed: u64) {
+         let mut config: SolverConfig = self.agent.config;
+         config.local_search_flips = flips;
+         config.local_search_seed = seed;
+         self.agent.configure(config);
+     }
+ 
+     /// takes effect with the next mine placement (first click or reset)
+     pub fn set_distribution(&mut self, distribution: MineDistribution) {
+         self.board.distribution = distribution;
+     }
+
+     /// placement model the solvers assume when they guess; stays uniform unless set,
+     /// pass the generator's own distribution for an oracle prior
+     pub fn set_prior(&mut self, prior: MineDistribution) {
+         self.board.prior = prior;
+     }
+ 
+     pub fn set_dynamic_mines(&mut self, interval: usize, move_probability: f64) {
+         self.board.dynamic_mines = if interval > 0 {
+             Some(DynamicMines { interval, move_probability })
+         } else {
+             None
+         };
+     }
  
      pub fn get_steps(&self) -> usize {
          self.steps
@@ -250,23 +479,140 @@
              game_won: self.board.game_won,
              total_revealed: self.board.total_revealed,
              total_cells: self.board.cells.len(),
+             total_guesses: self.board.total_guesses,
              mines: self.board.mines,
+             mine_density: self.board.mine_density,
+             total_mine_moves: self.board.total_mine_moves,
          }
      }
  
      pub fn get_next_move_metadata(&mut self) -> Option<SolverResult> {
          self.agent.next_move(&self.board)
      }
+
+     /// exact mine probabilities of the current board (the guess engine every solver shares)
+     pub fn mine_probabilities(&self) -> ProbabilityMap {
+         probability::compute_probabilities(&self.board)
+     }
+
+     /// exact mine probabilities from the constraint programming counts, none if they give up
+     pub fn cp_probabilities(&self) -> Option<ProbabilityMap> {
+         cp::probabilities(&self.board)
+     }
+
+     /// per frontier cell, solutions of its component with the cell safe and with it a mine
+     pub fn cp_value_counts(&self) -> Option<Vec<ValueCount>> {
+         cp::value_counts(&self.board)
+     }
+
+     /// exact layout counts of the current board (#SAT with component caching), none if too large
+     pub fn model_count(&self) -> Option<ModelCount> {
+         model_count::count_models(&self.board)
+     }
+
+     /// exact mine probabilities from the model counts
+     pub fn model_count_probabilities(&self) -> Option<ProbabilityMap> {
+         model_count::probabilities(&self.board)
+     }
+
+     /// loopy belief propagation marginals of the current board
+     pub fn belief_propagation_marginals(&self) -> ProbabilityMap {
+         belief_propagation::marginals(&self.board)
+     }
+
+     /// frontier mines of the most likely layout under the mine count (SCIP)
+     #[cfg(feature = "native")]
+     pub fn most_likely_layout(&self) -> Option<Vec<usize>> {
+         crate::algorithms::scip_solver::SCIPSolver::most_likely_layout(&self.board)
+     }
+
+     /// the SCIP solver's counted marginals of the current board by `method`, none where it
+     /// falls back to the shared probability engine
+     #[cfg(feature = "native")]
+     pub fn scip_counted_probabilities(&self, method: crate::algorithms::scip_solver::CountMethod) -> Option<ProbabilityMap> {
+         crate::algorithms::scip_solver::SCIPSolver::counted_probabilities(&self.board, method)
+     }
+
+     /// what another solver returns on the current board (same config, first click excluded)
+     pub fn solver_candidates(&self, algorithm_type: WasmAlgorithmType) -> SolverResult {
+         let dims = &self.board.dimensions;
+         let h = if dims.len() >= 2 { dims[dims.len()-2] } else { dims[0] };
+         let w = if !dims.is_empty() { dims[dims.len()-1] } else { 1 };
+         let mut agent = AlgorithmFactory::create_agent(algorithm_type, self.agent.objective, w, h, self.board.mines);
+         agent.configure(self.agent.config);
+         agent.solver.find_candidates(&self.board)
+     }
+
+     /// cells a solver would guess from `map` on the current board
+     pub fn safest_cells(&self, map: &ProbabilityMap) -> Vec<usize> {
+         map.safest_cells(&self.board)
+     }
+
+     /// patterns behind the solver's last candidates
+     pub fn last_explanation(&self) -> Vec<PatternMatch> {
+         self.agent.solver.last_explanation()
+     }
+
+     /// applies to the current solver only; switching algorithms goes back to the built-in database
+     pub fn load_pattern_database(&mut self, json: &str) -> Result<(), MinesweeperError> {
+         let db = PatternDb::from_json(json)?;
+         self.agent.solver.load_patterns(&db);
+         Ok(())
+     }
+
+     /// monte carlo estimate of every cell's mine probability, none if the clues are contradictory
+     pub fn estimate_mine_probabilities(&self, samples: usize, chains: usize) -> Option<SampledProbabilities> {
+         let settings = SamplerSettings { samples, chains, ..SamplerSettings::default() };
+         sampling::estimate_probabilities(&self.board, settings)
+     }
  
      // --- Internal helpers (shared) ---
+     /// 3 dims always mean the 6-faced cube: [6, H, W]
+     fn resolve_dims(dims: Vec<usize>) -> Vec<usize> {
+         if dims.len() == 3 && dims[0] != 6 {
+             vec![6, dims[1], dims[2]]
+         } else {
+             dims
+         }
+     }
+
+     fn generate_adjacency(actual_dims: &[usize]) -> Vec<Vec<usize>> {
+         if actual_dims.len() == 3 && actual_dims[0] == 6 {
+             Self::generate_cube_adjacency(actual_dims[2], actual_dims[1])
+         } else {
+             Self::generate_nd_adjacency(actual_dims)
+         }
+     }
+
+     fn with_board(board: Board, algorithm_type: WasmAlgorithmType) -> Self {
+         let dims = &board.dimensions;
+         let h = if dims.len() >= 2 { dims[dims.len()-2] } else { dims[0] };
+         let w = if !dims.is_empty() { dims[dims.len()-1] } else { 1 };
+
+         let agent = AlgorithmFactory::create_agent(
+             algorithm_type,
+             TspObjective::MinDistance,
+             w, h, board.mines
+         );
+
+         Simulator {
+             board,
+             agent,
+             algorithm_type,
+             steps: 0,
+             time_ms: 0.0,
+             initial_reveal: None,
+         }
+     }
+
      fn generate_nd_adjacency(dims: &[usize]) -> Vec<Vec<usize>> {
          let total_cells: usize = dims.iter().product();
          let mut adj = vec![Vec::new(); total_cells];
-         for i in 0..total_cells {
+         for (i, slot) in adj.iter_mut().enumerate() {
              let coords = Self::index_to_coords(i, dims);
              let mut neighbors = Vec::new();
              Self::find_nd_neighbors(0, &coords, &mut Vec::new(), dims, &mut neighbors);
-             adj[i] = neighbors.into_iter().filter(|&n_idx| n_idx != i).collect();
+             *slot = neighbors.into_iter().filter(|&n_idx| n_idx != i).collect();
          }
          adj
      }
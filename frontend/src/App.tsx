@@ -36,6 +36,14 @@ const App: React.FC = () => {
   });
   
 
+  // density mode builds boards from a mine density (exact count or per-cell bernoulli) instead of a count
+  const createSimulator = (dims: number[] | Uint32Array, algo: AlgorithmType): Simulator => {
+    if (gameConfig.mineMode === 'density') {
+      return Simulator.withDensity(new Uint32Array(dims), gameConfig.density ?? 0.15, gameConfig.bernoulli ?? false, algo as any);
+    }
+    return new Simulator(new Uint32Array(dims), gameConfig.mines, algo as any);
+  };
+
   const [wasmReady, setWasmReady] = useState<boolean>(false);
   const [simulator, setSimulator] = useState<Simulator | null>(null);
   const [boardState, setBoardState] = useState<any>(null);
@@ -83,7 +91,7 @@ const App: React.FC = () => {
       console.log(`Expected total cells : ${totalPossible}, mines: ${mines}`);
   
       // Generate Simulator (typecasting to prevent err)
-      const newSim = new Simulator(finalDims, mines, selectedAlgorithm as any);
+      const newSim = createSimulator(finalDims, selectedAlgorithm);
       
       const initialState = newSim.getState();
       setSimulator(newSim);
@@ -230,7 +238,7 @@ const App: React.FC = () => {
       const effectiveAlgo = getEffectiveAlgorithm();
       for (let i = 0; i < 100; i++) {
         // New Seed for each game to ensure different board
-        const sim = new Simulator(finalDims, mines, effectiveAlgo as any);
+        const sim = createSimulator(finalDims, effectiveAlgo);
         // need to use set_seedd constructor or reset with new seed to ensure different board each time
         
         const finalStateJson = sim.runFullGame(); // WASM -> converts final state to JSON for easier JS handling
@@ -258,7 +266,15 @@ const handleCompareAlgorithms = useCallback(async () => {
     const algorithms = [
       { type: AlgorithmType.Greedy, label: "Greedy" },
       { type: AlgorithmType.ExactSolver, label: "Exact Solver" },
-      { type: AlgorithmType.SatSolver, label: "SAT Solver" }
+      { type: AlgorithmType.SatSolver, label: "SAT Solver" },
+      { type: AlgorithmType.GaussianSolver, label: "Gaussian Solver" },
+      { type: AlgorithmType.BeliefPropagation, label: "Belief Propagation" },
+      { type: AlgorithmType.Mcts, label: "MCTS" },
+      { type: AlgorithmType.Patterns, label: "Pattern Solver" },
+      { type: AlgorithmType.Ilp, label: "ILP Solver" },
+      { type: AlgorithmType.Cp, label: "CP Solver" },
+      { type: AlgorithmType.SharpSat, label: "#SAT Counter" },
+      { type: AlgorithmType.BeliefTracker, label: "Belief Tracker" }
     ];
 
     const allRecords: GameRecord[] = [];
@@ -278,16 +294,16 @@ const handleCompareAlgorithms = useCallback(async () => {
           : algo.type;
 
       for (let i = 0; i < gamesPerAlgo; i++) {
-        const sim = new Simulator(
+        const sim = createSimulator(
           gameConfig.useNDimensions ? gameConfig.dimensions : [gameConfig.height, gameConfig.width],
-          gameConfig.mines,
-          effectiveAlgo as any
+          effectiveAlgo
         );
 
         const res = sim.runFullGame();
 
         const record: GameRecord = {
           algorithm: algo.label,
+          mines: res.mines,
           win: res.game_won ? "TRUE" : "FALSE",
           clicks: res.total_clicks,
           guesses: res.total_guesses, 
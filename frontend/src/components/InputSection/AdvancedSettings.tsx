@@ -3,7 +3,8 @@ import type { GameConfig } from '../../types';
 import '../../styles/AdvancedSettings.css';
 interface AdvancedSettingsProps {
   config: GameConfig;
-  onChangeConfig: (key: 'width' | 'height' | 'mines', value: number) => void;
+  onChangeConfig: (key: 'width' | 'height' | 'mines' | 'density', value: number) => void;
+  onChangeMineMode: (mode: 'count' | 'density', bernoulli: boolean) => void;
   onChangeDimensionCount: (count: number) => void;
   onChangeDimensionSize: (index: number, size: number) => void;
 }
@@ -11,12 +12,15 @@ interface AdvancedSettingsProps {
 const AdvancedSettings: React.FC<AdvancedSettingsProps> = ({
   config,
   onChangeConfig,
+  onChangeMineMode,
   onChangeDimensionCount,
   onChangeDimensionSize
 }) => {
   const maxMines = config.useNDimensions && config.dimensions 
     ? config.dimensions.reduce((a, b) => a * b, 1) - 1
     : config.width * config.height - 1;
+  const mineMode = config.mineMode ?? 'count';
+  const bernoulli = config.bernoulli ?? false;
 
   return (
     <div className="advanced-settings">
@@ -55,11 +59,28 @@ const AdvancedSettings: React.FC<AdvancedSettingsProps> = ({
           </div>
         </div>
 
-      <div className="mines-control">
-        <label>Mines:</label>
-        <input type="number" value={config.mines} min={1} max={maxMines} onChange={e => onChangeConfig('mines', parseInt(e.target.value) || 10)} />
-        <span>Max: {maxMines}</span>
+      <div className="dimension-control">
+        <label>Mine Placement:</label>
+        <div className="dimension-buttons">
+          <button className={mineMode === 'count' ? 'active' : ''} onClick={() => onChangeMineMode('count', false)}>Exact Count</button>
+          <button className={mineMode === 'density' && !bernoulli ? 'active' : ''} onClick={() => onChangeMineMode('density', false)}>Density</button>
+          <button className={mineMode === 'density' && bernoulli ? 'active' : ''} onClick={() => onChangeMineMode('density', true)}>Bernoulli</button>
+        </div>
       </div>
+
+      {mineMode === 'count' ? (
+        <div className="mines-control">
+          <label>Mines:</label>
+          <input type="number" value={config.mines} min={1} max={maxMines} onChange={e => onChangeConfig('mines', parseInt(e.target.value) || 10)} />
+          <span>Max: {maxMines}</span>
+        </div>
+      ) : (
+        <div className="mines-control">
+          <label>Mine Density:</label>
+          <input type="number" value={config.density ?? 0.15} min={0.01} max={0.99} step={0.01} onChange={e => onChangeConfig('density', parseFloat(e.target.value) || 0.15)} />
+          <span>{bernoulli ? 'Each cell is a mine with this probability' : `About ${Math.round((config.density ?? 0.15) * (maxMines + 1))} mines`}</span>
+        </div>
+      )}
     </div>
   );
 };
@@ -50,10 +50,13 @@ const Menu: React.FC<MenuProps> = ({
     });
   };
   const handleChangeConfig = (
-    key: 'width' | 'height' | 'mines',
+    key: 'width' | 'height' | 'mines' | 'density',
     value: number
   ) => setConfig(prev => ({ ...prev, [key]: value }));
 
+  const handleChangeMineMode = (mode: 'count' | 'density', bernoulli: boolean) =>
+    setConfig(prev => ({ ...prev, mineMode: mode, bernoulli, density: prev.density ?? 0.15 }));
+
   const handleChangeDimensionCount = (count: number) =>
     setConfig(prev => ({
       ...prev,
@@ -143,6 +146,7 @@ const Menu: React.FC<MenuProps> = ({
             <AdvancedSettings
               config={config}
               onChangeConfig={handleChangeConfig}
+              onChangeMineMode={handleChangeMineMode}
               onChangeDimensionCount={handleChangeDimensionCount}
               onChangeDimensionSize={handleChangeDimensionSize}
             />
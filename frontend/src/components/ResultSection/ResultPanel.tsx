@@ -52,7 +52,10 @@ const downloadCSV = (gameRecords: GameRecord[], filename: string) => {
     r.completion
   ]);
 
-  const titleHeader = `--- Benchmark Results (${gameConfig.dimensions?.join('×')}, Mines: ${gameConfig.mines}) ---`;
+  const mineLabel = gameConfig.mineMode === 'density'
+    ? `Density: ${gameConfig.density ?? 0.15}${gameConfig.bernoulli ? ' (Bernoulli)' : ''}`
+    : `Mines: ${gameConfig.mines}`;
+  const titleHeader = `--- Benchmark Results (${gameConfig.dimensions?.join('×')}, ${mineLabel}) ---`;
   const csvContent = [
     titleHeader,
     headers.join(","),
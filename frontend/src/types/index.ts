@@ -6,6 +6,11 @@ export interface GameConfig {
   width: number;
   height: number;
   mines: number;
+  // 'density': mines as a fraction of all cells (Simulator.withDensity) instead of a fixed count
+  mineMode?: 'count' | 'density';
+  density?: number;
+  // independent coin per cell instead of an exact count (density mode only)
+  bernoulli?: boolean;
 }
 
 export interface Preset {
@@ -29,6 +29,14 @@ export enum AlgorithmType {
   SatSolver = 2,
   PartitionedSat = 3,   
   SatSolver4D = 4,      
+  GaussianSolver = 5,
+  BeliefPropagation = 6,
+  Mcts = 7,
+  Patterns = 8,
+  Ilp = 9,
+  Cp = 10,
+  SharpSat = 11,
+  BeliefTracker = 12,
 }
 
 export enum TspObjective {
@@ -41,6 +49,14 @@ export const AlgorithmInfo = [
   { value: AlgorithmType.Greedy, label: 'Greedy Solver', description: 'Local heuristic logic', implemented: true },
   { value: AlgorithmType.ExactSolver, label: 'Exact Solver', description: 'Exact constraint solver', implemented: true },
   { value: AlgorithmType.SatSolver, label: 'SAT Solver', description: 'Boolean satisfiability logic.', implemented: true },
+  { value: AlgorithmType.GaussianSolver, label: 'Gaussian Solver', description: 'Row-reduced clue matrix with bounds reasoning', implemented: true },
+  { value: AlgorithmType.BeliefPropagation, label: 'Belief Propagation', description: 'Approximate marginals by loopy message passing', implemented: true },
+  { value: AlgorithmType.Mcts, label: 'MCTS', description: 'Guesses scored by simulated games on sampled layouts', implemented: true },
+  { value: AlgorithmType.Patterns, label: 'Pattern Solver', description: 'Human-style local patterns from a pattern database', implemented: true },
+  { value: AlgorithmType.Ilp, label: 'ILP Solver', description: 'Integer programming by LP relaxation and branch-and-bound', implemented: true },
+  { value: AlgorithmType.Cp, label: 'CP Solver', description: 'Propagation and component search with exact solution counts', implemented: true },
+  { value: AlgorithmType.SharpSat, label: '#SAT Counter', description: 'Exact model counting over the clue CNF with component caching', implemented: true },
+  { value: AlgorithmType.BeliefTracker, label: 'Belief Tracker', description: 'Carries safe cells and probabilities across moving mines', implemented: true },
   
 ];
 export interface SimulationResult {
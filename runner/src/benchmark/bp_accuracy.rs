@@ -0,0 +1,99 @@
+/** 
+* [PLEASE NOTE] BpAccuracyRunner: how close belief propagation gets to the exact marginals.
+* the exact solver plays small boards; at every guess the exact probability map and the
+* belief propagation map of the same position are compared cell by cell, and by the cell
+* each of them would click (scored with the exact probabilities).
+**/
+use engine::algorithms::WasmAlgorithmType;
+use engine::error::MinesweeperError;
+use engine::Simulator;
+use serde::{Serialize, Deserialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GuessComparison {
+    pub board_dims: String,
+    pub seed: u64,
+    pub step: usize,
+    pub frontier: usize,
+    pub mean_abs_error: f64,
+    pub max_abs_error: f64,
+    /// exact mine probability of the cells bp would guess (averaged over its ties)
+    pub bp_pick_prob: f64,
+    /// exact mine probability of the best guess
+    pub exact_pick_prob: f64,
+}
+
+pub struct BpAccuracyRunner {
+    pub iterations: usize,
+    /// (dimensions, mines)
+    pub boards: Vec<(Vec<usize>, usize)>,
+}
+
+impl BpAccuracyRunner {
+    pub fn new(iterations: usize) -> Self {
+        Self {
+            iterations,
+            boards: vec![(vec![9, 9], 10), (vec![16, 16], 40), (vec![16, 30], 99)],
+        }
+    }
+
+    pub fn run_benchmarks(&self) -> Vec<GuessComparison> {
+        let mut results = Vec::new();
+        for (dims, mines) in &self.boards {
+            for i in 0..self.iterations {
+                let seed = i as u64;
+                match self.run_single_game(dims, *mines, seed) {
+                    Ok(mut res) => {
+                        println!("Completed: {:?} board (Seed: {}) with {} compared guesses", dims, seed, res.len());
+                        results.append(&mut res);
+                    }
+                    Err(e) => eprintln!("Skipped: {:?} board (Seed: {}): {}", dims, seed, e),
+                }
+            }
+        }
+        results
+    }
+
+    fn run_single_game(&self, dims: &[usize], mines: usize, seed: u64) -> Result<Vec<GuessComparison>, MinesweeperError> {
+        let mut sim = Simulator::new(dims.to_vec(), mines, WasmAlgorithmType::ExactSolver)?;
+        sim.set_seed(seed)?;
+
+        let mut comparisons = Vec::new();
+        while !sim.get_state_internal().game_over {
+            // both maps come straight from the board, the game itself is stepped once per move.
+            // the opening click has nothing to compare, and a zero in the exact map is a safe
+            // deduction rather than a guess
+            if sim.get_steps() > 0 {
+                let exact = sim.mine_probabilities();
+                let exact_pick = sim.safest_cells(&exact);
+                let is_guess = exact_pick.first().is_some_and(|&c| exact.cell_probs[c] > 1e-12);
+                if exact.exact && is_guess {
+                    let bp = sim.belief_propagation_marginals();
+                    let errors: Vec<f64> = exact.frontier.iter().map(|&c| (exact.cell_probs[c] - bp.cell_probs[c]).abs()).collect();
+                    let bp_pick = sim.safest_cells(&bp);
+                    comparisons.push(GuessComparison {
+                        board_dims: dims.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("x"),
+                        seed,
+                        step: sim.get_steps(),
+                        frontier: exact.frontier.len(),
+                        mean_abs_error: errors.iter().sum::<f64>() / errors.len().max(1) as f64,
+                        max_abs_error: errors.iter().copied().fold(0.0, f64::max),
+                        bp_pick_prob: bp_pick.iter().map(|&c| exact.cell_probs[c]).sum::<f64>() / bp_pick.len().max(1) as f64,
+                        exact_pick_prob: exact_pick.first().map_or(0.0, |&c| exact.cell_probs[c]),
+                    });
+                }
+            }
+            if !sim.run_step()? { break; }
+        }
+        Ok(comparisons)
+    }
+
+    pub fn to_csv(results: &[GuessComparison]) -> String {
+        let mut csv = String::from("dims,seed,step,frontier,mean_abs_error,max_abs_error,bp_pick_prob,exact_pick_prob\n");
+        for r in results {
+            csv.push_str(&format!("{},{},{},{},{:.4},{:.4},{:.4},{:.4}\n",
+                r.board_dims, r.seed, r.step, r.frontier, r.mean_abs_error, r.max_abs_error, r.bp_pick_prob, r.exact_pick_prob));
+        }
+        csv
+    }
+}
@@ -0,0 +1,125 @@
+/**
+* [PLEASE NOTE] DynamicMinesRunner: stateless solvers against the belief-tracking agent when mines move.
+* every solver plays the same seeds, so layouts, opening click and the seeded mine moves only differ
+* once the solvers choose different cells.
+**/
+use engine::algorithms::WasmAlgorithmType;
+use engine::error::MinesweeperError;
+use engine::Simulator;
+use serde::{Serialize, Deserialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DynamicResult {
+    pub algorithm: String,
+    pub board_dims: String,
+    pub interval: usize,
+    pub move_probability: f64,
+    pub seed: u64,
+    pub win: bool,
+    pub total_clicks: usize,
+    pub guesses_made: usize,
+    pub mine_moves: usize,
+    pub completion_rate: f64,
+}
+
+/// per solver, board and move probability: games played and games won
+pub struct DynamicSummary {
+    pub algorithm: String,
+    pub board_dims: String,
+    pub move_probability: f64,
+    pub games: usize,
+    pub wins: usize,
+}
+
+pub struct DynamicMinesRunner {
+    pub iterations: usize,
+    pub boards: Vec<(Vec<usize>, usize)>,
+    /// mines may move after every `interval` clicks
+    pub interval: usize,
+    pub move_probabilities: Vec<f64>,
+    pub algorithms: Vec<WasmAlgorithmType>,
+}
+
+impl DynamicMinesRunner {
+    pub fn new(iterations: usize) -> Self {
+        Self {
+            iterations,
+            boards: vec![(vec![9, 9], 10), (vec![16, 16], 40)],
+            interval: 5,
+            move_probabilities: vec![0.0, 0.1, 0.3],
+            algorithms: vec![
+                WasmAlgorithmType::ExactSolver,
+                WasmAlgorithmType::PartitionedSat,
+                WasmAlgorithmType::GaussianSolver,
+                WasmAlgorithmType::BeliefTracker,
+            ],
+        }
+    }
+
+    pub fn run_benchmarks(&self) -> Vec<DynamicResult> {
+        let mut results = Vec::new();
+        for (dims, mines) in &self.boards {
+            for &move_probability in &self.move_probabilities {
+                for i in 0..self.iterations {
+                    let seed = i as u64;
+                    for &algo in &self.algorithms {
+                        match self.run_single_game(dims, *mines, algo, move_probability, seed) {
+                            Ok(res) => {
+                                println!("Completed: {} on {:?} p={} (Seed: {})", algo.as_str(), dims, move_probability, seed);
+                                results.push(res);
+                            }
+                            Err(e) => eprintln!("Skipped: {} on {:?} board (Seed: {}): {}", algo.as_str(), dims, seed, e),
+                        }
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    fn run_single_game(&self, dims: &[usize], mines: usize, algo: WasmAlgorithmType, move_probability: f64, seed: u64) -> Result<DynamicResult, MinesweeperError> {
+        let mut sim = Simulator::new(dims.to_vec(), mines, algo)?;
+        sim.set_dynamic_mines(self.interval, move_probability);
+        sim.set_seed(seed)?;
+        sim.run_full_game()?;
+
+        let state = sim.get_state_internal();
+        Ok(DynamicResult {
+            algorithm: algo.as_str().to_string(),
+            board_dims: dims.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("x"),
+            interval: self.interval,
+            move_probability,
+            seed,
+            win: state.game_won,
+            total_clicks: sim.get_steps(),
+            guesses_made: state.total_guesses,
+            mine_moves: state.total_mine_moves,
+            completion_rate: (state.total_revealed as f64 / (state.total_cells - state.mines) as f64) * 100.0,
+        })
+    }
+
+    pub fn summarize(results: &[DynamicResult]) -> Vec<DynamicSummary> {
+        let mut summaries: Vec<DynamicSummary> = Vec::new();
+        for r in results {
+            let index = match summaries.iter().position(|s| s.algorithm == r.algorithm && s.board_dims == r.board_dims && s.move_probability == r.move_probability) {
+                Some(index) => index,
+                None => {
+                    summaries.push(DynamicSummary { algorithm: r.algorithm.clone(), board_dims: r.board_dims.clone(), move_probability: r.move_probability, games: 0, wins: 0 });
+                    summaries.len() - 1
+                }
+            };
+            summaries[index].games += 1;
+            summaries[index].wins += r.win as usize;
+        }
+        summaries
+    }
+
+    pub fn to_csv(results: &[DynamicResult]) -> String {
+        let mut csv = String::from("algorithm,dims,interval,move_probability,seed,win,clicks,guesses,mine_moves,completion\n");
+        for r in results {
+            csv.push_str(&format!("{},{},{},{},{},{},{},{},{},{:.2}\n",
+                r.algorithm, r.board_dims, r.interval, r.move_probability, r.seed, r.win, r.total_clicks, r.guesses_made, r.mine_moves, r.completion_rate));
+        }
+        csv
+    }
+}
\ No newline at end of file
@@ -0,0 +1,127 @@
+/**
+* [PLEASE NOTE] IlpCheckRunner: cross-checks the pure-rust branch-and-bound solver against SCIP.
+* the ilp solver plays the games; at every step after the opening click both solvers are asked
+* for their candidates on the same board (global mine count on), and the answers must match:
+* exactly the same safe cells for a deduction. for a guess the two solvers' marginals may differ
+* in the last bits and break ties differently, so every cell either picks must be within
+* `GUESS_TOLERANCE` of the safest cell by the exact marginals of the position.
+**/
+use engine::algorithms::WasmAlgorithmType;
+use engine::error::MinesweeperError;
+use engine::Simulator;
+use std::time::Instant;
+use serde::{Serialize, Deserialize};
+
+/// largest gap in exact mine probability between a guessed cell and the safest one
+const GUESS_TOLERANCE: f64 = 1e-9;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StepCheck {
+    pub board_dims: String,
+    pub seed: u64,
+    pub step: usize,
+    pub ilp_guess: bool,
+    pub scip_guess: bool,
+    /// exact mine probability of the riskiest cell each solver proposed (guesses only)
+    pub ilp_pick_prob: Option<f64>,
+    pub scip_pick_prob: Option<f64>,
+    /// same guess flag; the same safe cells, or guesses all within the tolerance of the safest cell
+    pub agree: bool,
+    pub ilp_time_us: u128,
+    pub scip_time_us: u128,
+}
+
+pub struct IlpCheckRunner {
+    pub iterations: usize,
+    /// (dimensions, mines)
+    pub boards: Vec<(Vec<usize>, usize)>,
+}
+
+impl IlpCheckRunner {
+    pub fn new(iterations: usize) -> Self {
+        Self {
+            iterations,
+            boards: vec![(vec![9, 9], 10), (vec![16, 16], 40), (vec![16, 30], 99), (vec![6, 8, 8], 40)],
+        }
+    }
+
+    pub fn run_benchmarks(&self) -> Vec<StepCheck> {
+        let mut results = Vec::new();
+        for (dims, mines) in &self.boards {
+            for i in 0..self.iterations {
+                let seed = i as u64;
+                match self.run_single_game(dims, *mines, seed) {
+                    Ok(mut res) => {
+                        let mismatches = res.iter().filter(|r| !r.agree).count();
+                        println!("Completed: {:?} board (Seed: {}) with {} checked steps, {} mismatches", dims, seed, res.len(), mismatches);
+                        results.append(&mut res);
+                    }
+                    Err(e) => eprintln!("Skipped: {:?} board (Seed: {}): {}", dims, seed, e),
+                }
+            }
+        }
+        results
+    }
+
+    fn run_single_game(&self, dims: &[usize], mines: usize, seed: u64) -> Result<Vec<StepCheck>, MinesweeperError> {
+        let mut sim = Simulator::new(dims.to_vec(), mines, WasmAlgorithmType::Ilp)?;
+        sim.set_global_mine_count(true);
+        sim.set_seed(seed)?;
+
+        let mut checks = Vec::new();
+        while !sim.get_state_internal().game_over {
+            // the opening click is the agent's, not a solver's
+            if sim.get_steps() > 0 {
+                let start_time = Instant::now();
+                let ilp = sim.solver_candidates(WasmAlgorithmType::Ilp);
+                let ilp_time_us = start_time.elapsed().as_micros();
+                let start_time = Instant::now();
+                let scip = sim.solver_candidates(WasmAlgorithmType::SCIPSolver);
+                let scip_time_us = start_time.elapsed().as_micros();
+
+                let (mut ilp_pick_prob, mut scip_pick_prob) = (None, None);
+                let agree = if ilp.is_guess != scip.is_guess {
+                    false
+                } else if ilp.is_guess {
+                    let exact = sim.mine_probabilities();
+                    let safest = sim.safest_cells(&exact).first().map_or(0.0, |&c| exact.cell_probs[c]);
+                    let riskiest = |cells: &[usize]| cells.iter().map(|&c| exact.cell_probs[c]).fold(0.0, f64::max);
+                    let (ilp_p, scip_p) = (riskiest(&ilp.candidates), riskiest(&scip.candidates));
+                    (ilp_pick_prob, scip_pick_prob) = (Some(ilp_p), Some(scip_p));
+                    !ilp.candidates.is_empty() && !scip.candidates.is_empty()
+                        && ilp_p - safest <= GUESS_TOLERANCE && scip_p - safest <= GUESS_TOLERANCE
+                } else {
+                    let mut ilp_cells = ilp.candidates.clone();
+                    let mut scip_cells = scip.candidates.clone();
+                    ilp_cells.sort_unstable();
+                    scip_cells.sort_unstable();
+                    ilp_cells == scip_cells
+                };
+                checks.push(StepCheck {
+                    board_dims: dims.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("x"),
+                    seed,
+                    step: sim.get_steps(),
+                    ilp_guess: ilp.is_guess,
+                    scip_guess: scip.is_guess,
+                    ilp_pick_prob,
+                    scip_pick_prob,
+                    agree,
+                    ilp_time_us,
+                    scip_time_us,
+                });
+            }
+            if !sim.run_step()? { break; }
+        }
+        Ok(checks)
+    }
+
+    pub fn to_csv(results: &[StepCheck]) -> String {
+        let mut csv = String::from("dims,seed,step,ilp_guess,scip_guess,ilp_pick_prob,scip_pick_prob,agree,ilp_time_us,scip_time_us\n");
+        let prob = |p: Option<f64>| p.map_or(String::new(), |p| format!("{:.6}", p));
+        for r in results {
+            csv.push_str(&format!("{},{},{},{},{},{},{},{},{},{}\n",
+                r.board_dims, r.seed, r.step, r.ilp_guess, r.scip_guess, prob(r.ilp_pick_prob), prob(r.scip_pick_prob), r.agree, r.ilp_time_us, r.scip_time_us));
+        }
+        csv
+    }
+}
@@ -0,0 +1,113 @@
+/** 
+* [PLEASE NOTE] MctsGainRunner: win rate of the mcts solver against the exact solver it builds on.
+* both play the same seeds, so the boards and the first click are identical and every game is a
+* paired comparison: only the guesses differ.
+**/
+use engine::algorithms::WasmAlgorithmType;
+use engine::error::MinesweeperError;
+use engine::Simulator;
+use std::time::Instant;
+use serde::{Serialize, Deserialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GainResult {
+    pub board_dims: String,
+    pub seed: u64,
+    pub exact_win: bool,
+    pub mcts_win: bool,
+    pub exact_time_ms: u128,
+    pub mcts_time_ms: u128,
+}
+
+/// per board: games, exact wins, mcts wins, games only mcts won, games only exact won
+pub struct GainSummary {
+    pub board_dims: String,
+    pub games: usize,
+    pub exact_wins: usize,
+    pub mcts_wins: usize,
+    pub mcts_only: usize,
+    pub exact_only: usize,
+}
+
+pub struct MctsGainRunner {
+    pub iterations: usize,
+    pub boards: Vec<(Vec<usize>, usize)>,
+    /// playouts per guess (0 = the solver's default)
+    pub playouts: usize,
+}
+
+impl MctsGainRunner {
+    pub fn new(iterations: usize) -> Self {
+        Self {
+            iterations,
+            boards: vec![(vec![9, 9], 10), (vec![16, 16], 40), (vec![16, 30], 99)],
+            playouts: 0,
+        }
+    }
+
+    pub fn run_benchmarks(&self) -> Vec<GainResult> {
+        let mut results = Vec::new();
+        for (dims, mines) in &self.boards {
+            for i in 0..self.iterations {
+                let seed = i as u64;
+                let exact = self.run_single_game(dims, *mines, WasmAlgorithmType::ExactSolver, seed);
+                let mcts = self.run_single_game(dims, *mines, WasmAlgorithmType::Mcts, seed);
+                match (exact, mcts) {
+                    (Ok((exact_win, exact_time_ms)), Ok((mcts_win, mcts_time_ms))) => {
+                        println!("Completed: {:?} (Seed: {}) exact {} / mcts {} in {} ms", dims, seed, exact_win, mcts_win, mcts_time_ms);
+                        results.push(GainResult {
+                            board_dims: dims.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("x"),
+                            seed,
+                            exact_win,
+                            mcts_win,
+                            exact_time_ms,
+                            mcts_time_ms,
+                        });
+                    }
+                    (Err(e), _) | (_, Err(e)) => eprintln!("Skipped: {:?} board (Seed: {}): {}", dims, seed, e),
+                }
+            }
+        }
+        results
+    }
+
+    /// (won, time in ms)
+    fn run_single_game(&self, dims: &[usize], mines: usize, algo: WasmAlgorithmType, seed: u64) -> Result<(bool, u128), MinesweeperError> {
+        let mut sim = Simulator::new(dims.to_vec(), mines, algo)?;
+        sim.set_playout_budget(self.playouts);
+        sim.set_seed(seed)?;
+
+        let start_time = Instant::now();
+        sim.run_full_game()?;
+        Ok((sim.get_state_internal().game_won, start_time.elapsed().as_millis()))
+    }
+
+    pub fn summarize(results: &[GainResult]) -> Vec<GainSummary> {
+        let mut summaries: Vec<GainSummary> = Vec::new();
+        for r in results {
+            let index = match summaries.iter().position(|s| s.board_dims == r.board_dims) {
+                Some(index) => index,
+                None => {
+                    summaries.push(GainSummary { board_dims: r.board_dims.clone(), games: 0, exact_wins: 0, mcts_wins: 0, mcts_only: 0, exact_only: 0 });
+                    summaries.len() - 1
+                }
+            };
+            let s = &mut summaries[index];
+            s.games += 1;
+            s.exact_wins += r.exact_win as usize;
+            s.mcts_wins += r.mcts_win as usize;
+            s.mcts_only += (r.mcts_win && !r.exact_win) as usize;
+            s.exact_only += (r.exact_win && !r.mcts_win) as usize;
+        }
+        summaries
+    }
+
+    pub fn to_csv(results: &[GainResult]) -> String {
+        let mut csv = String::from("dims,seed,exact_win,mcts_win,exact_time_ms,mcts_time_ms\n");
+        for r in results {
+            csv.push_str(&format!("{},{},{},{},{},{}\n",
+                r.board_dims, r.seed, r.exact_win, r.mcts_win, r.exact_time_ms, r.mcts_time_ms));
+        }
+        csv
+    }
+}
@@ -1 +1,7 @@
 pub mod metaheuristic;
+pub mod nd_scaling;
+pub mod bp_accuracy;
+pub mod mcts_gain;
+pub mod ilp_check;
+pub mod dynamic_mines;
+pub mod scip_count;
@@ -0,0 +1,98 @@
+/** 
+* [PLEASE NOTE] NdScalingRunner: compares the deduction solvers on large N-dimensional boards.
+* 4D cells have 80 neighbours, which is where the subset encoding and pairwise rules stop scaling,
+* so the sat solvers run with the totalizer encoding here.
+**/
+use engine::algorithms::{WasmAlgorithmType, CardinalityEncoding};
+use engine::board::{MineSpec, Placement};
+use engine::error::MinesweeperError;
+use engine::Simulator;
+use std::time::Instant;
+use serde::{Serialize, Deserialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScalingResult {
+    pub algorithm: String,
+    pub board_dims: String,
+    pub mines: usize,
+    pub win: bool,
+    pub time_ms: u128,
+    pub guesses_made: usize,
+    pub completion_rate: f64,
+    pub seed: u64,
+}
+
+pub struct NdScalingRunner {
+    pub iterations: usize,
+    pub boards: Vec<Vec<usize>>,
+    pub density: f64,
+    pub algorithms: Vec<WasmAlgorithmType>,
+}
+
+impl NdScalingRunner {
+    pub fn new(iterations: usize) -> Self {
+        Self {
+            iterations,
+            boards: vec![vec![4, 4, 4, 4], vec![5, 5, 5, 5]],
+            density: 0.04,
+            algorithms: vec![
+                WasmAlgorithmType::GaussianSolver,
+                WasmAlgorithmType::ExactSolver,
+                WasmAlgorithmType::PartitionedSat,
+                WasmAlgorithmType::GlobalSat,
+            ],
+        }
+    }
+
+    pub fn run_benchmarks(&self) -> Vec<ScalingResult> {
+        let mut results = Vec::new();
+        for dims in &self.boards {
+            for i in 0..self.iterations {
+                let seed = i as u64;
+                for &algo in &self.algorithms {
+                    match self.run_single_sim(dims, algo, seed) {
+                        Ok(res) => {
+                            println!("Completed: {} on {} board (Seed: {}) in {} ms", algo.as_str(), res.board_dims, seed, res.time_ms);
+                            results.push(res);
+                        }
+                        Err(e) => eprintln!("Skipped: {} on {:?} board (Seed: {}): {}", algo.as_str(), dims, seed, e),
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    fn run_single_sim(&self, dims: &[usize], algo: WasmAlgorithmType, seed: u64) -> Result<ScalingResult, MinesweeperError> {
+        let mut sim = Simulator::with_spec(dims.to_vec(), MineSpec::Density(self.density), Placement::ExactCount, algo)?;
+        sim.set_cardinality_encoding(CardinalityEncoding::Totalizer);
+        sim.set_seed(seed)?;
+
+        // one solve per move (the agent keeps its opening click); the board counts the guesses
+        let start_time = Instant::now();
+        while !sim.get_state_internal().game_over {
+            if !sim.run_step()? { break; }
+        }
+
+        let board_state = sim.get_state_internal();
+        Ok(ScalingResult {
+            algorithm: algo.as_str().to_string(),
+            board_dims: dims.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("x"),
+            mines: board_state.mines,
+            win: board_state.game_won,
+            time_ms: start_time.elapsed().as_millis(),
+            guesses_made: board_state.total_guesses,
+            completion_rate: (board_state.total_revealed as f64 / (board_state.total_cells - board_state.mines) as f64) * 100.0,
+            seed,
+        })
+    }
+
+    pub fn to_csv(results: &[ScalingResult]) -> String {
+        let mut csv = String::from("algorithm,dims,mines,seed,win,time_ms,guesses,completion\n");
+        for r in results {
+            csv.push_str(&format!("{},{},{},{},{},{},{},{:.2}\n",
+                r.algorithm, r.board_dims, r.mines, r.seed, r.win, r.time_ms, r.guesses_made, r.completion_rate));
+        }
+        csv
+    }
+}
@@ -0,0 +1,111 @@
+/**
+* [PLEASE NOTE] ScipCountRunner: before/after timing of the SCIP solver's counted marginals.
+* the exact solver plays the games (global mine count on); at every guess both ways of counting
+* run on the same board. "per cell" is the older one SCIPcount per mine total plus one per cell
+* fixed to a mine, "collected" one SCIPcount per total with the per-cell counts read from the
+* collected sparse solutions. a count that hits a limit falls back to the shared probability
+* engine in the solver, here it is recorded as a fallback. the largest gap to the exact
+* marginals is kept as a check that both count the same layouts.
+**/
+use engine::algorithms::scip_solver::CountMethod;
+use engine::algorithms::probability::ProbabilityMap;
+use engine::algorithms::WasmAlgorithmType;
+use engine::error::MinesweeperError;
+use engine::Simulator;
+use std::time::Instant;
+use serde::{Serialize, Deserialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CountCheck {
+    pub board_dims: String,
+    pub seed: u64,
+    pub step: usize,
+    pub per_cell_time_us: u128,
+    pub collected_time_us: u128,
+    pub per_cell_fallback: bool,
+    pub collected_fallback: bool,
+    /// largest difference to the exact marginals over the hidden cells (counted maps only)
+    pub per_cell_error: Option<f64>,
+    pub collected_error: Option<f64>,
+}
+
+pub struct ScipCountRunner {
+    pub iterations: usize,
+    /// (dimensions, mines)
+    pub boards: Vec<(Vec<usize>, usize)>,
+}
+
+impl ScipCountRunner {
+    pub fn new(iterations: usize) -> Self {
+        Self {
+            iterations,
+            boards: vec![(vec![9, 9], 10), (vec![16, 16], 40), (vec![16, 30], 99)],
+        }
+    }
+
+    pub fn run_benchmarks(&self) -> Vec<CountCheck> {
+        let mut results = Vec::new();
+        for (dims, mines) in &self.boards {
+            for i in 0..self.iterations {
+                let seed = i as u64;
+                match self.run_single_game(dims, *mines, seed) {
+                    Ok(mut res) => {
+                        println!("Completed: {:?} board (Seed: {}) with {} counted guesses", dims, seed, res.len());
+                        results.append(&mut res);
+                    }
+                    Err(e) => eprintln!("Skipped: {:?} board (Seed: {}): {}", dims, seed, e),
+                }
+            }
+        }
+        results
+    }
+
+    fn run_single_game(&self, dims: &[usize], mines: usize, seed: u64) -> Result<Vec<CountCheck>, MinesweeperError> {
+        let mut sim = Simulator::new(dims.to_vec(), mines, WasmAlgorithmType::ExactSolver)?;
+        sim.set_global_mine_count(true);
+        sim.set_seed(seed)?;
+
+        let mut checks = Vec::new();
+        while !sim.get_state_internal().game_over {
+            // the opening click is the agent's; count only where no cell is provably safe
+            let exact = sim.mine_probabilities();
+            let guess = sim.get_steps() > 0
+                && sim.safest_cells(&exact).first().is_some_and(|&c| exact.cell_probs[c] > 1e-12);
+            if guess {
+                let error = |map: &ProbabilityMap| exact.frontier.iter()
+                    .map(|&c| (map.cell_probs[c] - exact.cell_probs[c]).abs())
+                    .fold(0.0, f64::max);
+                let start_time = Instant::now();
+                let per_cell = sim.scip_counted_probabilities(CountMethod::PerCell);
+                let per_cell_time_us = start_time.elapsed().as_micros();
+                let start_time = Instant::now();
+                let collected = sim.scip_counted_probabilities(CountMethod::Collected);
+                let collected_time_us = start_time.elapsed().as_micros();
+                checks.push(CountCheck {
+                    board_dims: dims.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("x"),
+                    seed,
+                    step: sim.get_steps(),
+                    per_cell_time_us,
+                    collected_time_us,
+                    per_cell_fallback: per_cell.is_none(),
+                    collected_fallback: collected.is_none(),
+                    per_cell_error: per_cell.as_ref().map(error),
+                    collected_error: collected.as_ref().map(error),
+                });
+            }
+            if !sim.run_step()? { break; }
+        }
+        Ok(checks)
+    }
+
+    pub fn to_csv(results: &[CountCheck]) -> String {
+        let mut csv = String::from("dims,seed,step,per_cell_time_us,collected_time_us,per_cell_fallback,collected_fallback,per_cell_error,collected_error\n");
+        let error = |e: Option<f64>| e.map_or(String::new(), |e| format!("{:.2e}", e));
+        for r in results {
+            csv.push_str(&format!("{},{},{},{},{},{},{},{},{}\n",
+                r.board_dims, r.seed, r.step, r.per_cell_time_us, r.collected_time_us,
+                r.per_cell_fallback, r.collected_fallback, error(r.per_cell_error), error(r.collected_error)));
+        }
+        csv
+    }
+}
@@ -1,4 +1,4 @@
-/** 
+/* 
 * [PLEASE NOTE] I seperated benchmarking and metaheuristic testing into a separate runner crate 
 * to avoid error with frontend and to keep the engine focused on core logic and algorithms.
 * This test codes cannot be used with frontend, because of the scip and wasm dependencies, 
@@ -13,7 +13,32 @@
 
 mod benchmark;
 use benchmark::metaheuristic::MetaHeuristicRunner;
+use benchmark::nd_scaling::NdScalingRunner;
+use benchmark::bp_accuracy::BpAccuracyRunner;
+use benchmark::mcts_gain::MctsGainRunner;
+use benchmark::ilp_check::IlpCheckRunner;
+use benchmark::dynamic_mines::DynamicMinesRunner;
+use benchmark::scip_count::ScipCountRunner;
+
 fn main() {
+    match std::env::args().nth(1).as_deref() {
+        // `cargo run -- nd` runs the n-dimensional scaling comparison instead of the tsp matrix
+        Some("nd") => run_nd_scaling(),
+        // `cargo run -- bp` compares belief propagation guesses with the exact marginals
+        Some("bp") => run_bp_accuracy(),
+        // `cargo run -- mcts` measures the mcts solver's win-rate gain over the exact solver
+        Some("mcts") => run_mcts_gain(),
+        // `cargo run -- ilp` cross-checks the pure-rust ilp solver against scip
+        Some("ilp") => run_ilp_check(),
+        // `cargo run -- dynamic` compares stateless solvers with the belief tracker when mines move
+        Some("dynamic") => run_dynamic_mines(),
+        // `cargo run -- scipcount` times scip's per-cell counting against the collected counts
+        Some("scipcount") => run_scip_count(),
+        _ => run_metaheuristic(),
+    }
+}
+
+fn run_metaheuristic() {
     let runner = MetaHeuristicRunner::new(30); // change iteration/configuration number here
 
     println!("configurations: 4 board sizes x 5 algorithms x 3 tsp objectives");
@@ -33,4 +58,101 @@ fn main() {
     println!("--- summary ---");
     println!("total games played: {}", results.len());
     println!("overall win rate: {:.2}%", win_rate);
-}
\ No newline at end of file
+}
+
+fn run_nd_scaling() {
+    let runner = NdScalingRunner::new(5);
+    println!("configurations: {} n-d boards x {} algorithms", runner.boards.len(), runner.algorithms.len());
+
+    let results = runner.run_benchmarks();
+    println!("\n--- nd scaling results ---\n");
+    println!("{}", NdScalingRunner::to_csv(&results));
+}
+
+fn run_bp_accuracy() {
+    let runner = BpAccuracyRunner::new(20);
+    println!("configurations: {} boards, exact solver games, bp compared at every guess", runner.boards.len());
+
+    let results = runner.run_benchmarks();
+    println!("\n--- bp accuracy results ---\n");
+    println!("{}", BpAccuracyRunner::to_csv(&results));
+
+    let n = results.len().max(1) as f64;
+    let regret: f64 = results.iter().map(|r| r.bp_pick_prob - r.exact_pick_prob).sum::<f64>() / n;
+    let same = results.iter().filter(|r| (r.bp_pick_prob - r.exact_pick_prob).abs() < 1e-9).count();
+    println!("--- summary ---");
+    println!("compared guesses: {}", results.len());
+    println!("mean abs marginal error: {:.4}", results.iter().map(|r| r.mean_abs_error).sum::<f64>() / n);
+    println!("bp guess as safe as the exact one: {:.2}%", same as f64 / n * 100.0);
+    println!("mean extra mine probability of the bp guess: {:.4}", regret);
+}
+
+fn run_mcts_gain() {
+    let runner = MctsGainRunner::new(100);
+    println!("configurations: {} boards, exact solver vs mcts on the same seeds", runner.boards.len());
+
+    let results = runner.run_benchmarks();
+    println!("\n--- mcts gain results ---\n");
+    println!("{}", MctsGainRunner::to_csv(&results));
+
+    println!("--- summary ---");
+    for s in MctsGainRunner::summarize(&results) {
+        let n = s.games.max(1) as f64;
+        println!("{}: exact {:.2}% / mcts {:.2}% (gain {:+.2} points, mcts only {} / exact only {} of {} games)",
+            s.board_dims, s.exact_wins as f64 / n * 100.0, s.mcts_wins as f64 / n * 100.0,
+            (s.mcts_wins as f64 - s.exact_wins as f64) / n * 100.0, s.mcts_only, s.exact_only, s.games);
+    }
+}
+
+fn run_ilp_check() {
+    let runner = IlpCheckRunner::new(20);
+    println!("configurations: {} boards, ilp solver games, scip asked at every step", runner.boards.len());
+
+    let results = runner.run_benchmarks();
+    println!("\n--- ilp cross-check results ---\n");
+    println!("{}", IlpCheckRunner::to_csv(&results));
+
+    let n = results.len().max(1) as f64;
+    let mismatches = results.iter().filter(|r| !r.agree).count();
+    println!("--- summary ---");
+    println!("checked steps: {}", results.len());
+    println!("mismatches: {}", mismatches);
+    println!("mean ilp time: {:.1} us", results.iter().map(|r| r.ilp_time_us as f64).sum::<f64>() / n);
+    println!("mean scip time: {:.1} us", results.iter().map(|r| r.scip_time_us as f64).sum::<f64>() / n);
+}
+
+fn run_dynamic_mines() {
+    let runner = DynamicMinesRunner::new(50);
+    println!("configurations: {} boards x {} move probabilities x {} algorithms, mines move every {} clicks",
+        runner.boards.len(), runner.move_probabilities.len(), runner.algorithms.len(), runner.interval);
+
+    let results = runner.run_benchmarks();
+    println!("\n--- dynamic mines results ---\n");
+    println!("{}", DynamicMinesRunner::to_csv(&results));
+
+    println!("--- summary ---");
+    for s in DynamicMinesRunner::summarize(&results) {
+        println!("{} on {} (p = {}): {:.2}% of {} games won",
+            s.algorithm, s.board_dims, s.move_probability, s.wins as f64 / s.games.max(1) as f64 * 100.0, s.games);
+    }
+}
+
+fn run_scip_count() {
+    let runner = ScipCountRunner::new(10);
+    println!("configurations: {} boards, exact solver games, scip counts compared at every guess", runner.boards.len());
+
+    let results = runner.run_benchmarks();
+    println!("\n--- scip count results ---\n");
+    println!("{}", ScipCountRunner::to_csv(&results));
+
+    let n = results.len().max(1) as f64;
+    let mean = |time: fn(&benchmark::scip_count::CountCheck) -> u128| results.iter().map(|r| time(r) as f64).sum::<f64>() / n;
+    let (before, after) = (mean(|r| r.per_cell_time_us), mean(|r| r.collected_time_us));
+    let worst = |error: fn(&benchmark::scip_count::CountCheck) -> Option<f64>| results.iter().filter_map(error).fold(0.0, f64::max);
+    println!("--- summary ---");
+    println!("counted guesses: {}", results.len());
+    println!("mean time per guess: per cell {:.1} us -> collected {:.1} us ({:.1}x)", before, after, before / after.max(1.0));
+    println!("fallbacks: per cell {} / collected {}",
+        results.iter().filter(|r| r.per_cell_fallback).count(), results.iter().filter(|r| r.collected_fallback).count());
+    println!("largest marginal error: per cell {:.2e} / collected {:.2e}", worst(|r| r.per_cell_error), worst(|r| r.collected_error));
+}